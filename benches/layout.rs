@@ -0,0 +1,130 @@
+//! Compares the layered layout of the nodes with the subtree-blocked one on a tree larger than the CPU caches
+//! and (with the std feature) on a tree in a file read a page at a time:
+//! cargo bench --bench layout --features std
+#![allow(incomplete_features)]
+#![feature(generic_const_exprs)]
+#![feature(test)]
+
+extern crate test;
+
+use test::{black_box, Bencher};
+
+use merkle_heapless::layout::{Blocked, LaidOut, Layered, Layout};
+use merkle_heapless::prefixed::Prefixed;
+use merkle_heapless::store::{NodeStore, StoredTree};
+use merkle_heapless::{num_of_prefixed, Error};
+
+#[macro_use]
+#[allow(dead_code)]
+#[path = "../tests/src/fixture.rs"]
+mod fixture;
+
+// nodes of 96 bytes
+std_hash!(StdHash, Wrapped32, 32);
+
+const ARITY: usize = 2;
+// 2^21 - 1 nodes of 96 bytes
+const HEIGHT: usize = 21;
+
+// nodes on the heap, too many for the stack
+struct Heap(Vec<Prefixed<ARITY, StdHash>>);
+
+impl NodeStore<ARITY, StdHash> for Heap {
+    fn read(&self, index: usize) -> Result<Prefixed<ARITY, StdHash>, Error> {
+        Ok(self.0[index])
+    }
+    fn write(&mut self, index: usize, node: &Prefixed<ARITY, StdHash>) -> Result<(), Error> {
+        self.0[index] = *node;
+        Ok(())
+    }
+}
+
+fn words() -> Vec<[u8; 4]> {
+    (0..1 << 19).map(|i: u32| i.to_le_bytes()).collect()
+}
+
+fn heap() -> Heap {
+    Heap(vec![Default::default(); num_of_prefixed!(ARITY, HEIGHT)])
+}
+
+// leaves spread over the tree
+fn leaves() -> impl Iterator<Item = usize> {
+    (0..256).map(|i: usize| i.wrapping_mul(2654435761) % (1 << HEIGHT))
+}
+
+macro_rules! bench_layout {
+    ($layout:ty, $generate_proofs:ident, $replace:ident) => {
+        #[bench]
+        fn $generate_proofs(b: &mut Bencher) {
+            let store = LaidOut::new(heap(), <$layout>::new::<ARITY>(HEIGHT));
+            let tree =
+                StoredTree::<ARITY, HEIGHT, StdHash, 16, _>::try_from(store, &words()).unwrap();
+            b.iter(|| {
+                for leaf in leaves() {
+                    black_box(tree.generate_proof(leaf).unwrap());
+                }
+            });
+        }
+
+        #[bench]
+        fn $replace(b: &mut Bencher) {
+            let store = LaidOut::new(heap(), <$layout>::new::<ARITY>(HEIGHT));
+            let mut tree =
+                StoredTree::<ARITY, HEIGHT, StdHash, 16, _>::try_from(store, &words()).unwrap();
+            b.iter(|| {
+                for leaf in leaves() {
+                    tree.try_replace(leaf, b"kiwi").unwrap();
+                }
+            });
+        }
+    };
+}
+
+bench_layout!(Layered, generate_proofs_layered, replace_layered);
+bench_layout!(Blocked<4>, generate_proofs_blocked, replace_blocked);
+
+#[cfg(feature = "std")]
+mod file {
+    use super::*;
+    use merkle_heapless::store::FileStore;
+
+    const HEIGHT: usize = 18;
+
+    // a file in the temporary directory removed on drop
+    struct TempPath(std::path::PathBuf);
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    macro_rules! bench_file_layout {
+        ($layout:ty, $generate_proofs:ident) => {
+            #[bench]
+            fn $generate_proofs(b: &mut Bencher) {
+                let path = TempPath(std::env::temp_dir().join(format!(
+                    "merkle-heapless-bench-{}-{}.bin",
+                    std::process::id(),
+                    stringify!($generate_proofs)
+                )));
+                let file =
+                    FileStore::<ARITY, StdHash>::create(&path.0, num_of_prefixed!(ARITY, HEIGHT))
+                        .unwrap();
+                let store = LaidOut::new(file, <$layout>::new::<ARITY>(HEIGHT));
+                let words = (0..1 << 16)
+                    .map(|i: u32| i.to_le_bytes())
+                    .collect::<Vec<_>>();
+                let tree =
+                    StoredTree::<ARITY, HEIGHT, StdHash, 16, _>::try_from(store, &words).unwrap();
+                b.iter(|| {
+                    for leaf in leaves().map(|leaf| leaf % (1 << HEIGHT)) {
+                        black_box(tree.generate_proof(leaf).unwrap());
+                    }
+                });
+            }
+        };
+    }
+
+    bench_file_layout!(Layered, generate_file_proofs_layered);
+    bench_file_layout!(Blocked<5>, generate_file_proofs_blocked);
+}
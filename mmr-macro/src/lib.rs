@@ -67,7 +67,7 @@ impl Parse for MMRInput {
             mmr_type = "MerkleMountainRange".to_owned();
         }
 
-        let err_msg = "error while parsing 'BranchFactor = <power of 2 number>' section";
+        let err_msg = "error while parsing 'BranchFactor = <number of 2 or more>' section";
         let branch_factor_ident = if with_type {
             input.parse::<Ident>().expect(err_msg)
         } else {
@@ -258,6 +258,14 @@ pub fn mmr(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         })
         .collect::<Vec<_>>();
 
+    let as_dyn_nodes_variant_def_token = peak_variant_def_idents.iter()
+        .map(|(peak_lit, _)| {
+            quote! {
+                #peak_lit(tree) => tree as &dyn merkle_heapless::traits::TreeNodes<#arity, #hash_type>
+            }
+        })
+        .collect::<Vec<_>>();
+
     let as_append_only_variant_def_token = peak_variant_def_idents
         .iter()
         .map(|(peak_lit, _)| {
@@ -289,6 +297,13 @@ pub fn mmr(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
         }
     };
 
+    let impl_nodes_method_body_token = quote! {
+        use #mmr_peak_type::*;
+        match self {
+            #(#as_dyn_nodes_variant_def_token),*
+        }
+    };
+
     let impl_append_only_method_body_token = quote! {
         use #mmr_peak_type::*;
         match self {
@@ -307,7 +322,8 @@ pub fn mmr(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
             mod #mod_ident {
                 use merkle_heapless::{StaticTree, Error};
                 use merkle_heapless::augmentable::{AugmentableTree};
-                use merkle_heapless::traits::{HashT, StaticTreeTrait, AppendOnly};
+                use merkle_heapless::traits::{HashT, StaticTreeTrait, TreeNodes, StaticTreeExt, AppendOnly};
+                use merkle_heapless::iter::{Order, MountainPart};
                 use merkle_heapless::proof::{Proof, chain_proofs};
                 use merkle_heapless::prefixed::{Prefixed};
                 use super::#hash_type;
@@ -383,6 +399,15 @@ pub fn mmr(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                     }
                 }
 
+                impl TreeNodes<#arity, #hash_type> for #mmr_peak_type {
+                    fn leaf_hash(&self, input: &[u8]) -> <#hash_type as HashT>::Output {
+                        #impl_nodes_method_body_token.leaf_hash(input)
+                    }
+                    fn nodes(&self) -> &[Prefixed<#arity, #hash_type>] {
+                        #impl_nodes_method_body_token.nodes()
+                    }
+                }
+
                 impl AppendOnly for #mmr_peak_type {
                     fn try_append(&mut self, input: &[u8]) -> Result<(), Error> {
                         #impl_mut_append_only_method_body_token.try_append(input)
@@ -493,6 +518,42 @@ pub fn mmr(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
                     pub fn peaks(&self) -> &[#mmr_peak_type] {
                         &self.peaks[..]
                     }
+                    // the tree whose leaves are the roots of the peaks
+                    pub fn summit(&self) -> &StaticTree<#arity, #summit_height, #hash_type, #max_input_len> {
+                        &self.summit_tree
+                    }
+                    // iterates over the hashed leaves with their indices across the peaks
+                    pub fn leaf_hashes(&self) -> impl Iterator<Item = (usize, <#hash_type as HashT>::Output)> + '_ {
+                        self.peaks
+                            .iter()
+                            .flat_map(|peak| peak.leaf_hashes().take(peak.num_of_leaves()).map(|(_, leaf)| leaf))
+                            .enumerate()
+                    }
+                    // returns the index of the first leaf holding the input
+                    pub fn position(&self, input: &[u8]) -> Option<usize> {
+                        self.position_of_hash(&self.peaks[0].leaf_hash(input))
+                    }
+                    // returns the index of the first leaf holding the hashed value, scans the leaves
+                    pub fn position_of_hash(&self, leaf: &<#hash_type as HashT>::Output) -> Option<usize> {
+                        self.leaf_hashes().find_map(|(index, hash)| (&hash == leaf).then_some(index))
+                    }
+                    // calls the visitor with (part, layer, index, hash) of every node of the peaks in use and the summit tree,
+                    // top-down visits the summit tree first, the roots of the peaks are visited again as the summit leaves
+                    pub fn visit(
+                        &self,
+                        order: Order,
+                        visitor: &mut dyn FnMut(MountainPart, usize, usize, <#hash_type as HashT>::Output),
+                    ) {
+                        if order == Order::TopDown {
+                            self.summit_tree.visit(order, &mut |layer, index, hash| visitor(MountainPart::Summit, layer, index, hash));
+                        }
+                        for (i, peak) in self.peaks[..=self.curr_peak_index].iter().enumerate() {
+                            peak.visit(order, &mut |layer, index, hash| visitor(MountainPart::Peak(i), layer, index, hash));
+                        }
+                        if order == Order::BottomUp {
+                            self.summit_tree.visit(order, &mut |layer, index, hash| visitor(MountainPart::Summit, layer, index, hash));
+                        }
+                    }
                 }
 
                 impl Default for #mmr_type
@@ -29,9 +29,10 @@
 //! mt.try_merge(mt2).unwrap();
 //! ```
 
-use crate::traits::AppendOnly;
+use crate::journal::Restorable;
+use crate::traits::{AppendOnly, TreeNodes};
 use crate::{
-    is_pow2, layer_size, num_of_prefixed, Assert, Error, HashT, IsTrue, Prefixed, Proof,
+    is_arity, layer_size, num_of_prefixed, Assert, Error, HashT, IsTrue, Prefixed, Proof,
     ProofBuilder, StaticTree, StaticTreeTrait,
 };
 use core::fmt::Debug;
@@ -59,7 +60,7 @@ pub struct AugmentableTree<
     PB = Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
 > where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -71,7 +72,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
     AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -158,7 +159,7 @@ where
         if total_len > ARITY * layer_size!(ARITY, HEIGHT, 0) {
             return Err(Error::Merge);
         }
-        self.tree = self.tree.with_leaves_inner(other.leaves(), with_offset);
+        self.tree = self.tree.with_leaves_inner(other.leaves(), with_offset)?;
         self.num_of_leaves += other.num_of_leaves();
 
         Ok(())
@@ -169,7 +170,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
     StaticTreeTrait<ARITY, H, PB> for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -199,17 +200,33 @@ where
     }
 }
 
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> TreeNodes<ARITY, H>
+    for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        self.tree.leaf_hash(input)
+    }
+    fn nodes(&self) -> &[Prefixed<ARITY, H>] {
+        &self.tree.prefixed[..]
+    }
+}
+
 impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> AppendOnly
     for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
 
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
     fn try_append(&mut self, input: &[u8]) -> Result<(), Error> {
-        if self.num_of_leaves >= (self.base_layer_size() << ARITY.trailing_zeros()) {
+        if self.num_of_leaves >= self.base_layer_size() * ARITY {
             return Err(Error::Append);
         }
         self.replace(self.num_of_leaves, input);
@@ -221,11 +238,33 @@ where
     }
 }
 
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    Restorable<ARITY, H> for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn restore_node(&mut self, index: usize, node: Prefixed<ARITY, H>) {
+        self.tree.restore_node(index, node);
+    }
+    // the leaves are appended contiguously, so an emptied leaf was the first one not appended
+    fn restored(&mut self, leaf: usize) {
+        self.tree.restored(leaf);
+
+        let (index, offset) = crate::location_in_prefixed::<ARITY>(leaf);
+        if self.tree.prefixed[index].hashes[offset] == Prefixed::<ARITY, H>::default_hash() {
+            self.num_of_leaves = self.num_of_leaves.min(leaf);
+        }
+    }
+}
+
 impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Clone
     for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -238,7 +277,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
     for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -254,7 +293,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
     for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -267,7 +306,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
     for AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
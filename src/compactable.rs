@@ -29,9 +29,12 @@ pub type DefaultCompactable<
     Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
 >;
 
-use crate::traits::CanRemove;
+use crate::journal::Restorable;
+use crate::sync::SyncReplica;
+use crate::traits::{CanRemove, TreeNodes};
+use crate::utils::hash_leaf;
 use crate::{
-    is_pow2, layer_size, location_in_prefixed, max_leaves, num_of_prefixed, Assert, Error, HashT,
+    is_arity, layer_size, location_in_prefixed, max_leaves, num_of_prefixed, Assert, Error, HashT,
     IsTrue, Prefixed, Proof, ProofBuilder, StaticTree, StaticTreeTrait,
 };
 use core::fmt::Debug;
@@ -46,7 +49,7 @@ pub struct CompactableHeaplessTree<
 > where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -60,7 +63,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -159,6 +162,19 @@ where
         assert_eq!(self.num_of_leaves(), j);
         Ok(prefixed)
     }
+    // a leaf is present unless it is empty (never set or removed)
+    fn update_presence(&mut self, index: usize) {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        let leaf = self.tree.prefixed[prefixed_index].hashes[offset];
+        let present = leaf != Prefixed::<ARITY, H>::default_hash()
+            && leaf != hash_leaf::<H, MAX_INPUT_LEN>(&[]);
+        match (self.leaves_present[index], present) {
+            (false, true) => self.num_of_leaves += 1,
+            (true, false) => self.num_of_leaves -= 1,
+            _ => {}
+        }
+        self.leaves_present[index] = present;
+    }
     /// move all existing leaves leftwards
     pub fn compact(&mut self)
     where
@@ -200,7 +216,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -240,12 +256,29 @@ where
     }
 }
 
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> TreeNodes<ARITY, H>
+    for CompactableHeaplessTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        self.tree.leaf_hash(input)
+    }
+    fn nodes(&self) -> &[Prefixed<ARITY, H>] {
+        &self.tree.prefixed[..]
+    }
+}
+
 impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> CanRemove
     for CompactableHeaplessTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -265,12 +298,49 @@ where
     }
 }
 
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    SyncReplica<ARITY, HEIGHT, H> for CompactableHeaplessTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn node_hashes(&self, layer: usize, index: usize) -> Option<[H::Output; ARITY]> {
+        self.tree.node_hashes(layer, index)
+    }
+    // a leaf removed on the peer's side (or never present) is adopted as removed
+    fn adopt_leaf(&mut self, index: usize, leaf: H::Output) {
+        self.tree.replace_leaf(index, leaf);
+        self.update_presence(index);
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    Restorable<ARITY, H> for CompactableHeaplessTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn restore_node(&mut self, index: usize, node: Prefixed<ARITY, H>) {
+        self.tree.restore_node(index, node);
+    }
+    fn restored(&mut self, leaf: usize) {
+        self.tree.restored(leaf);
+        self.update_presence(leaf);
+    }
+}
+
 impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Clone
     for CompactableHeaplessTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -288,7 +358,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
@@ -306,7 +376,7 @@ impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
     [(); max_leaves!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
 {
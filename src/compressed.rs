@@ -0,0 +1,271 @@
+//! Compressed form of a [crate::proof::Proof] omitting the siblings that are empty subtrees.
+//! In a partially filled tree (e.g. a [crate::compactable::CompactableHeaplessTree]) most siblings on a path
+//! are padding, i.e. the empty-subtree hash [crate::prefixed::Prefixed::default_hash].
+//! Every item of a compressed proof keeps a bitmap of its empty siblings and the non-empty siblings only,
+//! the verifier puts the empty-subtree hash back in place of the marked ones.
+//! ```rust
+//! let proof = tree.generate_proof(3);
+//! let compressed = CompressedProof::from(proof);
+//! let len = compressed.encode(&mut buffer).unwrap();
+//!
+//! let compressed = CompressedProof::<2, 10, StdHash, 10>::decode(&buffer[..len]).unwrap();
+//! assert!(compressed.validate(b"apple"));
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::proof::Proof;
+use crate::traits::{HashT, PlainBytes, ProofBuilder, ProofValidator};
+use crate::{Assert, Error, IsTrue};
+use core::fmt::Debug;
+use core::mem::size_of;
+
+/// Proof with the empty siblings of every item marked in a bitmap rather than stored,
+/// the sibling at the offset of an item is never stored since the verifier derives it
+pub struct CompressedProof<
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+> where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+{
+    root: H::Output,
+    height: usize,
+    offsets: [usize; HEIGHT],
+    empty: [u64; HEIGHT],
+    siblings: [H::Output; HEIGHT * (ARITY - 1)],
+    num_of_siblings: usize,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+    Assert<{ ARITY <= 64 }>: IsTrue,
+{
+    // length of an item's bitmap once encoded
+    const BITMAP_LEN: usize = ARITY.div_ceil(8);
+
+    /// returns the proof's root
+    pub fn root(&self) -> H::Output {
+        self.root
+    }
+
+    /// returns the proof's length
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// returns the offset and the bitmap of empty siblings of the proof's items
+    pub fn items(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.offsets[..self.height]
+            .iter()
+            .copied()
+            .zip(self.empty[..self.height].iter().copied())
+    }
+
+    /// returns the non-empty siblings stored, in the order of the items
+    pub fn siblings(&self) -> &[H::Output] {
+        &self.siblings[..self.num_of_siblings]
+    }
+
+    /// returns the length of the encoded proof
+    pub fn encoded_len(&self) -> usize {
+        size_of::<H::Output>() * (1 + self.num_of_siblings)
+            + 1
+            + self.height * (1 + Self::BITMAP_LEN)
+    }
+
+    // number of the siblings of an item stored, all but the offset and the empty ones
+    fn num_of_stored(empty: u64) -> usize {
+        ARITY - 1 - empty.count_ones() as usize
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+    Assert<{ ARITY <= 64 }>: IsTrue,
+    Assert<{ HEIGHT <= 255 }>: IsTrue,
+    H::Output: PlainBytes,
+{
+    /// encodes the proof into the beginning of the output as
+    /// the root, the height (a byte, hence HEIGHT <= 255),
+    /// then the offset, the bitmap and the non-empty siblings of each item,
+    /// returns the number of bytes written
+    pub fn encode(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let out = out.get_mut(..self.encoded_len()).ok_or(Error::Create)?;
+
+        let mut pos = 0;
+        let mut put = |bytes: &[u8]| {
+            out[pos..pos + bytes.len()].copy_from_slice(bytes);
+            pos += bytes.len();
+        };
+
+        put(self.root.as_bytes());
+        put(&[self.height as u8]);
+        let mut siblings = self.siblings().iter();
+        for (offset, empty) in self.items() {
+            put(&[offset as u8]);
+            put(&empty.to_le_bytes()[..Self::BITMAP_LEN]);
+            for _ in 0..Self::num_of_stored(empty) {
+                put(siblings
+                    .next()
+                    .expect("siblings are counted per item. qed")
+                    .as_bytes());
+            }
+        }
+        Ok(pos)
+    }
+
+    /// decodes a proof occupying the whole input
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let n = size_of::<H::Output>();
+        let mut this = Self::default();
+
+        let mut pos = 0;
+        let mut take = |len: usize| {
+            let chunk = bytes.get(pos..pos + len).ok_or(Error::Create);
+            pos += len;
+            chunk
+        };
+
+        this.root.as_bytes_mut().copy_from_slice(take(n)?);
+        this.height = take(1)?[0] as usize;
+        if this.height > HEIGHT {
+            return Err(Error::Create);
+        }
+        for i in 0..this.height {
+            let offset = take(1)?[0] as usize;
+            let mut empty = [0u8; 8];
+            empty[..Self::BITMAP_LEN].copy_from_slice(take(Self::BITMAP_LEN)?);
+            let empty = u64::from_le_bytes(empty);
+            // the offset is never marked as empty, no bits beyond the arity
+            if offset >= ARITY || empty & (1 << offset) != 0 || (ARITY < 64 && empty >> ARITY != 0)
+            {
+                return Err(Error::Create);
+            }
+            this.offsets[i] = offset;
+            this.empty[i] = empty;
+            for _ in 0..Self::num_of_stored(empty) {
+                this.siblings[this.num_of_siblings]
+                    .as_bytes_mut()
+                    .copy_from_slice(take(n)?);
+                this.num_of_siblings += 1;
+            }
+        }
+        // no trailing bytes
+        if pos != bytes.len() {
+            return Err(Error::Create);
+        }
+        Ok(this)
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    From<Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>>
+    for CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+    Assert<{ ARITY <= 64 }>: IsTrue,
+{
+    fn from(proof: Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>) -> Self {
+        let default_hash = Prefixed::<ARITY, H>::default_hash();
+        let mut this = Self {
+            root: proof.root(),
+            height: proof.height(),
+            ..Default::default()
+        };
+        for (i, item) in proof.path().iter().enumerate() {
+            this.offsets[i] = item.offset();
+            for (j, hash) in item.hashes().iter().enumerate() {
+                if j == item.offset() {
+                    continue;
+                }
+                if *hash == default_hash {
+                    this.empty[i] |= 1 << j;
+                } else {
+                    this.siblings[this.num_of_siblings] = *hash;
+                    this.num_of_siblings += 1;
+                }
+            }
+        }
+        this
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    From<CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>>
+    for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+    Assert<{ ARITY <= 64 }>: IsTrue,
+{
+    /// restores the proof with the empty siblings put back,
+    /// the hash at the offset of an item is left empty as it is overwritten on validation
+    fn from(compressed: CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>) -> Self {
+        let mut proof = Self::from_root(compressed.root);
+        let mut siblings = compressed.siblings().iter();
+        for (offset, empty) in compressed.items() {
+            let mut prefixed = Prefixed::<ARITY, H>::default();
+            for (j, hash) in prefixed.hashes.iter_mut().enumerate() {
+                if j != offset && empty & (1 << j) == 0 {
+                    *hash = *siblings.next().expect("siblings are counted per item. qed");
+                }
+            }
+            proof.push(offset, prefixed);
+        }
+        proof
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> ProofValidator
+    for CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+    Assert<{ ARITY <= 64 }>: IsTrue,
+{
+    /// verifies that the input was contained in the Merkle tree that generated this proof
+    fn validate(self, input: &[u8]) -> bool {
+        Proof::from(self).validate(input)
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Default
+    for CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+{
+    fn default() -> Self {
+        Self {
+            root: Default::default(),
+            height: 0,
+            offsets: [0; HEIGHT],
+            empty: [0; HEIGHT],
+            siblings: [Default::default(); HEIGHT * (ARITY - 1)],
+            num_of_siblings: 0,
+        }
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Debug
+    for CompressedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+    [(); HEIGHT * (ARITY - 1)]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[proof height]:   {:?}", self.height)?;
+        writeln!(f, "[proof root]:   {:?}", self.root)?;
+        writeln!(f, "[empty siblings]:   {:?}", &self.empty[..self.height])?;
+        write!(f, "{:?}", &self.siblings[..self.num_of_siblings])
+    }
+}
@@ -0,0 +1,126 @@
+//! Construction of a [crate::StaticTree] in a const context, e.g. a fixed allowlist embedded into firmware
+//! with its tree and root computed at compile time rather than at boot.
+//! The hash should be evaluable at compile time by implementing [crate::traits::ConstHashT],
+//! the number of the input elements is checked against the capacity of the tree at compile time.
+//! The tree is identical to the one built by [crate::StaticTree::try_from] at runtime.
+//! ```rust
+//! #![feature(const_trait_impl)]
+//! #![feature(effects)]
+//!
+//! impl const ConstHashT for Fnv64Hash {
+//!     fn const_hash(input: &[u8]) -> Self::Output { ... }
+//!     fn const_from(value: u8) -> Self::Output { ... }
+//!     fn const_default() -> Self::Output { ... }
+//! }
+//!
+//! static ALLOWLIST: StaticTree<2, 4, Fnv64Hash, 16> = StaticTree::from_const(&[b"alice", b"bob", b"carol"]);
+//! ```
+
+use crate::layout::{LaidOut, Layered};
+use crate::prefixed::Prefixed;
+use crate::traits::{ConstHashT, HashT, ProofBuilder};
+use core::slice::from_raw_parts;
+use crate::{INNER_HASH_PREPEND_VALUE, LEAF_HASH_PREPEND_VALUE};
+use crate::{is_arity, layer_size, max_leaves, num_of_prefixed, Assert, IsTrue, StaticTree};
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// creates a tree from an input in a const context,
+    /// fails to compile if the input does not fit the leaves of the tree
+    /// panics (at compile time if evaluated in a const context) if an input element is longer than MAX_INPUT_LEN
+    pub const fn from_const<const N: usize>(input: &[&[u8]; N]) -> Self
+    where
+        H: ~const ConstHashT,
+        Assert<{ N <= max_leaves!(ARITY, HEIGHT) }>: IsTrue,
+    {
+        let mut prefixed = [Prefixed::<ARITY, H>::const_default(); num_of_prefixed!(ARITY, HEIGHT)];
+
+        // the prefix is omitted for all the input if an element occupies the whole input buffer
+        let mut start_index = 1;
+        let mut i = 0;
+        while i < N {
+            assert!(
+                input[i].len() <= MAX_INPUT_LEN,
+                "input is longer than MAX_INPUT_LEN"
+            );
+            if input[i].len() == MAX_INPUT_LEN {
+                start_index = 0;
+            }
+            i += 1;
+        }
+        // fill the base layer
+        let mut buffer = [LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN];
+        let mut i = 0;
+        while i < N {
+            let mut j = 0;
+            while j < input[i].len() {
+                buffer[start_index + j] = input[i][j];
+                j += 1;
+            }
+            let (buffer, _) = buffer.split_at(input[i].len() + start_index);
+            prefixed[i / ARITY].hashes[i % ARITY] = H::const_hash(buffer);
+            i += 1;
+        }
+        // fill the rest of layers, the padding nodes are left default
+        let mut layer_base = 0;
+        let mut data_nodes = N.div_ceil(ARITY);
+        let mut layer = 0;
+        while layer < HEIGHT - 1 {
+            let parent_layer_base = layer_base + layer_size!(ARITY, HEIGHT, layer);
+            let mut child = 0;
+            while child < data_nodes {
+                prefixed[parent_layer_base + child / ARITY].hashes[child % ARITY] =
+                    prefixed[layer_base + child].const_hash_all();
+                child += 1;
+            }
+            // move on to the upper layer
+            layer_base = parent_layer_base;
+            data_nodes = data_nodes.div_ceil(ARITY);
+            layer += 1;
+        }
+
+        Self {
+            root: prefixed[layer_base].const_hash_all(),
+            root_aggregate: (),
+            prefixed: LaidOut {
+                store: prefixed,
+                layout: Layered,
+            },
+        }
+    }
+
+    /// returns the root in a const context
+    pub const fn const_root(&self) -> H::Output {
+        self.root
+    }
+}
+
+impl<const ARITY: usize, H: HashT> Prefixed<ARITY, H> {
+    /// default node evaluable at compile time
+    pub(crate) const fn const_default() -> Self
+    where
+        H: ~const ConstHashT,
+    {
+        Self {
+            prefix: H::const_from(INNER_HASH_PREPEND_VALUE),
+            hashes: [H::const_default(); ARITY],
+            aggregates: [(); ARITY],
+        }
+    }
+    /// hash the prefix together with inner hashes at compile time
+    pub(crate) const fn const_hash_all(&self) -> H::Output
+    where
+        H: ~const ConstHashT,
+    {
+        // a node without aggregates is a contiguous array of the prefix and the hashes, as in [Prefixed::concat_input]
+        H::const_concat_then_hash(unsafe {
+            from_raw_parts(&self.prefix as *const <H as HashT>::Output, ARITY + 1)
+        })
+    }
+}
@@ -0,0 +1,245 @@
+//! Diffing replicas of the same tree shape by descending from the root
+//! only into the nodes whose hashes mismatch, yielding the indices of differing leaves.
+//! ```rust
+//! let mt1 = StaticTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from::<&[u8]>(&[
+//!     b"apple", b"apricot", b"banana",
+//! ]).unwrap();
+//! let mut mt2 = mt1.clone();
+//! mt2.replace(1, b"cherry");
+//!
+//! assert_eq!(mt1.diff(&mt2).collect::<Vec<_>>(), vec![1]);
+//! ```
+//! A remote replica is diffed by fetching its node hashes layer-by-layer on the way down,
+//! the peer answers the requests with [crate::StaticTree::node].
+//! ```rust
+//! struct Peer<'a>(&'a Tree);
+//!
+//! impl<'a> NodeSource<ARITY, StdHash> for Peer<'a> {
+//!     type Error = ();
+//!     fn fetch(&mut self, layer: usize, index: usize) -> Result<[Wrapped8; ARITY], ()> {
+//!         // send the request over the wire and wait for the response instead
+//!         self.0.node(layer, index).map(|node| *node.hashes()).ok_or(())
+//!     }
+//! }
+//!
+//! for index in mt1.diff_remote(Peer(&mt2)) {
+//!     assert_eq!(index, Ok(1));
+//! }
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::traits::{HashT, NodeAggregate};
+use crate::utils::{div_arity, layer_base, mod_arity};
+
+/// source of the node hashes of a replica, e.g. a remote peer answering node requests
+pub trait NodeSource<const ARITY: usize, H: HashT> {
+    /// error on fetching a node
+    type Error;
+    /// returns the hashes of a node at index within a layer (layers are counted from the leaves)
+    fn fetch(&mut self, layer: usize, index: usize) -> Result<[H::Output; ARITY], Self::Error>;
+}
+
+impl<const ARITY: usize, H: HashT, R: NodeSource<ARITY, H>> NodeSource<ARITY, H> for &mut R {
+    type Error = R::Error;
+    fn fetch(&mut self, layer: usize, index: usize) -> Result<[H::Output; ARITY], Self::Error> {
+        (**self).fetch(layer, index)
+    }
+}
+
+// position of the depth-first descent
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Cursor {
+    pub(crate) layer: usize,
+    pub(crate) node: usize,
+    pub(crate) offset: usize,
+}
+
+impl Cursor {
+    pub(crate) fn new(height: usize) -> Self {
+        Self {
+            layer: height - 1,
+            node: 0,
+            offset: 0,
+        }
+    }
+    // moves into the child node at the current offset
+    pub(crate) fn down<const ARITY: usize>(&mut self) {
+        self.node = self.node * ARITY + self.offset;
+        self.layer -= 1;
+        self.offset = 0;
+    }
+    // moves back to the parent node next to the current one, false if there is no parent
+    pub(crate) fn up<const ARITY: usize>(&mut self, height: usize) -> bool {
+        if self.layer == height - 1 {
+            return false;
+        }
+        self.offset = mod_arity::<ARITY>(self.node) + 1;
+        self.node = div_arity::<ARITY>(self.node);
+        self.layer += 1;
+        true
+    }
+}
+
+/// Iterator over the indices of differing leaves of two trees of the same shape
+pub struct Diff<'a, const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    this: &'a [Prefixed<ARITY, H, A>],
+    other: &'a [Prefixed<ARITY, H, A>],
+    height: usize,
+    cursor: Option<Cursor>,
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Diff<'a, ARITY, H, A> {
+    pub(crate) fn new(
+        this: &'a [Prefixed<ARITY, H, A>],
+        other: &'a [Prefixed<ARITY, H, A>],
+        height: usize,
+    ) -> Self {
+        Self {
+            this,
+            other,
+            height,
+            cursor: Some(Cursor::new(height)),
+        }
+    }
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Iterator for Diff<'a, ARITY, H, A> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let cursor = self.cursor.as_mut()?;
+        loop {
+            if cursor.offset == ARITY {
+                if !cursor.up::<ARITY>(self.height) {
+                    self.cursor = None;
+                    return None;
+                }
+                continue;
+            }
+            let i = layer_base::<ARITY>(self.height, cursor.layer) + cursor.node;
+            let (this, other) = (&self.this[i], &self.other[i]);
+
+            if this.hashes[cursor.offset] == other.hashes[cursor.offset]
+                && this.aggregates[cursor.offset] == other.aggregates[cursor.offset]
+            {
+                cursor.offset += 1;
+            } else if cursor.layer == 0 {
+                let index = cursor.node * ARITY + cursor.offset;
+                cursor.offset += 1;
+                return Some(index);
+            } else {
+                cursor.down::<ARITY>();
+            }
+        }
+    }
+}
+
+/// Iterator over the indices of differing leaves of a tree and its remote replica of the same shape.
+/// Fetches a remote node only when descending into it (at most HEIGHT nodes are kept),
+/// aggregates are compared as committed into the hashes of the inner nodes.
+/// The iteration stops after yielding a fetch error
+pub struct RemoteDiff<
+    'a,
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    R: NodeSource<ARITY, H>,
+    A: NodeAggregate = (),
+> {
+    this: &'a [Prefixed<ARITY, H, A>],
+    remote: R,
+    height: usize,
+    // remote nodes along the current path
+    fetched: [[H::Output; ARITY]; HEIGHT],
+    cursor: Option<Cursor>,
+    started: bool,
+}
+
+impl<
+        'a,
+        const ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        R: NodeSource<ARITY, H>,
+        A: NodeAggregate,
+    > RemoteDiff<'a, ARITY, HEIGHT, H, R, A>
+{
+    pub(crate) fn new(this: &'a [Prefixed<ARITY, H, A>], height: usize, remote: R) -> Self {
+        Self {
+            this,
+            remote,
+            height,
+            fetched: [[Prefixed::<ARITY, H, A>::default_hash(); ARITY]; HEIGHT],
+            cursor: Some(Cursor::new(height)),
+            started: false,
+        }
+    }
+
+    fn fetch(&mut self, layer: usize, node: usize) -> Result<(), R::Error> {
+        match self.remote.fetch(layer, node) {
+            Ok(hashes) => {
+                self.fetched[layer] = hashes;
+                Ok(())
+            }
+            Err(e) => {
+                self.cursor = None;
+                Err(e)
+            }
+        }
+    }
+
+    /// returns the remote node source
+    pub fn into_remote(self) -> R {
+        self.remote
+    }
+}
+
+impl<
+        'a,
+        const ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        R: NodeSource<ARITY, H>,
+        A: NodeAggregate,
+    > Iterator for RemoteDiff<'a, ARITY, HEIGHT, H, R, A>
+{
+    type Item = Result<usize, R::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.started {
+            self.started = true;
+            if let Err(e) = self.fetch(self.height - 1, 0) {
+                return Some(Err(e));
+            }
+        }
+        loop {
+            let mut cursor = self.cursor?;
+
+            if cursor.offset == ARITY {
+                if !cursor.up::<ARITY>(self.height) {
+                    self.cursor = None;
+                    return None;
+                }
+                self.cursor = Some(cursor);
+                continue;
+            }
+            let i = layer_base::<ARITY>(self.height, cursor.layer) + cursor.node;
+
+            if self.this[i].hashes[cursor.offset] == self.fetched[cursor.layer][cursor.offset] {
+                cursor.offset += 1;
+                self.cursor = Some(cursor);
+            } else if cursor.layer == 0 {
+                let index = cursor.node * ARITY + cursor.offset;
+                cursor.offset += 1;
+                self.cursor = Some(cursor);
+                return Some(Ok(index));
+            } else {
+                cursor.down::<ARITY>();
+                self.cursor = Some(cursor);
+                if let Err(e) = self.fetch(cursor.layer, cursor.node) {
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
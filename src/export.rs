@@ -0,0 +1,301 @@
+//! Rendering of a tree as Graphviz DOT or as an indented ASCII diagram (requires the std feature).
+//! Nodes are labelled by (layer, index) and the leading bytes of their hashes in hex
+//! (the hash output is [crate::traits::PlainBytes]),
+//! the subtrees of empty (default hash) nodes are omitted.
+//! The proof path of a leaf is optionally highlighted: the nodes from the leaf up to the root
+//! and their siblings making up the proof items.
+//! ```rust
+//! use merkle_heapless::export;
+//!
+//! std::fs::write("tree.dot", export::dot(&tree, Some(2))).unwrap();
+//! println!("{}", export::ascii(&tree, Some(2)));
+//! // the peaks in use and the summit tree of a Mountain Range
+//! println!("{}", export::mountain_range_ascii(mmr.peaks(), mmr.summit(), Some(5)));
+//! ```
+//! In the ASCII diagram the nodes on the proof path are marked by ```*```, the proof items by ```+```.
+
+use crate::prefixed::Prefixed;
+use crate::traits::{
+    AppendOnly, HashT, NodeAggregate, PlainBytes, ProofBuilder, StaticTreeTrait, TreeNodes,
+};
+use crate::utils::{div_arity, layer_base, pow_arity};
+use std::fmt::Write;
+use std::string::String;
+
+// number of the leading bytes of a hash shown
+const HASH_BYTES_SHOWN: usize = 4;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    None,
+    Path,
+    ProofItem,
+}
+
+// tree to render, layers are counted from the leaves, the root is at layer = height
+struct View<'a, const ARITY: usize, H: HashT, A: NodeAggregate> {
+    nodes: &'a [Prefixed<ARITY, H, A>],
+    root: H::Output,
+    height: usize,
+    proof_of: Option<usize>,
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> View<'a, ARITY, H, A>
+where
+    H::Output: PlainBytes,
+{
+    fn of<PB, T>(tree: &'a T, proof_of: Option<usize>) -> Self
+    where
+        PB: ProofBuilder<ARITY, H, A>,
+        T: StaticTreeTrait<ARITY, H, PB, A> + TreeNodes<ARITY, H, A> + ?Sized,
+    {
+        Self {
+            nodes: tree.nodes(),
+            root: tree.root(),
+            height: tree.height(),
+            proof_of,
+        }
+    }
+
+    fn children(&self, layer: usize, index: usize) -> &'a [H::Output; ARITY] {
+        &self.nodes[layer_base::<ARITY>(self.height, layer - 1) + index].hashes
+    }
+
+    fn mark(&self, layer: usize, index: usize) -> Mark {
+        let Some(leaf) = self.proof_of else {
+            return Mark::None;
+        };
+        let on_path = leaf / pow_arity::<ARITY>(layer);
+        if index == on_path {
+            Mark::Path
+        } else if layer < self.height && div_arity::<ARITY>(index) == div_arity::<ARITY>(on_path) {
+            Mark::ProofItem
+        } else {
+            Mark::None
+        }
+    }
+
+    // whether a child node is rendered
+    fn shown(&self, layer: usize, index: usize, hash: &H::Output) -> bool {
+        hash != &Prefixed::<ARITY, H, A>::default_hash() || self.mark(layer, index) != Mark::None
+    }
+
+    fn write_dot(&self, out: &mut String, prefix: &str) {
+        self.write_dot_node(out, prefix, self.height, 0, &self.root);
+    }
+
+    fn write_dot_node(
+        &self,
+        out: &mut String,
+        prefix: &str,
+        layer: usize,
+        index: usize,
+        hash: &H::Output,
+    ) {
+        let style = match self.mark(layer, index) {
+            Mark::None => "",
+            Mark::Path => " style=filled fillcolor=lightcoral",
+            Mark::ProofItem => " style=filled fillcolor=lightblue",
+        };
+        let _ = writeln!(
+            out,
+            "    \"{prefix}{layer}_{index}\" [label=\"{layer}:{index}\\n{}\"{style}];",
+            hex::<H>(hash)
+        );
+        if layer == 0 {
+            return;
+        }
+        for (offset, child) in self.children(layer, index).iter().enumerate() {
+            let child_index = index * ARITY + offset;
+            if !self.shown(layer - 1, child_index, child) {
+                continue;
+            }
+            let style = if self.mark(layer - 1, child_index) == Mark::Path {
+                " [color=red penwidth=2]"
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "    \"{prefix}{layer}_{index}\" -> \"{prefix}{}_{child_index}\"{style};",
+                layer - 1
+            );
+            self.write_dot_node(out, prefix, layer - 1, child_index, child);
+        }
+    }
+
+    fn write_ascii(&self, out: &mut String) {
+        self.write_ascii_node(out, &mut String::new(), self.height, 0, &self.root);
+    }
+
+    fn write_ascii_node(
+        &self,
+        out: &mut String,
+        indent: &mut String,
+        layer: usize,
+        index: usize,
+        hash: &H::Output,
+    ) {
+        let mark = match self.mark(layer, index) {
+            Mark::None => "",
+            Mark::Path => " *",
+            Mark::ProofItem => " +",
+        };
+        let _ = writeln!(out, "{layer}:{index} {}{mark}", hex::<H>(hash));
+        if layer == 0 {
+            return;
+        }
+        let children = self.children(layer, index);
+        let shown = (0..ARITY)
+            .filter(|&offset| self.shown(layer - 1, index * ARITY + offset, &children[offset]))
+            .collect::<std::vec::Vec<_>>();
+
+        for (i, &offset) in shown.iter().enumerate() {
+            let last = i + 1 == shown.len();
+            let _ = write!(out, "{indent}{}", if last { "└── " } else { "├── " });
+
+            let len = indent.len();
+            indent.push_str(if last { "    " } else { "│   " });
+            self.write_ascii_node(
+                out,
+                indent,
+                layer - 1,
+                index * ARITY + offset,
+                &children[offset],
+            );
+            indent.truncate(len);
+        }
+    }
+}
+
+// leading bytes of a hash in hex
+fn hex<H: HashT>(hash: &H::Output) -> String
+where
+    H::Output: PlainBytes,
+{
+    let bytes = hash.as_bytes();
+    let mut s = String::new();
+    for b in bytes.iter().take(HASH_BYTES_SHOWN) {
+        let _ = write!(s, "{b:02x}");
+    }
+    if bytes.len() > HASH_BYTES_SHOWN {
+        s.push_str("..");
+    }
+    s
+}
+
+// peak holding a leaf of a Mountain Range and the index of the leaf within the peak
+fn locate_in_peaks<P: AppendOnly>(peaks: &[P], index: usize) -> Option<(usize, usize)> {
+    let mut accrue_len = 0;
+    for (i, peak) in peaks.iter().enumerate() {
+        if index < accrue_len + peak.num_of_leaves() {
+            return Some((i, index - accrue_len));
+        }
+        accrue_len += peak.num_of_leaves();
+    }
+    None
+}
+
+/// renders a tree as a Graphviz DOT digraph, highlighting the proof path of a leaf if any
+pub fn dot<const ARITY: usize, H, PB, A, T>(tree: &T, proof_of: Option<usize>) -> String
+where
+    H: HashT,
+    H::Output: PlainBytes,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    T: StaticTreeTrait<ARITY, H, PB, A> + TreeNodes<ARITY, H, A> + ?Sized,
+{
+    let mut out = String::from("digraph {\n    node [shape=box fontname=monospace];\n");
+    View::of(tree, proof_of).write_dot(&mut out, "n");
+    out.push_str("}\n");
+    out
+}
+
+/// renders a tree as an indented ASCII diagram, highlighting the proof path of a leaf if any
+pub fn ascii<const ARITY: usize, H, PB, A, T>(tree: &T, proof_of: Option<usize>) -> String
+where
+    H: HashT,
+    H::Output: PlainBytes,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    T: StaticTreeTrait<ARITY, H, PB, A> + TreeNodes<ARITY, H, A> + ?Sized,
+{
+    let mut out = String::new();
+    View::of(tree, proof_of).write_ascii(&mut out);
+    out
+}
+
+/// renders the peaks in use and the summit tree of a Mountain Range as a Graphviz DOT digraph
+/// with a cluster per tree, the leaves of the summit tree are linked to the roots of the peaks.
+/// Highlights the proof path of a leaf of the Mountain Range if any
+pub fn mountain_range_dot<const ARITY: usize, H, P, PPB, S, SPB>(
+    peaks: &[P],
+    summit: &S,
+    proof_of: Option<usize>,
+) -> String
+where
+    H: HashT,
+    H::Output: PlainBytes,
+    PPB: ProofBuilder<ARITY, H>,
+    SPB: ProofBuilder<ARITY, H>,
+    P: StaticTreeTrait<ARITY, H, PPB> + TreeNodes<ARITY, H> + AppendOnly,
+    S: StaticTreeTrait<ARITY, H, SPB> + TreeNodes<ARITY, H> + ?Sized,
+{
+    let location = proof_of.and_then(|index| locate_in_peaks(peaks, index));
+
+    let mut out = String::from("digraph {\n    node [shape=box fontname=monospace];\n");
+    out.push_str("    subgraph cluster_summit {\n    label=\"summit\";\n");
+    View::of(summit, location.map(|(i, _)| i)).write_dot(&mut out, "s");
+    out.push_str("    }\n");
+
+    for (i, peak) in peaks.iter().enumerate() {
+        if peak.num_of_leaves() == 0 {
+            continue;
+        }
+        let proof_of = location.and_then(|(j, index)| (i == j).then_some(index));
+        let _ = writeln!(
+            out,
+            "    subgraph cluster_peak{i} {{\n    label=\"peak {i}\";"
+        );
+        View::of(peak, proof_of).write_dot(&mut out, &std::format!("p{i}_"));
+        out.push_str("    }\n");
+        let _ = writeln!(
+            out,
+            "    \"s0_{i}\" -> \"p{i}_{}_0\" [style=dashed];",
+            peak.height()
+        );
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// renders the summit tree and the peaks in use of a Mountain Range as indented ASCII diagrams,
+/// highlighting the proof path of a leaf of the Mountain Range if any
+pub fn mountain_range_ascii<const ARITY: usize, H, P, PPB, S, SPB>(
+    peaks: &[P],
+    summit: &S,
+    proof_of: Option<usize>,
+) -> String
+where
+    H: HashT,
+    H::Output: PlainBytes,
+    PPB: ProofBuilder<ARITY, H>,
+    SPB: ProofBuilder<ARITY, H>,
+    P: StaticTreeTrait<ARITY, H, PPB> + TreeNodes<ARITY, H> + AppendOnly,
+    S: StaticTreeTrait<ARITY, H, SPB> + TreeNodes<ARITY, H> + ?Sized,
+{
+    let location = proof_of.and_then(|index| locate_in_peaks(peaks, index));
+
+    let mut out = String::from("summit\n");
+    View::of(summit, location.map(|(i, _)| i)).write_ascii(&mut out);
+
+    for (i, peak) in peaks.iter().enumerate() {
+        if peak.num_of_leaves() == 0 {
+            continue;
+        }
+        let proof_of = location.and_then(|(j, index)| (i == j).then_some(index));
+        let _ = writeln!(out, "peak {i}");
+        View::of(peak, proof_of).write_ascii(&mut out);
+    }
+    out
+}
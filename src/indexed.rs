@@ -0,0 +1,270 @@
+//! Tree wrapper keeping a fixed-capacity hash index of its leaves,
+//! so a position of a leaf is looked up without scanning the leaves.
+//! The index is maintained on replace and remove, the empty (default hash) leaves
+//! and the removed ones (the hash of an empty input) are not indexed but scanned for.
+//! The tombstones left by the changes are reclaimed by rehashing the index once they take a quarter of it.
+//! ```rust
+//! use merkle_heapless::indexed::IndexedTree;
+//!
+//! let mt = StaticTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from::<&[u8]>(&[
+//!     b"apple", b"apricot", b"banana",
+//! ]).unwrap();
+//! // CAPACITY should be at least the number of the leaf slots, preferably twice as much
+//! let mut indexed = IndexedTree::<ARITY, 128, _, _, _>::try_from_tree(mt).unwrap();
+//!
+//! indexed.replace(5, b"kiwi");
+//! let proof = indexed.generate_proof(indexed.position(b"kiwi").unwrap());
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::traits::{CanRemove, HashT, ProofBuilder, StaticTreeTrait, TreeNodes};
+use crate::utils::location_in_prefixed;
+use crate::Error;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+
+// FNV-1a, a few instructions per byte and no state beyond a u64, good enough to spread the buckets
+struct Fnv64(u64);
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for Fnv64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 = (self.0 ^ *b as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Slot<K> {
+    Empty,
+    // a tombstone, the probing continues past it
+    Removed,
+    Occupied(K, usize),
+}
+
+/// Tree wrapper keeping an open-addressing hash index of the leaves of a fixed CAPACITY
+pub struct IndexedTree<const ARITY: usize, const CAPACITY: usize, H: HashT, PB, T> {
+    tree: T,
+    slots: [Slot<H::Output>; CAPACITY],
+    tombstones: usize,
+    // the hash of an empty input, written by a removal
+    removed: H::Output,
+    _marker: PhantomData<PB>,
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> IndexedTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + TreeNodes<ARITY, H>,
+{
+    /// creates the index of the leaves of a tree,
+    /// fails if CAPACITY is less than the number of the leaf slots
+    pub fn try_from_tree(tree: T) -> Result<Self, Error> {
+        if CAPACITY < tree.base_layer_size() * ARITY {
+            return Err(Error::Create);
+        }
+        let removed = tree.leaf_hash(&[]);
+        let mut this = Self {
+            tree,
+            slots: [Slot::Empty; CAPACITY],
+            tombstones: 0,
+            removed,
+            _marker: PhantomData,
+        };
+        this.rehash();
+        Ok(this)
+    }
+
+    /// returns the underlying tree
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// returns the underlying tree dropping the index
+    pub fn into_inner(self) -> T {
+        self.tree
+    }
+
+    fn leaf_at(&self, index: usize) -> H::Output {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        self.tree.leaves()[prefixed_index].hashes[offset]
+    }
+
+    // updates the index after the leaf at index was changed from the old one
+    fn reindex(&mut self, index: usize, old: H::Output) {
+        self.erase(old, index);
+        self.insert(self.leaf_at(index), index);
+        if self.tombstones > CAPACITY / 4 {
+            self.rehash();
+        }
+    }
+
+    // rebuilds the index from the leaves in place, dropping the tombstones
+    fn rehash(&mut self) {
+        self.slots = [Slot::Empty; CAPACITY];
+        self.tombstones = 0;
+        for index in 0..self.tree.base_layer_size() * ARITY {
+            self.insert(self.leaf_at(index), index);
+        }
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H: HashT, PB, T>
+    IndexedTree<ARITY, CAPACITY, H, PB, T>
+{
+    // the leaf hash is fed to FNV-1a through its Hash implementation
+    fn bucket(leaf: &H::Output) -> usize {
+        let mut hasher = Fnv64::default();
+        leaf.hash(&mut hasher);
+        (hasher.finish() % CAPACITY as u64) as usize
+    }
+
+    // probes the slots starting from the bucket of the leaf
+    fn probe(leaf: &H::Output) -> impl Iterator<Item = usize> {
+        let bucket = Self::bucket(leaf);
+        (0..CAPACITY).map(move |i| (bucket + i) % CAPACITY)
+    }
+
+    // the empty and the removed leaves are scanned for instead
+    fn is_indexed(&self, leaf: &H::Output) -> bool {
+        leaf != &Prefixed::<ARITY, H>::default_hash() && leaf != &self.removed
+    }
+
+    fn insert(&mut self, leaf: H::Output, index: usize) {
+        if !self.is_indexed(&leaf) {
+            return;
+        }
+        let slot = Self::probe(&leaf)
+            .find(|&i| !matches!(self.slots[i], Slot::Occupied(..)))
+            .expect("capacity covers all leaf slots. qed");
+        if matches!(self.slots[slot], Slot::Removed) {
+            self.tombstones -= 1;
+        }
+        self.slots[slot] = Slot::Occupied(leaf, index);
+    }
+
+    fn erase(&mut self, leaf: H::Output, index: usize) {
+        for i in Self::probe(&leaf) {
+            match self.slots[i] {
+                Slot::Empty => return,
+                Slot::Occupied(k, j) if k == leaf && j == index => {
+                    self.slots[i] = Slot::Removed;
+                    self.tombstones += 1;
+                    return;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // the lowest index holding the leaf
+    fn lookup(&self, leaf: &H::Output) -> Option<usize> {
+        Self::probe(leaf)
+            .map(|i| &self.slots[i])
+            .take_while(|slot| !matches!(slot, Slot::Empty))
+            .filter_map(|slot| match slot {
+                Slot::Occupied(k, index) if k == leaf => Some(*index),
+                _ => None,
+            })
+            .min()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> StaticTreeTrait<ARITY, H, PB>
+    for IndexedTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + TreeNodes<ARITY, H>,
+{
+    fn generate_proof(&self, index: usize) -> PB {
+        self.tree.generate_proof(index)
+    }
+    fn replace(&mut self, index: usize, input: &[u8]) {
+        let old = self.leaf_at(index);
+        self.tree.replace(index, input);
+        self.reindex(index, old);
+    }
+    fn replace_leaf(&mut self, index: usize, leaf: H::Output) {
+        let old = self.leaf_at(index);
+        self.tree.replace_leaf(index, leaf);
+        self.reindex(index, old);
+    }
+    /// looks the leaf up in the index, the empty and the removed leaves are scanned for
+    fn position_of_hash(&self, leaf: &H::Output) -> Option<usize> {
+        if self.is_indexed(leaf) {
+            self.lookup(leaf)
+        } else {
+            self.tree.position_of_hash(leaf)
+        }
+    }
+    fn root(&self) -> H::Output {
+        self.tree.root()
+    }
+    fn leaves(&self) -> &[Prefixed<ARITY, H>] {
+        self.tree.leaves()
+    }
+    fn base_layer_size(&self) -> usize {
+        self.tree.base_layer_size()
+    }
+    fn height(&self) -> usize {
+        self.tree.height()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> TreeNodes<ARITY, H>
+    for IndexedTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + TreeNodes<ARITY, H>,
+{
+    fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        self.tree.leaf_hash(input)
+    }
+    fn nodes(&self) -> &[Prefixed<ARITY, H>] {
+        self.tree.nodes()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> CanRemove
+    for IndexedTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + TreeNodes<ARITY, H> + CanRemove,
+{
+    fn remove(&mut self, index: usize) {
+        let old = self.leaf_at(index);
+        self.tree.remove(index);
+        self.reindex(index, old);
+    }
+    fn num_of_leaves(&self) -> usize {
+        self.tree.num_of_leaves()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H: HashT, PB, T: Clone> Clone
+    for IndexedTree<ARITY, CAPACITY, H, PB, T>
+{
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            slots: self.slots,
+            tombstones: self.tombstones,
+            removed: self.removed,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Iterators over the leaves, layers and nodes of a tree, hiding the packed layout of the nodes.
+//! Layers are counted from the leaves (layer 0) up, the root is reported at layer = height.
+//! ```rust
+//! let mt = StaticTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from::<&[u8]>(&[
+//!     b"apple", b"apricot", b"banana",
+//! ]).unwrap();
+//!
+//! for (index, leaf) in mt.leaf_hashes().take(3) {
+//!     println!("{index}: {leaf:?}");
+//! }
+//! for layer in mt.layers() {
+//!     for node in layer {
+//!         println!("{:?}", node.hashes());
+//!     }
+//! }
+//! mt.visit(Order::TopDown, &mut |layer, index, hash| {
+//!     println!("{layer}:{index} {hash:?}");
+//! });
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::traits::{HashT, NodeAggregate};
+use crate::utils::{layer_base, location_in_prefixed};
+use crate::{layer_size, max_leaves};
+
+/// order of a walk over the nodes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Order {
+    /// from the root down to the leaves
+    TopDown,
+    /// from the leaves up to the root
+    BottomUp,
+}
+
+/// part of a Mountain Range a visited node belongs to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MountainPart {
+    /// peak at index
+    Peak(usize),
+    /// summit tree whose leaves are the roots of the peaks
+    Summit,
+}
+
+/// Iterator over the hashed leaves with their indices, the empty slots yield the default hash
+pub struct LeafHashes<'a, const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    leaves: &'a [Prefixed<ARITY, H, A>],
+    index: usize,
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> LeafHashes<'a, ARITY, H, A> {
+    pub(crate) fn new(leaves: &'a [Prefixed<ARITY, H, A>]) -> Self {
+        Self { leaves, index: 0 }
+    }
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Iterator for LeafHashes<'a, ARITY, H, A> {
+    type Item = (usize, H::Output);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(self.index);
+        let leaf = self.leaves.get(prefixed_index)?.hashes[offset];
+        self.index += 1;
+        Some((self.index - 1, leaf))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.leaves.len() * ARITY - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> ExactSizeIterator
+    for LeafHashes<'a, ARITY, H, A>
+{
+}
+
+/// Iterator over the layers from the leaves up, each as a slice of nodes holding ARITY hashes apiece
+pub struct Layers<'a, const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    nodes: &'a [Prefixed<ARITY, H, A>],
+    height: usize,
+    layer: usize,
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Layers<'a, ARITY, H, A> {
+    pub(crate) fn new(nodes: &'a [Prefixed<ARITY, H, A>], height: usize) -> Self {
+        Self {
+            nodes,
+            height,
+            layer: 0,
+        }
+    }
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Iterator for Layers<'a, ARITY, H, A> {
+    type Item = &'a [Prefixed<ARITY, H, A>];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.layer == self.height {
+            return None;
+        }
+        let base = layer_base::<ARITY>(self.height, self.layer);
+        let layer = &self.nodes[base..base + layer_size!(ARITY, self.height, self.layer)];
+        self.layer += 1;
+        Some(layer)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.height - self.layer;
+        (len, Some(len))
+    }
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> ExactSizeIterator
+    for Layers<'a, ARITY, H, A>
+{
+}
+
+/// Iterator over (layer, index, hash) of every node of a tree in the given order,
+/// nodes within a layer are yielded left to right, the root is at layer = height
+pub struct Walk<'a, const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    nodes: &'a [Prefixed<ARITY, H, A>],
+    root: H::Output,
+    height: usize,
+    order: Order,
+    // None when the walk is over
+    layer: Option<usize>,
+    index: usize,
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Walk<'a, ARITY, H, A> {
+    pub(crate) fn new(
+        nodes: &'a [Prefixed<ARITY, H, A>],
+        root: H::Output,
+        height: usize,
+        order: Order,
+    ) -> Self {
+        Self {
+            nodes,
+            root,
+            height,
+            order,
+            layer: Some(match order {
+                Order::TopDown => height,
+                Order::BottomUp => 0,
+            }),
+            index: 0,
+        }
+    }
+}
+
+impl<'a, const ARITY: usize, H: HashT, A: NodeAggregate> Iterator for Walk<'a, ARITY, H, A> {
+    type Item = (usize, usize, H::Output);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let layer = self.layer?;
+        let (hash, layer_len) = if layer == self.height {
+            (self.root, 1)
+        } else {
+            let (prefixed_index, offset) = location_in_prefixed::<ARITY>(self.index);
+            (
+                self.nodes[layer_base::<ARITY>(self.height, layer) + prefixed_index].hashes[offset],
+                max_leaves!(ARITY, self.height - layer),
+            )
+        };
+        let item = (layer, self.index, hash);
+
+        self.index += 1;
+        if self.index == layer_len {
+            self.index = 0;
+            self.layer = match self.order {
+                Order::TopDown => layer.checked_sub(1),
+                Order::BottomUp => (layer < self.height).then_some(layer + 1),
+            };
+        }
+        Some(item)
+    }
+}
@@ -0,0 +1,381 @@
+//! Tree wrapper journaling the nodes overwritten by every modification, so the tree is rolled back
+//! to a checkpoint without copying it as a whole.
+//! A modification records the nodes on the path of the modified leaf (height + 1 journal entries),
+//! a merge into an [crate::augmentable::AugmentableTree] or a compaction of a [crate::compactable::CompactableHeaplessTree]
+//! records the nodes over the span of the leaves it rewrites, a checkpoint takes one entry.
+//! No entries are recorded unless a checkpoint is open.
+//! Augmenting or reducing a tree changes its type, so it is done on the unwrapped tree (see [crate::journal::JournaledTree::into_inner]).
+//! The journal is of a fixed CAPACITY, a modification not fitting into it fails and the tree remains unmodified.
+//! ```rust
+//! use merkle_heapless::journal::JournaledTree;
+//!
+//! let mut journaled = JournaledTree::<ARITY, 64, _, _, _>::new(tree);
+//!
+//! let block = journaled.checkpoint().unwrap();
+//! journaled.try_replace(3, b"kiwi").unwrap();
+//! // a reorg
+//! journaled.rollback_to(block).unwrap();
+//! // or the block is final
+//! journaled.commit();
+//! ```
+
+use crate::augmentable::AugmentableTree;
+use crate::compactable::CompactableHeaplessTree;
+use crate::prefixed::Prefixed;
+use crate::traits::{AppendOnly, CanRemove, HashT, ProofBuilder, StaticTreeTrait, TreeNodes};
+use crate::utils::{layer_base, pow_arity};
+use crate::{is_arity, layer_size, max_leaves, num_of_prefixed, Assert, Error, IsTrue};
+use core::marker::PhantomData;
+use core::ops::Range;
+
+/// trait for a tree whose nodes a [crate::journal::JournaledTree] restores on rollback
+pub trait Restorable<const ARITY: usize, H: HashT>: TreeNodes<ARITY, H> {
+    /// overwrites a node at index within the contiguous nodes of the tree (layers from the leaves up)
+    fn restore_node(&mut self, index: usize, node: Prefixed<ARITY, H>);
+    /// brings the root and the bookkeeping of a leaf in line with the restored nodes on its path
+    fn restored(&mut self, leaf: usize);
+}
+
+enum Entry<const ARITY: usize, H: HashT> {
+    Checkpoint(usize),
+    // a modification of a span of leaves, followed by the previous nodes over it
+    Modified(usize, usize),
+    Node(usize, Prefixed<ARITY, H>),
+}
+
+impl<const ARITY: usize, H: HashT> Clone for Entry<ARITY, H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<const ARITY: usize, H: HashT> Copy for Entry<ARITY, H> {}
+
+/// Tree wrapper journaling the overwritten nodes in a fixed CAPACITY journal
+pub struct JournaledTree<const ARITY: usize, const CAPACITY: usize, H: HashT, PB, T> {
+    tree: T,
+    journal: [Entry<ARITY, H>; CAPACITY],
+    len: usize,
+    next_id: usize,
+    _marker: PhantomData<PB>,
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> JournaledTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + Restorable<ARITY, H>,
+{
+    /// wraps a tree with an empty journal
+    pub fn new(tree: T) -> Self {
+        Self {
+            tree,
+            journal: [Entry::Checkpoint(0); CAPACITY],
+            len: 0,
+            next_id: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    /// returns the underlying tree
+    pub fn tree(&self) -> &T {
+        &self.tree
+    }
+
+    /// returns the underlying tree dropping the journal
+    pub fn into_inner(self) -> T {
+        self.tree
+    }
+
+    /// opens a checkpoint to roll back to and returns its id,
+    /// fails if the journal is full
+    pub fn checkpoint(&mut self) -> Result<usize, Error> {
+        if self.len == CAPACITY {
+            return Err(Error::Journal);
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.journal[self.len] = Entry::Checkpoint(id);
+        self.len += 1;
+        Ok(id)
+    }
+
+    /// restores the tree as it was at the checkpoint, the checkpoint and the later ones are discarded.
+    /// Fails if the checkpoint is unknown (already committed or rolled back)
+    pub fn rollback_to(&mut self, id: usize) -> Result<(), Error> {
+        if !self.journal[..self.len]
+            .iter()
+            .any(|entry| matches!(entry, Entry::Checkpoint(i) if *i == id))
+        {
+            return Err(Error::Journal);
+        }
+        while self.len > 0 {
+            self.len -= 1;
+            match self.journal[self.len] {
+                Entry::Checkpoint(i) if i == id => break,
+                Entry::Checkpoint(_) => {}
+                Entry::Node(index, node) => self.tree.restore_node(index, node),
+                // the nodes over the span are restored by now
+                Entry::Modified(start, end) => {
+                    for leaf in start..end {
+                        self.tree.restored(leaf);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// keeps the modifications and discards all the checkpoints
+    pub fn commit(&mut self) {
+        self.len = 0;
+    }
+
+    /// returns the number of the open checkpoints
+    pub fn checkpoints(&self) -> usize {
+        self.journal[..self.len]
+            .iter()
+            .filter(|entry| matches!(entry, Entry::Checkpoint(_)))
+            .count()
+    }
+
+    /// returns the number of the journal entries in use
+    pub fn journal_len(&self) -> usize {
+        self.len
+    }
+
+    // records the nodes over a span of leaves about to be modified if a checkpoint is open
+    fn record(&mut self, leaves: Range<usize>) -> Result<(), Error> {
+        if self.len == 0 || leaves.is_empty() {
+            return Ok(());
+        }
+        let height = self.tree.height();
+        // the indices of the nodes over the span within a layer
+        let span = |layer: usize| {
+            let base = layer_base::<ARITY>(height, layer);
+            let width = pow_arity::<ARITY>(layer + 1);
+            base + leaves.start / width..base + (leaves.end - 1) / width + 1
+        };
+        if self.len + 1 + (0..height).map(|layer| span(layer).len()).sum::<usize>() > CAPACITY {
+            return Err(Error::Journal);
+        }
+        self.journal[self.len] = Entry::Modified(leaves.start, leaves.end);
+        self.len += 1;
+
+        let nodes = self.tree.nodes();
+        for index in (0..height).flat_map(span) {
+            self.journal[self.len] = Entry::Node(index, nodes[index]);
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// replaces an element at index with input,
+    /// fails if the journal is full
+    pub fn try_replace(&mut self, index: usize, input: &[u8]) -> Result<(), Error> {
+        self.record(index..index + 1)?;
+        self.tree.replace(index, input);
+        Ok(())
+    }
+
+    /// replaces a hashed leaf at index,
+    /// fails if the journal is full
+    pub fn try_replace_leaf(&mut self, index: usize, leaf: H::Output) -> Result<(), Error> {
+        self.record(index..index + 1)?;
+        self.tree.replace_leaf(index, leaf);
+        Ok(())
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> JournaledTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + Restorable<ARITY, H> + CanRemove,
+{
+    /// removes a leaf at index,
+    /// fails if the journal is full
+    pub fn try_remove(&mut self, index: usize) -> Result<(), Error> {
+        self.record(index..index + 1)?;
+        self.tree.remove(index);
+        Ok(())
+    }
+}
+
+impl<
+        const ARITY: usize,
+        const CAPACITY: usize,
+        const HEIGHT: usize,
+        H,
+        const MAX_INPUT_LEN: usize,
+        PB,
+    > JournaledTree<ARITY, CAPACITY, H, PB, AugmentableTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// merges a tree into the leaves following the present ones,
+    /// fails if there is not enough room for it or the journal is full
+    pub fn try_merge<const OTHER_HEIGHT: usize, OTHERPB: ProofBuilder<ARITY, H>>(
+        &mut self,
+        other: AugmentableTree<ARITY, OTHER_HEIGHT, H, MAX_INPUT_LEN, OTHERPB>,
+    ) -> Result<(), Error>
+    where
+        [(); num_of_prefixed!(ARITY, OTHER_HEIGHT)]: Sized,
+        Assert<{ OTHER_HEIGHT <= HEIGHT }>: IsTrue,
+    {
+        let start = self.tree.num_of_leaves();
+        let end = start + other.num_of_leaves();
+        if end > ARITY * layer_size!(ARITY, HEIGHT, 0) {
+            return Err(Error::Merge);
+        }
+        self.record(start..end)?;
+        self.tree.try_merge(other)
+    }
+}
+
+impl<
+        const ARITY: usize,
+        const CAPACITY: usize,
+        const HEIGHT: usize,
+        H,
+        const MAX_INPUT_LEN: usize,
+        PB,
+    >
+    JournaledTree<
+        ARITY,
+        CAPACITY,
+        H,
+        PB,
+        CompactableHeaplessTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>,
+    >
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// moves all present leaves leftwards,
+    /// fails if the journal is full
+    pub fn try_compact(&mut self) -> Result<(), Error> {
+        // the leaves from the first absent one up to the last occupied slot move
+        let default_hash = Prefixed::<ARITY, H>::default_hash();
+        let removed = self.tree.leaf_hash(&[]);
+        let start = self
+            .tree
+            .leaf_hashes()
+            .find_map(|(index, hash)| (hash == default_hash || hash == removed).then_some(index));
+        let end = self
+            .tree
+            .leaf_hashes()
+            .filter_map(|(index, hash)| (hash != default_hash).then_some(index + 1))
+            .last();
+        if let (Some(start), Some(end)) = (start, end) {
+            self.record(start..end)?;
+        }
+        self.tree.compact();
+        Ok(())
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> StaticTreeTrait<ARITY, H, PB>
+    for JournaledTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + Restorable<ARITY, H>,
+{
+    fn generate_proof(&self, index: usize) -> PB {
+        self.tree.generate_proof(index)
+    }
+    /// panics if the journal is full
+    fn replace(&mut self, index: usize, input: &[u8]) {
+        self.try_replace(index, input)
+            .expect("the journal has capacity");
+    }
+    /// panics if the journal is full
+    fn replace_leaf(&mut self, index: usize, leaf: H::Output) {
+        self.try_replace_leaf(index, leaf)
+            .expect("the journal has capacity");
+    }
+    fn root(&self) -> H::Output {
+        self.tree.root()
+    }
+    fn leaves(&self) -> &[Prefixed<ARITY, H>] {
+        self.tree.leaves()
+    }
+    fn base_layer_size(&self) -> usize {
+        self.tree.base_layer_size()
+    }
+    fn height(&self) -> usize {
+        self.tree.height()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> TreeNodes<ARITY, H>
+    for JournaledTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + Restorable<ARITY, H>,
+{
+    fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        self.tree.leaf_hash(input)
+    }
+    fn nodes(&self) -> &[Prefixed<ARITY, H>] {
+        self.tree.nodes()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> CanRemove
+    for JournaledTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + Restorable<ARITY, H> + CanRemove,
+{
+    /// panics if the journal is full
+    fn remove(&mut self, index: usize) {
+        self.try_remove(index).expect("the journal has capacity");
+    }
+    fn num_of_leaves(&self) -> usize {
+        self.tree.num_of_leaves()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H, PB, T> AppendOnly
+    for JournaledTree<ARITY, CAPACITY, H, PB, T>
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+    T: StaticTreeTrait<ARITY, H, PB> + Restorable<ARITY, H> + AppendOnly,
+{
+    /// fails if the tree or the journal is full
+    fn try_append(&mut self, input: &[u8]) -> Result<(), Error> {
+        let leaf = self.tree.num_of_leaves();
+        if leaf >= self.tree.base_layer_size() * ARITY {
+            return Err(Error::Append);
+        }
+        self.record(leaf..leaf + 1)?;
+        self.tree.try_append(input)
+    }
+    fn num_of_leaves(&self) -> usize {
+        self.tree.num_of_leaves()
+    }
+}
+
+impl<const ARITY: usize, const CAPACITY: usize, H: HashT, PB, T: Clone> Clone
+    for JournaledTree<ARITY, CAPACITY, H, PB, T>
+{
+    fn clone(&self) -> Self {
+        Self {
+            tree: self.tree.clone(),
+            journal: self.journal,
+            len: self.len,
+            next_id: self.next_id,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Memory layouts of the nodes of a tree, selectable per tree by the layout parameter of a [crate::StaticTree]
+//! or by wrapping the [crate::store::NodeStore] of a [crate::store::StoredTree] into a [crate::layout::LaidOut] store,
+//! so the layers are filled, a leaf is replaced and a proof is generated exactly as on the layers stored
+//! from the leaves up ([crate::layout::Layered], the default layout).
+//!
+//! The layered layout places the nodes on the path of a leaf far apart, one layer away from each other,
+//! so a proof of a tall tree touches a distinct cache line (or page) per layer.
+//! The [crate::layout::Blocked] layout splits the tree top-down into bands of BLOCK_HEIGHT layers
+//! and stores every subtree of a band contiguously, so a path crosses a block per BLOCK_HEIGHT layers.
+//! ```rust
+//! use merkle_heapless::layout::{Blocked, LaidOut, Layout};
+//! use merkle_heapless::store::StoredTree;
+//!
+//! let tree = StaticTree::<2, 16, StdHash, MAX_WORD_LEN, Proof<2, 16, StdHash, MAX_WORD_LEN>, (), Blocked<4>>::try_from(&words).unwrap();
+//! assert!(tree.generate_proof(7).validate(words[7]));
+//!
+//! let store = LaidOut::new([Prefixed::<2, StdHash>::default(); num_of_prefixed!(2, 16)], Blocked::<4>::new::<2>(16));
+//! let tree = StoredTree::<2, 16, StdHash, MAX_WORD_LEN, _>::try_from(store, &words).unwrap();
+//! assert!(tree.generate_proof(7).unwrap().validate(words[7]));
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::store::NodeStore;
+use crate::traits::{HashT, NodeAggregate};
+use crate::utils::pow_arity;
+use crate::{Assert, Error, IsTrue};
+use core::ops::{Deref, DerefMut};
+
+/// trait mapping the index of a node (layers from the leaves up) in a tree of given height to its position in a store
+pub trait Layout: Copy {
+    /// creates the layout of a tree of given height
+    fn new<const ARITY: usize>(height: usize) -> Self;
+    /// returns the position of a node at index
+    fn position(&self, index: usize) -> usize;
+}
+
+/// Layers stored contiguously from the leaves up, the position of a node is its index
+#[derive(Clone, Copy, Default, Debug)]
+pub struct Layered;
+
+impl Layout for Layered {
+    #[inline]
+    fn new<const ARITY: usize>(_height: usize) -> Self {
+        Self
+    }
+    #[inline]
+    fn position(&self, index: usize) -> usize {
+        index
+    }
+}
+
+// a tree of ARITY >= 2 has at most as many layers as the bits of its number of nodes
+const MAX_LAYERS: usize = usize::BITS as usize;
+
+// placement of the nodes of a layer by the blocked layout
+#[derive(Clone, Copy, Default, Debug)]
+struct BlockedLayer {
+    // index of the first node of the layer
+    first: usize,
+    // position of the first subtree of the band of the layer
+    band: usize,
+    // number of the nodes of a subtree of the band
+    subtree_nodes: usize,
+    // position of the first node of the layer within a subtree
+    within: usize,
+    // number of the nodes of the layer within a subtree
+    width: usize,
+}
+
+/// Bands of BLOCK_HEIGHT layers from the top down (the lowest band may be thinner),
+/// every subtree of a band stored contiguously in breadth-first order, the bands stored from the top down.
+/// The placement of every layer is computed once on creation, so a position costs a lookup of the layer and a division
+#[derive(Clone, Copy, Debug)]
+pub struct Blocked<const BLOCK_HEIGHT: usize> {
+    height: usize,
+    layers: [BlockedLayer; MAX_LAYERS],
+}
+
+impl<const BLOCK_HEIGHT: usize> Layout for Blocked<BLOCK_HEIGHT>
+where
+    Assert<{ BLOCK_HEIGHT > 0 }>: IsTrue,
+{
+    fn new<const ARITY: usize>(height: usize) -> Self {
+        // number of the nodes of a subtree of depth layers
+        let nodes = |depth: usize| (pow_arity::<ARITY>(depth) - 1) / (ARITY - 1);
+
+        let mut layers = [BlockedLayer::default(); MAX_LAYERS];
+        // the layer at depth counted from the top is preceded by the deeper layers
+        for depth in 0..height {
+            let band_top = depth - depth % BLOCK_HEIGHT;
+            let band_height = core::cmp::min(BLOCK_HEIGHT, height - band_top);
+            layers[height - 1 - depth] = BlockedLayer {
+                first: nodes(height) - nodes(depth + 1),
+                band: nodes(band_top),
+                subtree_nodes: nodes(band_height),
+                within: nodes(depth - band_top),
+                width: pow_arity::<ARITY>(depth - band_top),
+            };
+        }
+        Self { height, layers }
+    }
+
+    #[inline]
+    fn position(&self, index: usize) -> usize {
+        let layer = self.layers[..self.height].partition_point(|layer| layer.first <= index) - 1;
+        let layer = &self.layers[layer];
+        // the index of the node within its layer and the index of its subtree within the band
+        let index = index - layer.first;
+        let subtree = index / layer.width;
+
+        layer.band + subtree * layer.subtree_nodes + layer.within + (index - subtree * layer.width)
+    }
+}
+
+/// Store wrapper placing the nodes of a tree by a [Layout]
+#[derive(Clone, Copy)]
+pub struct LaidOut<S, L> {
+    pub(crate) store: S,
+    pub(crate) layout: L,
+}
+
+impl<S, L: Layout> LaidOut<S, L> {
+    /// wraps a store of at least num_of_prefixed!(ARITY, height) nodes placed by the layout of a tree of that height
+    pub fn new(store: S, layout: L) -> Self {
+        Self { store, layout }
+    }
+
+    /// returns the underlying store
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// returns the underlying store dropping the wrapper
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+impl<const ARITY: usize, const N: usize, H, A, L> LaidOut<[Prefixed<ARITY, H, A>; N], L>
+where
+    H: HashT,
+    A: NodeAggregate,
+    L: Layout,
+{
+    /// returns the in-memory node at index
+    #[inline]
+    pub(crate) fn node(&self, index: usize) -> &Prefixed<ARITY, H, A> {
+        &self.store[self.layout.position(index)]
+    }
+    /// returns the in-memory node at index for modification
+    #[inline]
+    pub(crate) fn node_mut(&mut self, index: usize) -> &mut Prefixed<ARITY, H, A> {
+        &mut self.store[self.layout.position(index)]
+    }
+}
+
+// the layered store is its underlying store, indexed as it is
+impl<S> Deref for LaidOut<S, Layered> {
+    type Target = S;
+
+    #[inline]
+    fn deref(&self) -> &S {
+        &self.store
+    }
+}
+
+impl<S> DerefMut for LaidOut<S, Layered> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.store
+    }
+}
+
+impl<const ARITY: usize, H, A, S, L> NodeStore<ARITY, H, A> for LaidOut<S, L>
+where
+    H: HashT,
+    A: NodeAggregate,
+    S: NodeStore<ARITY, H, A>,
+    L: Layout,
+{
+    #[inline]
+    fn read(&self, index: usize) -> Result<Prefixed<ARITY, H, A>, Error> {
+        self.store.read(self.layout.position(index))
+    }
+    #[inline]
+    fn write(&mut self, index: usize, node: &Prefixed<ARITY, H, A>) -> Result<(), Error> {
+        self.store.write(self.layout.position(index), node)
+    }
+    fn flush(&mut self) -> Result<(), Error> {
+        self.store.flush()
+    }
+}
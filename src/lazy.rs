@@ -0,0 +1,217 @@
+//! Tree wrapper deferring the rehashing of the modified paths up to the root.
+//! A replaced leaf marks its node dirty in a bitset over the nodes, the dirty nodes are rehashed
+//! layer by layer (in batches, see [crate::traits::HashT::hash_many]) only when the root or a proof
+//! is requested or on an explicit flush, so the paths shared by many updates are hashed once.
+//! The flushed tree is identical to the one modified eagerly.
+//! ```rust
+//! use merkle_heapless::lazy::LazyTree;
+//!
+//! let mut lazy = LazyTree::new(tree);
+//! for (i, word) in words.iter().enumerate() {
+//!     lazy.replace(i, word);
+//! }
+//! // the dirty paths are rehashed here
+//! let root = lazy.root();
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::proof::Proof;
+use crate::traits::{HashT, NodeAggregate, ProofBuilder, StaticTreeTrait};
+use crate::utils::{self, location_in_prefixed, HASH_LANES};
+use crate::{is_arity, layer_size, num_of_prefixed, Assert, Error, IsTrue, StaticTree};
+
+/// Tree wrapper tracking the dirty nodes in a bitset and rehashing them on demand,
+/// a flush skips the clean 64-node words of the bitset
+pub struct LazyTree<
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H,
+    const MAX_INPUT_LEN: usize,
+    PB = Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+    A = (),
+> where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); num_of_prefixed!(ARITY, HEIGHT) / 64 + 1]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    tree: StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>,
+    dirty: [u64; num_of_prefixed!(ARITY, HEIGHT) / 64 + 1],
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A>
+    LazyTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); num_of_prefixed!(ARITY, HEIGHT) / 64 + 1]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    /// wraps a tree with no dirty nodes
+    pub fn new(tree: StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>) -> Self {
+        Self {
+            tree,
+            dirty: [0; num_of_prefixed!(ARITY, HEIGHT) / 64 + 1],
+        }
+    }
+
+    /// returns the underlying tree, flushed
+    /// panics if aggregates cannot be combined
+    pub fn tree(&mut self) -> &StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A> {
+        self.flush().expect("aggregates are combinable");
+        &self.tree
+    }
+
+    /// returns the underlying tree, flushed, dropping the bitset
+    /// panics if aggregates cannot be combined
+    pub fn into_inner(mut self) -> StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A> {
+        self.flush().expect("aggregates are combinable");
+        self.tree
+    }
+
+    // the first dirty node in from..end, the clean words of the bitset are skipped
+    fn next_dirty(&self, from: usize, end: usize) -> Option<usize> {
+        let mut word = from / 64;
+        let mut bits = self.dirty[word] & (!0 << (from % 64));
+        loop {
+            if bits != 0 {
+                let index = word * 64 + bits.trailing_zeros() as usize;
+                return (index < end).then_some(index);
+            }
+            word += 1;
+            if word * 64 >= end {
+                return None;
+            }
+            bits = self.dirty[word];
+        }
+    }
+
+    fn set_dirty(&mut self, index: usize, dirty: bool) {
+        if dirty {
+            self.dirty[index / 64] |= 1 << (index % 64);
+        } else {
+            self.dirty[index / 64] &= !(1 << (index % 64));
+        }
+    }
+
+    /// returns true if some nodes are to be rehashed
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.iter().any(|word| *word != 0)
+    }
+
+    /// replaces an element at index with input and its aggregate, the path is rehashed on flush
+    /// panics if index is out of leaf layer bound
+    pub fn replace_with(&mut self, index: usize, input: &[u8], aggregate: impl Into<A>) {
+        self.replace_leaf_with(
+            index,
+            utils::hash_leaf::<H, MAX_INPUT_LEN>(input),
+            aggregate,
+        );
+    }
+
+    /// replaces a hashed leaf at index and its aggregate, the path is rehashed on flush
+    /// panics if index is out of leaf layer bound
+    pub fn replace_leaf_with(&mut self, index: usize, leaf: H::Output, aggregate: impl Into<A>) {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        self.tree.prefixed[prefixed_index].hashes[offset] = leaf;
+        self.tree.prefixed[prefixed_index].aggregates[offset] = aggregate.into();
+        self.set_dirty(prefixed_index, true);
+    }
+
+    /// replaces an element at index with input, the path is rehashed on flush,
+    /// its aggregate is derived from the input by [NodeAggregate::from_input]
+    /// panics if index is out of leaf layer bound
+    pub fn replace(&mut self, index: usize, input: &[u8]) {
+        self.replace_with(index, input, A::from_input(input));
+    }
+
+    /// replaces a hashed leaf at index, its aggregate becomes empty, the path is rehashed on flush
+    /// panics if index is out of leaf layer bound
+    pub fn replace_leaf(&mut self, index: usize, leaf: H::Output) {
+        self.replace_leaf_with(index, leaf, A::default());
+    }
+
+    /// rehashes the dirty nodes layer by layer and updates the root,
+    /// fails if aggregates cannot be combined, the nodes failing to combine stay dirty
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if !self.is_dirty() {
+            return Ok(());
+        }
+        let mut nodes = [Prefixed::<ARITY, H, A>::default(); HASH_LANES];
+        let mut indices = [0; HASH_LANES];
+        let mut hashes = [H::Output::default(); HASH_LANES];
+        let mut layer_base = 0;
+
+        for layer in 0..HEIGHT - 1 {
+            let parent_layer_base = layer_base + layer_size!(ARITY, HEIGHT, layer);
+            let mut index = layer_base;
+            loop {
+                // the next batch of the dirty nodes of the layer
+                let mut batch = 0;
+                while batch < HASH_LANES {
+                    let Some(dirty) = self.next_dirty(index, parent_layer_base) else {
+                        break;
+                    };
+                    indices[batch] = dirty;
+                    nodes[batch] = self.tree.prefixed[dirty];
+                    batch += 1;
+                    index = dirty + 1;
+                }
+                if batch == 0 {
+                    break;
+                }
+                utils::hash_nodes(&nodes[..batch], &mut hashes[..batch]);
+
+                for ((node, hash), index) in nodes.iter().zip(hashes).zip(indices).take(batch) {
+                    // an emptied node becomes padding again, as on an eager replace
+                    let (hash, aggregate) = if node.is_padding() {
+                        (Prefixed::<ARITY, H, A>::default_hash(), A::default())
+                    } else {
+                        (hash, node.aggregate().ok_or(Error::Overflow)?)
+                    };
+                    let (parent, offset) = location_in_prefixed::<ARITY>(index - layer_base);
+                    let parent = parent_layer_base + parent;
+                    self.tree.prefixed[parent].hashes[offset] = hash;
+                    self.tree.prefixed[parent].aggregates[offset] = aggregate;
+                    self.set_dirty(parent, true);
+                    self.set_dirty(index, false);
+                }
+            }
+            // move on to the upper layer
+            layer_base = parent_layer_base;
+        }
+
+        let top = self.tree.prefixed[layer_base];
+        self.tree.root_aggregate = top.aggregate().ok_or(Error::Overflow)?;
+        self.tree.root = top.hash_all();
+        self.set_dirty(layer_base, false);
+        Ok(())
+    }
+
+    /// returns the root, rehashing the dirty nodes
+    /// panics if aggregates cannot be combined
+    pub fn root(&mut self) -> H::Output {
+        self.tree().root()
+    }
+
+    /// returns the aggregate of the whole tree, rehashing the dirty nodes
+    /// panics if aggregates cannot be combined
+    pub fn root_aggregate(&mut self) -> A {
+        self.tree().root_aggregate()
+    }
+
+    /// generates proof at given index on base layer, rehashing the dirty nodes
+    /// panics if aggregates cannot be combined
+    pub fn generate_proof(&mut self, index: usize) -> PB {
+        self.tree().generate_proof(index)
+    }
+
+    /// returns the hash of an input as a leaf of the tree
+    pub fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        utils::hash_leaf::<H, MAX_INPUT_LEN>(input)
+    }
+}
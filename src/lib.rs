@@ -3,14 +3,37 @@
 //! This Merkle tree is implemented as a contiguous memory array and does not betake to dynamic allocations.
 //! As such it allows for certain optimizations and compile-time imposed constraints on arity and size boundaries.
 //! - no std dependencies (actually no dependencies)
-//! - 2, 4, 8,... power of 2 general branching arity
+//! - any branching arity from 2 up (2, 3, 4, 10,...), powers of 2 indexed by shifts
 //! - any hash function that takes ```&[u8]``` and returns something that implements ```AsRef<[u8]>```
+//! - optional batched hashing of leaves and nodes for multi-lane (SIMD) hash implementations
 //! - 99% safe Rust
 //! - optionally augmentable or reducible
+//! - single proofs for contiguous spans of leaves
+//! - chaining sequences of proofs of nested trees into a single proof
+//! - mixed-arity proofs chaining trees of different arity (e.g. a binary subtree into an 8-ary tree)
+//! - compressed proofs omitting the empty siblings of partially filled trees
+//! - diffing replicas by descending into mismatching nodes only
+//! - transport-agnostic sync of replicas over fixed-size messages
+//! - checkpoints and rollbacks journaling the overwritten nodes only
+//! - optional lazy rehashing of the modified paths on demand
+//! - trees built at compile time with a const hash implementation
+//! - nodes kept in RAM or in a pluggable storage (flash, EEPROM, a file)
+//! - cache-friendly subtree-blocked layout of the nodes selectable per tree
+//! - zero-copy read-only views of serialized (e.g. memory-mapped) trees
+//! - optionally resizable at runtime within a compile-time capacity
+//! - optionally unbalanced (RFC 6962 shaped) with proofs as long as the number of leaves requires
+//! - optionally sorted by key, with range and exclusion proofs
+//! - salted leaves disclosed selectively, one leaf at a time
+//! - hash-based (post-quantum) Merkle signatures of one-time keys
+//! - optional user-defined aggregates committed into the nodes (sum, count, interval trees)
+//! - optional parallel construction of trees (when compiled with a rayon feature)
+//! - optional Graphviz DOT and ASCII rendering of trees (when compiled with a std feature)
+//! - optional construction of trees at compile time (when compiled with a const feature)
 //! - optional Mountain Range proc macro (when compiled with a mmr-macro feature)
 //!
 //! ## Hashing
 //! Leaves are prefixed with ```LEAF_HASH_PREPEND_VALUE``` prior to being hashed, while the intermediate nodes are prefixed with ```[1u8; 4]```.
+//! Salted leaves are hashed as ```LEAF_HASH_PREPEND_VALUE || salt || value```.
 //!
 //! # Mountain Range
 //!
@@ -139,6 +162,9 @@
 #![allow(incomplete_features)]
 #![feature(generic_const_exprs)]
 #![feature(trivial_bounds)]
+#![cfg_attr(feature = "const", feature(const_trait_impl))]
+#![cfg_attr(feature = "const", feature(effects))]
+#![cfg_attr(feature = "const", feature(const_refs_to_cell))]
 #![warn(missing_docs)]
 /// contains implementation of an extention for a Merkle Tree that can be augmented into a bigger tree
 // and merge a smaller tree into the tree
@@ -146,13 +172,56 @@ pub mod augmentable;
 /// contains implementation of an extention for a Merkle Tree that can remove a leaf, compact and reduce
 /// the tree to a smaller tree.
 pub mod compactable;
+/// contains a compressed form of a proof omitting the empty siblings
+pub mod compressed;
+/// contains Graphviz DOT and ASCII rendering of trees with highlighted proof paths
+#[cfg(feature = "std")]
+pub mod export;
+/// contains a tree wrapper keeping a hash index of the leaves for position lookups
+pub mod indexed;
+/// contains iterators over leaves, layers and nodes of a tree
+pub mod iter;
+/// contains a tree wrapper journaling overwritten nodes for checkpoints and rollbacks
+pub mod journal;
+/// contains memory layouts of the nodes of a tree selectable by wrapping its store
+pub mod layout;
+/// contains a tree wrapper tracking dirty nodes and rehashing them on demand
+pub mod lazy;
+/// contains proofs of a per-level arity chaining proofs of trees of different arity
+pub mod mixed;
+/// contains a hash-based Merkle Signature Scheme of one-time keys whose public keys are the leaves of a tree
+pub mod mss;
+/// contains parallel construction of a [StaticTree]
+#[cfg(feature = "rayon")]
+pub mod parallel;
 /// prefixed hashes
 pub mod prefixed;
 /// module containing [Proof] implementation the [StaticTree] generates
 pub mod proof;
+/// contains construction of a [StaticTree] in a const context
+#[cfg(feature = "const")]
+pub mod const_tree;
+/// contains iterators over differing leaves of two replicas of the same tree shape
+pub mod diff;
+/// contains implementation of a Merkle Tree with a fixed capacity whose height can be changed at runtime
+pub mod resizable;
+/// contains salted leaves and selective disclosure of a single leaf
+pub mod salted;
+/// contains a trait abstracting the storage of the nodes, a tree over it and a file-backed store
+pub mod store;
+/// contains implementation of a Merkle Tree keeping its leaves sorted by key, with range and exclusion proofs
+pub mod sorted;
+/// contains implementation of a Merkle Sum Tree which nodes carry sums of balances ([sum_tree::Sum] aggregates) along with hashes
+pub mod sum_tree;
+/// contains state machines syncing two replicas of the same tree shape by exchanging fixed-size messages
+pub mod sync;
 /// module declaring basic traits for tree and proof
 pub mod traits;
+/// contains a tree wrapper shaped by its number of leaves, generating minimal proofs
+pub mod unbalanced;
 mod utils;
+/// contains a zero-copy read-only view of a serialized tree and the serialization of a [StaticTree]
+pub mod view;
 
 #[cfg(feature = "mmr_macro")]
 pub use mmr_macro;
@@ -161,9 +230,13 @@ use core::fmt::Debug;
 use core::mem::size_of;
 use core::ops::Deref;
 
+use crate::diff::{Diff, NodeSource, RemoteDiff};
 use crate::prefixed::Prefixed;
 use crate::proof::Proof;
-use crate::traits::{HashT, ProofBuilder, StaticTreeTrait};
+use crate::journal::Restorable;
+use crate::layout::{Blocked, LaidOut, Layered, Layout};
+use crate::sync::SyncReplica;
+use crate::traits::{HashT, NodeAggregate, ProofBuilder, StaticTreeTrait, TreeNodes};
 use crate::utils::{location_in_prefixed, Assert, IsTrue};
 
 /// leaves will be prepended with this value prior to hashing
@@ -173,6 +246,7 @@ pub const INNER_HASH_PREPEND_VALUE: u8 = 1;
 
 /// Merkle Tree Errors
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// Error on tree creation
     Create,
@@ -180,6 +254,20 @@ pub enum Error {
     Append,
     /// Error on merging
     Merge,
+    /// Error on growing or shrinking a tree
+    Resize,
+    /// Error on overflowing an aggregated value
+    Overflow,
+    /// Error on inserting a key already present
+    Duplicate,
+    /// Error on signing with all the one-time keys used
+    Exhausted,
+    /// Error on a full journal or an unknown checkpoint
+    Journal,
+    /// Error on reading or writing a node store
+    Store,
+    /// Error on chaining a proof whose leaf is not the root of the chain or overflowing the chain
+    Chain,
 }
 
 /// type alias for [StaticTree] with arity of 2
@@ -190,20 +278,27 @@ pub type StaticBinaryTree<
     PB = Proof<2, HEIGHT, H, MAX_INPUT_LEN>,
 > = StaticTree<2, HEIGHT, H, MAX_INPUT_LEN, PB>;
 /// Basic statically-allocated Merkle Tree
+/// optionally carrying a [NodeAggregate] of the children in every node,
+/// its nodes are placed in memory by a [Layout] (the layers from the leaves up by default)
 pub struct StaticTree<
     const ARITY: usize,
     const HEIGHT: usize,
     H,
     const MAX_INPUT_LEN: usize,
     PB = Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+    A = (),
+    L = Layered,
 > where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
     root: H::Output,
-    prefixed: [Prefixed<ARITY, H>; num_of_prefixed!(ARITY, HEIGHT)],
+    root_aggregate: A,
+    prefixed: LaidOut<[Prefixed<ARITY, H, A>; num_of_prefixed!(ARITY, HEIGHT)], L>,
 }
 
 // impl<'a, T, const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> TryFrom<&'a [T]> for
@@ -211,7 +306,7 @@ pub struct StaticTree<
 // where
 //     T: AsRef<[u8]> + Deref<Target = [u8]>,
 //     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-//     Assert<{ is_pow2!(ARITY) }>: IsTrue,
+//     Assert<{ is_arity!(ARITY) }>: IsTrue,
 //     H: HashT,
 //     PB: ProofBuilder<ARITY, H>,
 // {
@@ -221,241 +316,330 @@ pub struct StaticTree<
 //     }
 // }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
-    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A, L>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, L>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
     const BASE_LAYER_SIZE: usize = layer_size!(ARITY, HEIGHT, 0);
 
     fn create(input_len: usize) -> Result<Self, Error> {
         (input_len <= Self::BASE_LAYER_SIZE * ARITY)
-            .then_some(Self {
-                root: Default::default(),
-                prefixed: [Default::default(); num_of_prefixed!(ARITY, HEIGHT)],
-            })
+            .then_some(Self::default())
             .ok_or(Error::Create)
     }
 
-    /// creates a tree from an input if possible
+    /// creates a tree from an input if possible,
+    /// leaf aggregates are derived from the input by [NodeAggregate::from_input]
     pub fn try_from<T: AsRef<[u8]> + Deref<Target = [u8]>>(input: &[T]) -> Result<Self, Error> {
-        Self::create(input.len()).map(|this| this.create_inner(input, 0))
+        Self::create(input.len()).and_then(|this| this.create_inner(input, 0))
     }
 
     /// creates a tree from an input if possible
+    /// panics if aggregates of the input cannot be combined
     pub fn from<T: AsRef<[u8]> + Deref<Target = [u8]>>(input: &[T]) -> Self {
-        Self {
-            root: Default::default(),
-            prefixed: [Default::default(); num_of_prefixed!(ARITY, HEIGHT)],
-        }
-        .create_inner(input, 0)
+        Self::default()
+            .create_inner(input, 0)
+            .expect("aggregates of the input are combinable")
+    }
+
+    /// creates a tree from an input paired with leaf aggregates if possible
+    pub fn try_from_aggregated<T: AsRef<[u8]> + Deref<Target = [u8]>, B: Into<A> + Copy>(
+        input: &[(T, B)],
+    ) -> Result<Self, Error> {
+        Self::create(input.len()).and_then(|this| {
+            this.create_with_aggregates(
+                input.iter().map(|(d, aggregate)| (d.as_ref(), (*aggregate).into())),
+                0,
+            )
+        })
     }
 
     #[inline]
     pub(crate) fn pad_leaves(&mut self, from_index: usize) {
-        let default_hash = Prefixed::<ARITY, H>::default_hash();
-        let default_hashes = [default_hash; ARITY];
-        let to_index = core::cmp::min((from_index / ARITY + 1) * ARITY, max_leaves!(ARITY, HEIGHT));
-        // pad first partial prefixed hashes in the base layer
-        for i in from_index..to_index {
-            let (index, offset) = location_in_prefixed::<ARITY>(i);
-            self.prefixed[index].hashes[offset] = default_hash;
-        }
-        // pad the rest of hashes in the base layer
-        let start_prefixed_index = to_index / ARITY;
-        for i in start_prefixed_index..layer_size!(ARITY, HEIGHT, 0) {
-            self.prefixed[i].hashes = default_hashes;
-        }
+        utils::pad_leaves(&mut self.prefixed, HEIGHT, from_index)
+            .expect("in-memory nodes are writable. qed");
     }
 
     pub(crate) fn create_inner<T: AsRef<[u8]> + Deref<Target = [u8]>>(
-        mut self,
+        self,
         input: &[T],
         with_offset: usize,
-    ) -> Self {
-        let mut prefixed = [LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN];
-
-        let start_index = if input.iter().map(|d| d.len()).max() < Some(MAX_INPUT_LEN) {
-            1
-        } else {
-            0
-        };
-        // fill the base layer
-        for (i, d) in input.iter().enumerate() {
-            prefixed[start_index..d.len() + start_index].copy_from_slice(d.as_ref());
+    ) -> Result<Self, Error> {
+        self.create_with_aggregates(
+            input.iter().map(|d| (d.as_ref(), A::from_input(d))),
+            with_offset,
+        )
+    }
 
+    fn create_with_aggregates<'a>(
+        mut self,
+        input: impl Iterator<Item = (&'a [u8], A)> + Clone,
+        with_offset: usize,
+    ) -> Result<Self, Error> {
+        let omit_prefix = input.clone().map(|(d, _)| d.len()).max() >= Some(MAX_INPUT_LEN);
+        // fill the base layer
+        utils::hash_leaves::<H, MAX_INPUT_LEN>(
+            input.clone().map(|(d, _)| d),
+            omit_prefix,
+            |i, hash| {
+                let (index, offset) = location_in_prefixed::<ARITY>(i + with_offset);
+                self.prefixed.node_mut(index).hashes[offset] = hash;
+            },
+        );
+        let mut input_len = 0;
+        for (i, (_, aggregate)) in input.enumerate() {
             let (index, offset) = location_in_prefixed::<ARITY>(i + with_offset);
-            self.prefixed[index].hashes[offset] = H::hash(&prefixed[0..d.len() + start_index]);
+            self.prefixed.node_mut(index).aggregates[offset] = aggregate;
+            input_len += 1;
         }
 
-        self.pad_leaves(input.len());
+        self.pad_leaves(input_len);
         // fill the rest of layers
-        self.fill_layers(input.len());
-        self
+        self.fill_layers(input_len)?;
+        Ok(self)
     }
 
     /// creates a tree from hashed leaves (of another tree)
-    pub fn try_from_leaves(leaves: &[Prefixed<ARITY, H>]) -> Result<Self, Error> {
-        Self::create(leaves.len()).map(|this| this.with_leaves_inner(leaves, 0))
+    pub fn try_from_leaves(leaves: &[Prefixed<ARITY, H, A>]) -> Result<Self, Error> {
+        Self::create(leaves.len()).and_then(|this| this.with_leaves_inner(leaves, 0))
     }
 
     pub(crate) fn with_leaves_inner(
         mut self,
-        leaves: &[Prefixed<ARITY, H>],
+        leaves: &[Prefixed<ARITY, H, A>],
         with_offset: usize,
-    ) -> Self {
+    ) -> Result<Self, Error> {
         let mut i = with_offset;
 
         for leaf in leaves {
-            for h in leaf.hashes {
+            for (h, aggregate) in leaf.hashes.into_iter().zip(leaf.aggregates) {
                 let (index, offset) = location_in_prefixed::<ARITY>(i);
 
-                self.prefixed[index].hashes[offset] = h;
+                self.prefixed.node_mut(index).hashes[offset] = h;
+                self.prefixed.node_mut(index).aggregates[offset] = aggregate;
                 i += 1;
             }
         }
         self.pad_leaves(i);
         // fill the rest of layers
-        self.fill_layers(with_offset + leaves.len() * ARITY);
-        self
-    }
-
-    fn fill_layers(&mut self, data_len: usize) {
-        let mut start_ind = 0;
-        let mut next_layer_ind = Self::BASE_LAYER_SIZE;
-        let mut data_len_aligned = if data_len & (ARITY - 1) == 0 {
-            data_len / ARITY
-        } else {
-            data_len / ARITY + 1
-        };
-        let default_hash = Prefixed::<ARITY, H>::default_hash();
-
-        for h in 0..HEIGHT - 1 {
-            // hash packed siblings of the current layer and fill the upper layer
-            for i in start_ind..data_len_aligned {
-                let offset = i & (ARITY - 1); // index modulo ARITY
-                let (j, _) = self.parent_index_and_base(i, h, start_ind);
-
-                // hash concatenated siblings from the contiguous memory
-                // each element has (ARITY-1) siblings
-                // store it as a parent hash
-                self.prefixed[j].hashes[offset] = self.prefixed[i].hash_all();
-            }
-            for i in data_len_aligned..next_layer_ind {
-                let offset = i & (ARITY - 1); // index modulo ARITY
-                let (j, _) = self.parent_index_and_base(i, h, start_ind);
+        self.fill_layers(with_offset + leaves.len() * ARITY)?;
+        Ok(self)
+    }
 
-                self.prefixed[j].hashes[offset] = default_hash;
-            }
+    pub(crate) fn fill_layers(&mut self, data_len: usize) -> Result<(), Error> {
+        (self.root, self.root_aggregate) =
+            utils::fill_layers(&mut self.prefixed, HEIGHT, data_len)?;
+        Ok(())
+    }
 
-            let d = next_layer_ind - start_ind;
-            // move on to the upper layer
-            start_ind = next_layer_ind;
-            next_layer_ind += d >> ARITY.trailing_zeros();
-            data_len_aligned = start_ind
-                + if data_len_aligned & (ARITY - 1) == 0 {
-                    data_len_aligned / ARITY
-                } else {
-                    data_len_aligned / ARITY + 1
-                };
-            data_len_aligned = core::cmp::min(data_len_aligned, next_layer_ind);
+    // on failure the previous leaf is restored, so the tree remains unmodified
+    pub(crate) fn try_replace_inner(
+        &mut self,
+        index: usize,
+        leaf: H::Output,
+        aggregate: A,
+    ) -> Result<(), Error> {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        let node = self.prefixed.node_mut(prefixed_index);
+        let (old_leaf, old_aggregate) = (node.hashes[offset], node.aggregates[offset]);
+
+        node.hashes[offset] = leaf;
+        node.aggregates[offset] = aggregate;
+
+        match utils::replace_inner(&mut self.prefixed, HEIGHT, index) {
+            Ok((root, root_aggregate)) => {
+                (self.root, self.root_aggregate) = (root, root_aggregate);
+                Ok(())
+            }
+            Err(e) => {
+                let node = self.prefixed.node_mut(prefixed_index);
+                node.hashes[offset] = old_leaf;
+                node.aggregates[offset] = old_aggregate;
+                utils::replace_inner(&mut self.prefixed, HEIGHT, index)
+                    .expect("the previous aggregates were combinable. qed");
+                Err(e)
+            }
         }
+    }
 
-        self.root = self
-            .prefixed
-            .iter()
-            .last()
-            .expect("prefixed buffer is not empty. qed")
-            .hash_all();
+    /// replaces an element at index with input and its aggregate,
+    /// the tree remains unmodified if aggregates cannot be combined
+    /// panics if index is out of leaf layer bound
+    pub fn try_replace_with(
+        &mut self,
+        index: usize,
+        input: &[u8],
+        aggregate: impl Into<A>,
+    ) -> Result<(), Error> {
+        self.try_replace_inner(
+            index,
+            utils::hash_leaf::<H, MAX_INPUT_LEN>(input),
+            aggregate.into(),
+        )
     }
 
-    #[inline]
-    fn parent_index_and_base(
-        &self,
+    /// replaces a hashed leaf at index and its aggregate,
+    /// the tree remains unmodified if aggregates cannot be combined
+    /// panics if index is out of leaf layer bound
+    pub fn try_replace_leaf_with(
+        &mut self,
         index: usize,
-        layer: usize,
-        layer_base: usize,
-    ) -> (usize, usize) {
-        let curr_layer_len = layer_size!(ARITY, HEIGHT, layer);
-        let parent_layer_base = layer_base + curr_layer_len;
-        //        println!("index: {index}, layer_base: {layer_base}");
-        let parent_index = parent_layer_base + ((index - layer_base) >> ARITY.trailing_zeros());
-        //        println!("parent_index: {parent_index}");
+        leaf: H::Output,
+        aggregate: impl Into<A>,
+    ) -> Result<(), Error> {
+        self.try_replace_inner(index, leaf, aggregate.into())
+    }
 
-        (parent_index, parent_layer_base)
+    /// replaces elements at indices with inputs like [StaticTreeTrait::replace] one after another,
+    /// the leaves and the paths of up to 8 of them at a time are hashed in batches (see [HashT::hash_many])
+    /// panics if an index is out of leaf layer bound or aggregates cannot be combined
+    pub fn replace_many<T: AsRef<[u8]>>(&mut self, updates: &[(usize, T)]) {
+        for updates in updates.chunks(utils::HASH_LANES) {
+            let mut indices = [0; utils::HASH_LANES];
+            for (index, (i, input)) in indices.iter_mut().zip(updates) {
+                assert!(*i < Self::BASE_LAYER_SIZE * ARITY, "index is out of leaf layer bound");
+                *index = *i;
+                let (prefixed_index, offset) = location_in_prefixed::<ARITY>(*i);
+                self.prefixed.node_mut(prefixed_index).aggregates[offset] =
+                    A::from_input(input.as_ref());
+            }
+            utils::hash_leaves::<H, MAX_INPUT_LEN>(
+                updates.iter().map(|(_, input)| input.as_ref()),
+                false,
+                |k, hash| {
+                    let (prefixed_index, offset) = location_in_prefixed::<ARITY>(indices[k]);
+                    self.prefixed.node_mut(prefixed_index).hashes[offset] = hash;
+                },
+            );
+            (self.root, self.root_aggregate) =
+                utils::replace_batch(&mut self.prefixed, HEIGHT, &indices[..updates.len()])
+                    .expect("aggregates are combinable");
+        }
     }
 
-    fn replace_inner(&mut self, index: usize) {
-        let mut layer_base = 0;
-        let mut j = index / ARITY;
+    /// returns the aggregate of the whole tree
+    pub fn root_aggregate(&self) -> A {
+        self.root_aggregate
+    }
 
-        // start from the base layer and propagate the new hashes upwords
-        for layer in 0..HEIGHT - 1 {
-            let parent_hashed = self.prefixed[j].hash_all();
+    /// returns the aggregate of a leaf at index
+    /// panics if index is out of leaf layer bound
+    pub fn aggregate(&self, index: usize) -> A {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        self.prefixed.node(prefixed_index).aggregates[offset]
+    }
 
-            let offset = j & (ARITY - 1); // index modulo ARITY
-            (j, layer_base) = self.parent_index_and_base(j, layer, layer_base);
+    /// returns a node at index within a layer (layers are counted from the leaves)
+    pub fn node(&self, layer: usize, index: usize) -> Option<&Prefixed<ARITY, H, A>> {
+        (layer < HEIGHT && index < layer_size!(ARITY, HEIGHT, layer))
+            .then(|| self.prefixed.node(utils::layer_base::<ARITY>(HEIGHT, layer) + index))
+    }
 
-            self.prefixed[j].hashes[offset] = parent_hashed;
-        }
-        self.root = self
-            .prefixed
-            .iter()
-            .last()
-            .expect("prefixed buffer is not empty. qed")
-            .hash_all();
+    fn generate_proof_inner(&self, index: usize) -> PB {
+        utils::generate_proof(&self.prefixed, HEIGHT, self.root, index)
+            .expect("in-memory nodes are readable. qed")
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
-    StaticTreeTrait<ARITY, H, PB> for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+// the nodes of a blocked tree are not stored layer after layer, so it has no slices of leaves and nodes
+// and exposes the rest of [StaticTreeTrait] on its own
+impl<
+        const ARITY: usize,
+        const HEIGHT: usize,
+        H,
+        const MAX_INPUT_LEN: usize,
+        PB,
+        A,
+        const BLOCK_HEIGHT: usize,
+    > StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, Blocked<BLOCK_HEIGHT>>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    Assert<{ BLOCK_HEIGHT > 0 }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
 {
-    /// generate proof at given index on base layer
-    fn generate_proof(&self, index: usize) -> PB {
-        let mut proof = PB::from_root(self.root());
-        let mut layer_base = 0;
-        let mut j = index / ARITY;
-        let mut offset = index & (ARITY - 1); // index modulo ARITY (power of 2)
+    /// generates a proof for a leaf at index
+    pub fn generate_proof(&self, index: usize) -> PB {
+        self.generate_proof_inner(index)
+    }
 
-        for layer in 0..HEIGHT {
-            proof.push(offset, self.prefixed[j]);
+    /// replaces an element at index with input,
+    /// its aggregate is derived from the input by [NodeAggregate::from_input]
+    /// panics if index is out of leaf layer bound or aggregates cannot be combined
+    pub fn replace(&mut self, index: usize, input: &[u8]) {
+        self.try_replace_with(index, input, A::from_input(input))
+            .expect("aggregates are combinable");
+    }
 
-            offset = j & (ARITY - 1); // index modulo ARITY
-            (j, layer_base) = self.parent_index_and_base(j, layer, layer_base);
-        }
-        proof
+    /// returns the root
+    pub fn root(&self) -> H::Output {
+        self.root
     }
-    /// replace an element at index with input
-    /// panics if index is out of leaf layer bound
-    fn replace(&mut self, index: usize, input: &[u8]) {
-        let mut prefixed = [LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN];
-        let prefixed_len = input.len() + 1;
-        prefixed[1..prefixed_len].copy_from_slice(input);
+}
 
-        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    /// iterates over indices of the leaves differing from another tree,
+    /// descends only into mismatching nodes
+    pub fn diff<'a>(&'a self, other: &'a Self) -> Diff<'a, ARITY, H, A> {
+        Diff::new(&self.prefixed[..], &other.prefixed[..], HEIGHT)
+    }
 
-        self.prefixed[prefixed_index].hashes[offset] = H::hash(&prefixed[0..prefixed_len]);
-        self.replace_inner(index);
+    /// iterates over indices of the leaves differing from a remote replica,
+    /// fetches only the remote nodes mismatching on the way down
+    pub fn diff_remote<R: NodeSource<ARITY, H>>(
+        &self,
+        remote: R,
+    ) -> RemoteDiff<'_, ARITY, HEIGHT, H, R, A> {
+        RemoteDiff::new(&self.prefixed[..], HEIGHT, remote)
     }
+}
 
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A>
+    StaticTreeTrait<ARITY, H, PB, A> for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    /// generate proof at given index on base layer
+    fn generate_proof(&self, index: usize) -> PB {
+        self.generate_proof_inner(index)
+    }
+    /// replace an element at index with input,
+    /// its aggregate is derived from the input by [NodeAggregate::from_input]
+    /// panics if index is out of leaf layer bound or aggregates cannot be combined
+    fn replace(&mut self, index: usize, input: &[u8]) {
+        self.try_replace_with(index, input, A::from_input(input))
+            .expect("aggregates are combinable");
+    }
+    /// replace a hashed leaf at index, its aggregate becomes empty
+    /// panics if index is out of leaf layer bound
     fn replace_leaf(&mut self, index: usize, leaf: H::Output) {
-        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
-        self.prefixed[prefixed_index].hashes[offset] = leaf;
-
-        self.replace_inner(index);
+        self.try_replace_inner(index, leaf, A::default())
+            .expect("aggregates are combinable");
     }
     fn root(&self) -> H::Output {
         self.root
     }
-    fn leaves(&self) -> &[Prefixed<ARITY, H>] {
+    fn leaves(&self) -> &[Prefixed<ARITY, H, A>] {
         &self.prefixed[..layer_size!(ARITY, HEIGHT, 0)]
     }
     fn base_layer_size(&self) -> usize {
@@ -469,65 +653,128 @@ where
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Clone
-    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A>
+    TreeNodes<ARITY, H, A> for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        utils::hash_leaf::<H, MAX_INPUT_LEN>(input)
+    }
+    fn nodes(&self) -> &[Prefixed<ARITY, H, A>] {
+        &self.prefixed[..]
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    SyncReplica<ARITY, HEIGHT, H> for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn node_hashes(&self, layer: usize, index: usize) -> Option<[H::Output; ARITY]> {
+        self.node(layer, index).map(|node| node.hashes)
+    }
+    fn adopt_leaf(&mut self, index: usize, leaf: H::Output) {
+        self.replace_leaf(index, leaf);
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    Restorable<ARITY, H> for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
     PB: ProofBuilder<ARITY, H>,
+{
+    fn restore_node(&mut self, index: usize, node: Prefixed<ARITY, H>) {
+        self.prefixed[index] = node;
+    }
+    fn restored(&mut self, _leaf: usize) {
+        self.root = self.prefixed[num_of_prefixed!(ARITY, HEIGHT) - 1].hash_all();
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A, L> Clone
+    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, L>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
     fn clone(&self) -> Self {
         *self
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Copy
-    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A, L> Copy
+    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, L>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Default
-    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A, L> Default
+    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, L>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
     fn default() -> Self {
         Self {
             root: Default::default(),
-            prefixed: [Default::default(); num_of_prefixed!(ARITY, HEIGHT)],
+            root_aggregate: Default::default(),
+            prefixed: LaidOut::new(
+                [Default::default(); num_of_prefixed!(ARITY, HEIGHT)],
+                L::new::<ARITY>(HEIGHT),
+            ),
         }
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> PartialEq
-    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A, L> PartialEq
+    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, L>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
     fn eq(&self, other: &Self) -> bool {
-        self.root() == other.root() && self.height() == other.height()
+        self.root == other.root && self.root_aggregate == other.root_aggregate
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Debug
-    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A, L> Debug
+    for StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A, L>
 where
     [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
-    Assert<{ is_pow2!(ARITY) }>: IsTrue,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
     H: HashT,
-    PB: ProofBuilder<ARITY, H>,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    L: Layout,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         writeln!(f, "[arity]:   {ARITY}")?;
@@ -537,9 +784,13 @@ where
             f,
             "[total bytes]:     {}",
             size_of::<H::Output>()
-                + size_of::<Prefixed<ARITY, H>>() * num_of_prefixed!(ARITY, HEIGHT)
+                + size_of::<A>()
+                + size_of::<Prefixed<ARITY, H, A>>() * num_of_prefixed!(ARITY, HEIGHT)
         )?;
         writeln!(f, "[hash output len]: {} bytes", size_of::<H::Output>())?;
-        write!(f, "{:?}", self.prefixed)
+        if size_of::<A>() != 0 {
+            writeln!(f, "[root aggregate]:  {:?}", self.root_aggregate)?;
+        }
+        write!(f, "{:?}", self.prefixed.store())
     }
 }
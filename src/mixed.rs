@@ -0,0 +1,282 @@
+//! Proofs made up of items of a per-level arity (up to MAX_ARITY), so proofs of trees of different arity are chained,
+//! e.g. a proof from a binary subtree with a proof from an 8-ary summit tree.
+//! A tree generates a [crate::mixed::MixedProof] directly when it is its proof builder,
+//! or a [crate::proof::ProofChain] composes proofs of any arity into one
+//! checking that every proof chained proves the root of the chain (and its aggregate) as its leaf.
+//! ```rust
+//! let proof = ProofChain::<8, 16, StdHash, MAX_WORD_LEN>::new(binary.generate_proof(2))
+//!     .and_then(|chain| chain.then(octal.generate_proof(9)))
+//!     .unwrap()
+//!     .finish();
+//! assert!(proof.validate(b"banana"));
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::traits::{HashT, NodeAggregate, ProofBuilder, ProofItemT, ProofValidator};
+use crate::{Assert, IsTrue};
+use core::fmt::Debug;
+
+// hashes the first ARITY hashes and aggregates of a node as a node of ARITY children,
+// returns its hash and aggregate
+fn hash_node<const ARITY: usize, const MAX_ARITY: usize, H: HashT, A: NodeAggregate>(
+    node: &Prefixed<MAX_ARITY, H, A>,
+) -> Option<(H::Output, A)> {
+    let mut prefixed = Prefixed::<ARITY, H, A>::default();
+    prefixed.hashes.copy_from_slice(&node.hashes[..ARITY]);
+    prefixed
+        .aggregates
+        .copy_from_slice(&node.aggregates[..ARITY]);
+    Some((prefixed.hash_all(), prefixed.aggregate()?))
+}
+
+// hashes the children of an item as a node of its arity, returns its hash and aggregate
+type HashNode<const MAX_ARITY: usize, H, A> =
+    fn(&Prefixed<MAX_ARITY, H, A>) -> Option<(<H as HashT>::Output, A)>;
+
+/// Item of a [MixedProof], a node of any arity up to MAX_ARITY
+pub struct MixedProofItem<const MAX_ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    // the first arity hashes and aggregates are the node's children
+    prefixed: Prefixed<MAX_ARITY, H, A>,
+    arity: usize,
+    offset: usize,
+    hash: HashNode<MAX_ARITY, H, A>,
+}
+
+impl<const MAX_ARITY: usize, H: HashT, A: NodeAggregate> MixedProofItem<MAX_ARITY, H, A> {
+    /// returns item's hashes (as many as its arity)
+    pub fn hashes(&self) -> &[H::Output] {
+        &self.prefixed.hashes[..self.arity]
+    }
+
+    /// returns item's aggregates (as many as its arity)
+    pub fn aggregates(&self) -> &[A] {
+        &self.prefixed.aggregates[..self.arity]
+    }
+
+    /// returns item's arity
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// returns item's offset
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    fn hash_with(mut self, word_hash: H::Output, aggregate: A) -> Option<(H::Output, A)> {
+        self.prefixed.hashes[self.offset] = word_hash;
+        self.prefixed.aggregates[self.offset] = aggregate;
+        (self.hash)(&self.prefixed)
+    }
+}
+
+impl<const ARITY: usize, const MAX_ARITY: usize, H: HashT, A: NodeAggregate> ProofItemT<ARITY, H, A>
+    for MixedProofItem<MAX_ARITY, H, A>
+where
+    Assert<{ ARITY <= MAX_ARITY }>: IsTrue,
+{
+    /// constructor
+    fn create(offset: usize, prefixed: Prefixed<ARITY, H, A>) -> Self {
+        let mut item = Self::default();
+        item.prefixed.hashes[..ARITY].copy_from_slice(prefixed.hashes());
+        item.prefixed.aggregates[..ARITY].copy_from_slice(prefixed.aggregates());
+        item.arity = ARITY;
+        item.offset = offset;
+        item.hash = hash_node::<ARITY, MAX_ARITY, H, A>;
+        item
+    }
+    /// hashes a provided hashed data and its aggregate at offset with its siblings
+    fn hash_with_siblings(self, word_hash: H::Output, aggregate: A) -> Option<(H::Output, A)> {
+        self.hash_with(word_hash, aggregate)
+    }
+}
+
+impl<const MAX_ARITY: usize, H: HashT, A: NodeAggregate> Copy for MixedProofItem<MAX_ARITY, H, A> {}
+
+impl<const MAX_ARITY: usize, H: HashT, A: NodeAggregate> Clone for MixedProofItem<MAX_ARITY, H, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const MAX_ARITY: usize, H: HashT, A: NodeAggregate> Default
+    for MixedProofItem<MAX_ARITY, H, A>
+{
+    fn default() -> Self {
+        Self {
+            prefixed: Default::default(),
+            arity: 0,
+            offset: 0,
+            hash: hash_node::<MAX_ARITY, MAX_ARITY, H, A>,
+        }
+    }
+}
+
+impl<const MAX_ARITY: usize, H: HashT, A: NodeAggregate> Debug for MixedProofItem<MAX_ARITY, H, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "{:?}", self.hashes())?;
+        if core::mem::size_of::<A>() != 0 {
+            writeln!(f, "{:?}", self.aggregates())?;
+        }
+        Ok(())
+    }
+}
+
+/// Proof of items of a per-level arity up to MAX_ARITY, at most HEIGHT items
+pub struct MixedProof<
+    const MAX_ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+    A: NodeAggregate = (),
+> where
+    [(); HEIGHT]: Sized,
+{
+    pub(crate) root: H::Output,
+    pub(crate) root_aggregate: A,
+    pub(crate) height: usize,
+    pub(crate) items: [MixedProofItem<MAX_ARITY, H, A>; HEIGHT],
+}
+
+impl<
+        const ARITY: usize,
+        const MAX_ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        A: NodeAggregate,
+    > ProofBuilder<ARITY, H, A> for MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+    Assert<{ ARITY <= MAX_ARITY }>: IsTrue,
+{
+    type Item = MixedProofItem<MAX_ARITY, H, A>;
+
+    fn from_root(root: H::Output) -> Self {
+        Self {
+            root,
+            ..Default::default()
+        }
+    }
+
+    fn set_root_aggregate(&mut self, aggregate: A) {
+        self.root_aggregate = aggregate;
+    }
+
+    fn push(&mut self, offset: usize, prefixed: Prefixed<ARITY, H, A>) {
+        self.items[self.height] = <Self::Item as ProofItemT<ARITY, H, A>>::create(offset, prefixed);
+        self.height += 1;
+    }
+}
+
+impl<
+        const MAX_ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        A: NodeAggregate,
+    > ProofValidator for MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that the input was contained in the Merkle tree that generated this proof,
+    /// the input's aggregate is derived by [NodeAggregate::from_input]
+    fn validate(self, input: &[u8]) -> bool {
+        self.validate_with(input, A::from_input(input))
+    }
+}
+
+impl<
+        const MAX_ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        A: NodeAggregate,
+    > MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that the input with its aggregate was contained in the Merkle tree that generated this proof
+    /// and that the aggregates along the path sum up to the root aggregate
+    pub fn validate_with(self, input: &[u8], aggregate: impl Into<A>) -> bool {
+        let leaf = crate::utils::hash_leaf::<H, MAX_INPUT_LEN>(input);
+        self.path()
+            .iter()
+            .try_fold((leaf, aggregate.into()), |(h, a), item| {
+                item.hash_with(h, a)
+            })
+            == Some((self.root, self.root_aggregate))
+    }
+
+    /// returns the aggregate of the proof's root
+    pub fn root_aggregate(&self) -> A {
+        self.root_aggregate
+    }
+
+    /// returns the proof's length
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// returns the proof's root
+    pub fn root(&self) -> H::Output {
+        self.root
+    }
+
+    /// returns the proof's path (as long as the proof's height)
+    pub fn path(&self) -> &[MixedProofItem<MAX_ARITY, H, A>] {
+        &self.items[..self.height]
+    }
+
+    /// returns the index of claim as a leaf of the chained trees,
+    /// counted in the leaves of the lowest tree
+    pub fn claim_index(&self) -> usize {
+        let mut a = 1usize;
+        let mut index = 0;
+        for item in self.path() {
+            index += a * item.offset();
+            a *= item.arity();
+        }
+        index
+    }
+}
+
+impl<
+        const MAX_ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        A: NodeAggregate,
+    > Default for MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    fn default() -> Self {
+        Self {
+            root: Default::default(),
+            root_aggregate: Default::default(),
+            height: 0,
+            items: [Default::default(); HEIGHT],
+        }
+    }
+}
+
+impl<
+        const MAX_ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        A: NodeAggregate,
+    > Debug for MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[proof height]:   {:?}", self.height)?;
+        writeln!(f, "[proof root]:   {:?}", self.root)?;
+        if core::mem::size_of::<A>() != 0 {
+            writeln!(f, "[proof root aggregate]:   {:?}", self.root_aggregate)?;
+        }
+        write!(f, "{:?}", self.path())
+    }
+}
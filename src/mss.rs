@@ -0,0 +1,289 @@
+//! Merkle Signature Scheme: a hash-based, post-quantum signature scheme with a limited number of signatures.
+//! Every leaf of a [crate::StaticTree] is the hash of the public key of a Winternitz one-time key (WOTS, w = 16),
+//! the root of the tree is the long-term public key.
+//! A signature carries the one-time signature of the message digest, the leaf index and the [crate::proof::Proof]
+//! authenticating the one-time public key against the root.
+//!
+//! The one-time keys are derived from a seed, so the signer stores the seed and the index of the next unused leaf only.
+//! Signing is stateful: a leaf must never sign twice, so the index should be persisted before a signature is released.
+//! N is the number of leading bytes of the message digest that are signed, at most the hash output length
+//! and [crate::mss::MAX_MESSAGE_LEN],
+//! the output being [crate::traits::PlainBytes] of at most 128 bytes.
+//! ```rust
+//! use merkle_heapless::mss::Signer;
+//!
+//! // 16 one-time keys signing 32 bytes digests
+//! let mut signer = Signer::<2, 4, Blake2_256Hash, 32>::from_seed(&seed);
+//! let public_key = signer.public_key();
+//!
+//! let signature = signer.sign(b"firmware image").unwrap();
+//! // persist signer.next_index() prior to releasing the signature
+//! assert!(signature.verify(&public_key, b"firmware image"));
+//! ```
+
+use crate::proof::Proof;
+use crate::traits::{HashT, PlainBytes, StaticTreeTrait};
+use crate::utils::location_in_prefixed;
+use crate::{is_arity, layer_size, max_leaves, num_of_prefixed, Assert, Error, IsTrue, StaticTree};
+use core::fmt::Debug;
+use core::marker::PhantomData;
+use core::mem::size_of;
+
+#[macro_export]
+/// number of hash chains of a one-time key signing N digest bytes:
+/// two base-16 digits per byte and three digits of the checksum
+macro_rules! wots_len {
+    ($n:expr) => {
+        2 * $n + 3
+    };
+}
+
+// Winternitz parameter, every chain is of W - 1 steps
+const W: u8 = 16;
+// number of base-16 digits of the checksum, see [wots_len]
+const CHECKSUM_DIGITS: u32 = 3;
+
+/// the longest digest prefix N a one-time key signs:
+/// the checksum of its 2 * N digits, at most 2 * N * (W - 1), must fit in 3 base-16 digits
+pub const MAX_MESSAGE_LEN: usize = ((W as usize).pow(CHECKSUM_DIGITS) - 1) / (2 * (W as usize - 1));
+// domain separation of the hashes from the leaves and the inner nodes of the tree
+const SECRET_PREPEND_VALUE: u8 = 2;
+const CHAIN_PREPEND_VALUE: u8 = 3;
+const PUBLIC_KEY_PREPEND_VALUE: u8 = 4;
+
+// the tree leaves are set as hashed public keys, no input is hashed by the tree
+type KeyTree<const ARITY: usize, const HEIGHT: usize, H> = StaticTree<ARITY, HEIGHT, H, 0>;
+type KeyProof<const ARITY: usize, const HEIGHT: usize, H> = Proof<ARITY, HEIGHT, H, 0>;
+
+// longest hash output tweaked, 1024 bits
+const MAX_TWEAKED_LEN: usize = 128;
+
+// a hash tweaked by its position: the value followed by the address of the leaf,
+// the chain and the step within the chain
+struct Tweaked<O>(PhantomData<O>);
+
+impl<O: PlainBytes> Tweaked<O> {
+    const FITS: () = assert!(
+        size_of::<O>() <= MAX_TWEAKED_LEN,
+        "hash output is too long to be tweaked"
+    );
+
+    fn hash<H: HashT<Output = O>>(prepend: u8, value: O, leaf: usize, chain: usize, step: u8) -> O {
+        let () = Self::FITS;
+        let mut address = [prepend, 0, 0, 0, 0, 0, 0, step];
+        address[1..5].copy_from_slice(&(leaf as u32).to_be_bytes());
+        address[5..7].copy_from_slice(&(chain as u16).to_be_bytes());
+
+        let value = value.as_bytes();
+        let mut input = [0u8; MAX_TWEAKED_LEN + 8];
+        input[..value.len()].copy_from_slice(value);
+        input[value.len()..value.len() + 8].copy_from_slice(&address);
+        H::hash(&input[..value.len() + 8])
+    }
+}
+
+// the ends of the chains hashed together with a prefix
+#[repr(C)]
+struct PublicKey<O, const LEN: usize> {
+    prefix: O,
+    ends: [O; LEN],
+}
+
+impl<O: From<u8>, const LEN: usize> PublicKey<O, LEN> {
+    fn hash<H: HashT<Output = O>>(ends: [O; LEN]) -> O {
+        let this = Self {
+            prefix: PUBLIC_KEY_PREPEND_VALUE.into(),
+            ends,
+        };
+        H::concat_then_hash(unsafe {
+            core::slice::from_raw_parts(&this as *const Self as *const O, LEN + 1)
+        })
+    }
+}
+
+// advances a chain value from step `from` to step `to`
+fn chain<H: HashT>(mut value: H::Output, leaf: usize, chain: usize, from: u8, to: u8) -> H::Output
+where
+    H::Output: PlainBytes,
+{
+    for step in from..to {
+        value = Tweaked::hash::<H>(CHAIN_PREPEND_VALUE, value, leaf, chain, step);
+    }
+    value
+}
+
+// base-16 digits of the leading N bytes of the message digest followed by the digits of their checksum
+fn digits<H: HashT, const N: usize>(message: &[u8]) -> [u8; wots_len!(N)]
+where
+    H::Output: PlainBytes,
+{
+    let digest = H::hash(message);
+    let bytes = digest.as_bytes();
+    let mut digits = [0u8; wots_len!(N)];
+    for (i, b) in bytes[..N].iter().enumerate() {
+        digits[2 * i] = b >> 4;
+        digits[2 * i + 1] = b & (W - 1);
+    }
+    // the checksum grows as the digits decrease, so none of them can be advanced by a forger
+    let checksum = digits[..2 * N]
+        .iter()
+        .map(|d| (W - 1 - d) as usize)
+        .sum::<usize>();
+    for (i, d) in digits[2 * N..].iter_mut().enumerate() {
+        *d = ((checksum >> (4 * (CHECKSUM_DIGITS as usize - 1 - i))) & (W as usize - 1)) as u8;
+    }
+    digits
+}
+
+/// Stateful signer holding a tree of one-time public keys derived from a seed
+pub struct Signer<const ARITY: usize, const HEIGHT: usize, H, const N: usize>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    master: H::Output,
+    tree: KeyTree<ARITY, HEIGHT, H>,
+    next: usize,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const N: usize> Signer<ARITY, HEIGHT, H, N>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); HEIGHT]: Sized,
+    [(); wots_len!(N)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    Assert<{ N <= MAX_MESSAGE_LEN }>: IsTrue,
+    H: HashT,
+    H::Output: PlainBytes,
+{
+    /// generates all the one-time keys from a seed and the tree of their public keys,
+    /// panics if N exceeds the hash output length
+    pub fn from_seed(seed: &[u8]) -> Self {
+        Self::resume(seed, 0)
+    }
+
+    /// generates the keys from a seed, the leaves prior to the next unused one are considered used
+    /// panics if N exceeds the hash output length
+    pub fn resume(seed: &[u8], next: usize) -> Self {
+        assert!(N <= core::mem::size_of::<H::Output>());
+
+        let mut this = Self {
+            master: H::hash(seed),
+            tree: KeyTree::default(),
+            next,
+        };
+        for leaf in 0..max_leaves!(ARITY, HEIGHT) {
+            let (index, offset) = location_in_prefixed::<ARITY>(leaf);
+            this.tree.prefixed[index].hashes[offset] = this.one_time_public_key(leaf);
+        }
+        this.tree
+            .fill_layers(max_leaves!(ARITY, HEIGHT))
+            .expect("no aggregates to overflow. qed");
+        this
+    }
+
+    fn secret(&self, leaf: usize, chain: usize) -> H::Output {
+        Tweaked::hash::<H>(SECRET_PREPEND_VALUE, self.master, leaf, chain, 0)
+    }
+
+    fn one_time_public_key(&self, leaf: usize) -> H::Output {
+        let mut ends = [H::Output::default(); wots_len!(N)];
+        for (i, end) in ends.iter_mut().enumerate() {
+            *end = chain::<H>(self.secret(leaf, i), leaf, i, 0, W - 1);
+        }
+        PublicKey::<_, { wots_len!(N) }>::hash::<H>(ends)
+    }
+
+    /// returns the long-term public key (the root of the tree)
+    pub fn public_key(&self) -> H::Output {
+        self.tree.root()
+    }
+
+    /// returns the index of the next unused one-time key, to be persisted by the caller
+    pub fn next_index(&self) -> usize {
+        self.next
+    }
+
+    /// returns the number of signatures left
+    pub fn remaining(&self) -> usize {
+        max_leaves!(ARITY, HEIGHT).saturating_sub(self.next)
+    }
+
+    /// signs a message with the next unused one-time key and marks it used,
+    /// fails if all the keys are used
+    pub fn sign(&mut self, message: &[u8]) -> Result<Signature<ARITY, HEIGHT, H, N>, Error> {
+        if self.remaining() == 0 {
+            return Err(Error::Exhausted);
+        }
+        let leaf = self.next;
+        self.next += 1;
+
+        let mut chains = [H::Output::default(); wots_len!(N)];
+        for (i, (value, digit)) in chains.iter_mut().zip(digits::<H, N>(message)).enumerate() {
+            *value = chain::<H>(self.secret(leaf, i), leaf, i, 0, digit);
+        }
+        Ok(Signature {
+            chains,
+            proof: self.tree.generate_proof(leaf),
+        })
+    }
+}
+
+/// One-time signature of a message along with the proof of its one-time public key
+pub struct Signature<const ARITY: usize, const HEIGHT: usize, H: HashT, const N: usize>
+where
+    [(); HEIGHT]: Sized,
+    [(); wots_len!(N)]: Sized,
+{
+    chains: [H::Output; wots_len!(N)],
+    proof: KeyProof<ARITY, HEIGHT, H>,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const N: usize>
+    Signature<ARITY, HEIGHT, H, N>
+where
+    [(); HEIGHT]: Sized,
+    [(); wots_len!(N)]: Sized,
+{
+    /// verifies that the message was signed by a one-time key of the tree with the public key (root)
+    pub fn verify(self, public_key: &H::Output, message: &[u8]) -> bool
+    where
+        H::Output: PlainBytes,
+    {
+        if N > core::mem::size_of::<H::Output>() || &self.proof.root() != public_key {
+            return false;
+        }
+        let leaf = self.index();
+        // complete the chains up to their ends
+        let mut ends = self.chains;
+        for (i, (end, digit)) in ends.iter_mut().zip(digits::<H, N>(message)).enumerate() {
+            *end = chain::<H>(*end, leaf, i, digit, W - 1);
+        }
+        self.proof
+            .validate_leaf(PublicKey::<_, { wots_len!(N) }>::hash::<H>(ends), ())
+    }
+
+    /// returns the index of the one-time key that signed
+    pub fn index(&self) -> usize {
+        self.proof.claim_index()
+    }
+
+    /// returns the proof of the one-time public key
+    pub fn proof(&self) -> &KeyProof<ARITY, HEIGHT, H> {
+        &self.proof
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const N: usize> Debug
+    for Signature<ARITY, HEIGHT, H, N>
+where
+    [(); HEIGHT]: Sized,
+    [(); wots_len!(N)]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[index]:   {}", self.index())?;
+        writeln!(f, "[chains]:   {:?}", self.chains)?;
+        write!(f, "{:?}", self.proof)
+    }
+}
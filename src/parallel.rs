@@ -0,0 +1,110 @@
+//! Parallel construction of a [crate::StaticTree] (when compiled with a rayon feature).
+//! The leaves are hashed in parallel chunks of ARITY inputs, then every layer is hashed in parallel
+//! into the next one: the layers are contiguous, so a layer and the one above it are split apart
+//! and every parent node is filled from its own chunk of children.
+//! The resulting tree (and its root) is bit-identical to the one built serially.
+//! ```rust
+//! use merkle_heapless::StaticBinaryTree;
+//!
+//! let tree = StaticBinaryTree::<20, Blake2_256Hash, 32>::par_try_from(&words).unwrap();
+//! assert_eq!(tree.root(), StaticBinaryTree::<20, Blake2_256Hash, 32>::try_from(&words).unwrap().root());
+//! ```
+
+use rayon::prelude::*;
+
+use crate::prefixed::Prefixed;
+use crate::traits::{HashT, NodeAggregate, ProofBuilder};
+use crate::utils::{self, location_in_prefixed};
+use crate::{is_arity, layer_size, num_of_prefixed, Assert, Error, IsTrue, StaticTree};
+
+/// hashes the layers of a tree of given height bottom-up in parallel and returns the root with its aggregate,
+/// fails if aggregates cannot be combined
+pub(crate) fn par_fill_layers<const ARITY: usize, H, A>(
+    prefixed: &mut [Prefixed<ARITY, H, A>],
+    height: usize,
+    data_len: usize,
+) -> Result<(H::Output, A), Error>
+where
+    H: HashT,
+    H::Output: Send + Sync,
+    A: NodeAggregate + Send + Sync,
+{
+    let mut layer_base = 0;
+    // number of the nodes holding data in the current layer, the rest are padding
+    let mut data_nodes = data_len.div_ceil(ARITY);
+
+    for layer in 0..height - 1 {
+        let layer_len = layer_size!(ARITY, height, layer);
+        let (lower, upper) = prefixed.split_at_mut(layer_base + layer_len);
+        // every parent is written once with the hashes of its chunk of children
+        upper[..layer_len / ARITY]
+            .par_iter_mut()
+            .zip(lower[layer_base..].par_chunks(ARITY))
+            .enumerate()
+            .try_for_each(|(parent, (node, children))| {
+                let data = core::cmp::min(ARITY, data_nodes.saturating_sub(parent * ARITY));
+                *node = Prefixed::default();
+                utils::hash_nodes(&children[..data], &mut node.hashes[..data]);
+                for ((hash, aggregate), child) in node
+                    .hashes
+                    .iter_mut()
+                    .zip(node.aggregates.iter_mut())
+                    .zip(&children[..data])
+                {
+                    // a data node with all its children padding is padding, as on a replace
+                    if child.is_padding() {
+                        *hash = Prefixed::<ARITY, H, A>::default_hash();
+                    } else {
+                        *aggregate = child.aggregate().ok_or(Error::Overflow)?;
+                    }
+                }
+                Ok(())
+            })?;
+        // move on to the upper layer
+        layer_base += layer_len;
+        data_nodes = data_nodes.div_ceil(ARITY);
+    }
+
+    let top = &prefixed[layer_base];
+    Ok((top.hash_all(), top.aggregate().ok_or(Error::Overflow)?))
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    H::Output: Send + Sync,
+    A: NodeAggregate + Send + Sync,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    /// creates a tree from an input if possible, hashing the leaves and the layers in parallel,
+    /// leaf aggregates are derived from the input by [NodeAggregate::from_input]
+    pub fn par_try_from<T: AsRef<[u8]> + Sync>(input: &[T]) -> Result<Self, Error> {
+        let mut this = Self::create(input.len())?;
+
+        let omit_prefix = input.iter().map(|d| d.as_ref().len()).max() >= Some(MAX_INPUT_LEN);
+        // fill the base layer, the input is laid out in the leaves contiguously
+        let (data_nodes, _) = location_in_prefixed::<ARITY>(input.len() + ARITY - 1);
+        this.prefixed[..data_nodes]
+            .par_iter_mut()
+            .zip(input.par_chunks(ARITY))
+            .for_each(|(node, chunk)| {
+                utils::hash_leaves::<H, MAX_INPUT_LEN>(
+                    chunk.iter().map(AsRef::as_ref),
+                    omit_prefix,
+                    |offset, hash| node.hashes[offset] = hash,
+                );
+                for (aggregate, d) in node.aggregates.iter_mut().zip(chunk) {
+                    *aggregate = A::from_input(d.as_ref());
+                }
+            });
+
+        this.pad_leaves(input.len());
+        // fill the rest of layers
+        (this.root, this.root_aggregate) =
+            par_fill_layers(&mut this.prefixed[..], HEIGHT, input.len())?;
+        Ok(this)
+    }
+}
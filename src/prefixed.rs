@@ -1,34 +1,88 @@
 use core::fmt::Debug;
+use core::mem::size_of;
 use core::slice::from_raw_parts;
 
-use crate::traits::HashT;
+use crate::traits::{HashT, NodeAggregate, PlainBytes};
 
-/// structure containing a prefix (aligned to 4 bytes) and hashes as a contiguous memory block
+/// structure containing a prefix (aligned to 4 bytes), hashes and their aggregates as a contiguous memory block
 /// prefix is used to prevent a proof length extension attack
 #[repr(C)]
-pub struct Prefixed<const ARITY: usize, H: HashT> {
+pub struct Prefixed<const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
 //    prefix: [u8; 4],
-    prefix: H::Output,
+    pub(crate) prefix: H::Output,
     pub(crate) hashes: [H::Output; ARITY],
+    pub(crate) aggregates: [A; ARITY],
+}
+
+/// input of a node with aggregates hashed by [Prefixed::hash_all]: the prefix, the hashes and the encoded aggregates,
+/// packed so there is no padding between them
+#[repr(C, packed)]
+pub(crate) struct NodeInput<const ARITY: usize, H: HashT, A: NodeAggregate> {
+    prefix: H::Output,
+    hashes: [H::Output; ARITY],
+    aggregates: [A::Encoded; ARITY],
 }
 
-impl<const ARITY: usize, H: HashT> Prefixed<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Clone for NodeInput<ARITY, H, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Copy for NodeInput<ARITY, H, A> {}
+// the hashes are read as bytes as by [HashT::concat_then_hash] and the aggregates are encoded into plain bytes,
+// packed with no padding between them
+unsafe impl<const ARITY: usize, H: HashT, A: NodeAggregate> PlainBytes for NodeInput<ARITY, H, A> {}
+
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Prefixed<ARITY, H, A> {
     /// hash of &[] prefixed with LEAF_HASH_PREPEND_VALUE
     #[inline]
     pub fn default_hash() -> H::Output {
         //        H::hash(&[crate::LEAF_HASH_PREPEND_VALUE; 1])
         H::Output::default()
     }
-    /// hash the prefix together with inner hashes
+    /// hash the prefix together with inner hashes and their aggregates
     #[inline]
     pub fn hash_all(&self) -> H::Output {
-        unsafe {
-            H::concat_then_hash(from_raw_parts(
-                &self.prefix as *const <H as HashT>::Output,
-                ARITY + 1,
-            ))
+        if size_of::<A>() == 0 {
+            H::concat_then_hash(self.concat_input())
+        } else {
+            H::hash(self.hash_input().as_bytes())
         }
     }
+    /// the prefix and the hashes concatenated by [Prefixed::hash_all] for a node without aggregates
+    #[inline]
+    pub(crate) fn concat_input(&self) -> &[H::Output] {
+        // the prefix is followed by the hashes in the C layout
+        unsafe { from_raw_parts(&self.prefix as *const <H as HashT>::Output, ARITY + 1) }
+    }
+    /// the prefix, the hashes and the encoded aggregates hashed by [Prefixed::hash_all] as bytes
+    #[inline]
+    pub(crate) fn hash_input(&self) -> NodeInput<ARITY, H, A> {
+        NodeInput {
+            prefix: self.prefix,
+            hashes: self.hashes,
+            aggregates: self.aggregates.map(|a| a.encode()),
+        }
+    }
+    /// returns hashes of the children
+    pub fn hashes(&self) -> &[H::Output; ARITY] {
+        &self.hashes
+    }
+    /// returns aggregates of the children
+    pub fn aggregates(&self) -> &[A; ARITY] {
+        &self.aggregates
+    }
+    /// whether all children are padding (empty-subtree hashes with empty aggregates),
+    /// such a node is padding itself whether it is filled or updated
+    #[inline]
+    pub fn is_padding(&self) -> bool {
+        self.hashes == [Self::default_hash(); ARITY] && self.aggregates == [A::default(); ARITY]
+    }
+    /// combines aggregates of the children into the aggregate of this node
+    #[inline]
+    pub fn aggregate(&self) -> Option<A> {
+        A::combine(&self.aggregates)
+    }
 
     //     pub fn hash_all(&self) -> H::Output {
     //         unsafe {
@@ -41,28 +95,34 @@ impl<const ARITY: usize, H: HashT> Prefixed<ARITY, H> {
     //     }
 }
 
-impl<const ARITY: usize, H: HashT> Clone for Prefixed<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Clone for Prefixed<ARITY, H, A> {
     fn clone(&self) -> Self {
         *self
     }
 }
-impl<const ARITY: usize, H: HashT> Copy for Prefixed<ARITY, H> {}
-impl<const ARITY: usize, H: HashT> Default for Prefixed<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Copy for Prefixed<ARITY, H, A> {}
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Default for Prefixed<ARITY, H, A> {
     fn default() -> Self {
         Self {
 //            prefix: H::Output::default(),
             prefix: crate::INNER_HASH_PREPEND_VALUE.into(),
             hashes: [Self::default_hash(); ARITY],
+            aggregates: [A::default(); ARITY],
         }
     }
 }
 
-impl<const ARITY: usize, H: HashT> Debug for Prefixed<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Debug for Prefixed<ARITY, H, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         writeln!(f, "prefix: {:?}", self.prefix)?;
         for (i, h) in self.hashes.iter().enumerate() {
             writeln!(f, "h[{i}]: {h:?}")?;
         }
+        if size_of::<A>() != 0 {
+            for (i, a) in self.aggregates.iter().enumerate() {
+                writeln!(f, "a[{i}]: {a:?}")?;
+            }
+        }
         Ok(())
     }
 }
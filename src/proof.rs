@@ -1,47 +1,58 @@
-use crate::traits::{HashT, ProofBuilder, ProofItemT, ProofValidator};
-use crate::Prefixed;
+use crate::mixed::MixedProof;
+use crate::traits::{HashT, NodeAggregate, ProofBuilder, ProofItemT, ProofValidator};
+use crate::utils::{div_arity, mod_arity, pow_arity};
+use crate::{Assert, Error, IsTrue, Prefixed};
 use core::fmt::Debug;
+use core::ops::Range;
 
 /// Basic implementation of an item making up a proof.
-/// Supports a power-of-2 number of siblings
-pub struct ProofItem<const ARITY: usize, H: HashT> {
-    prefixed: Prefixed<ARITY, H>,
+/// Supports any number of siblings from 2 up
+pub struct ProofItem<const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    prefixed: Prefixed<ARITY, H, A>,
     offset: usize,
 }
 
-impl<const ARITY: usize, H: HashT> ProofItem<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> ProofItem<ARITY, H, A> {
     /// returns item's hashes
     pub fn hashes(&self) -> &[H::Output; ARITY] {
         &self.prefixed.hashes
     }
 
+    /// returns item's aggregates
+    pub fn aggregates(&self) -> &[A; ARITY] {
+        &self.prefixed.aggregates
+    }
+
     /// returns item's offset
     pub fn offset(&self) -> usize {
         self.offset
     }
 }
 
-impl<const ARITY: usize, H: HashT> ProofItemT<ARITY, H> for ProofItem<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> ProofItemT<ARITY, H, A>
+    for ProofItem<ARITY, H, A>
+{
     /// constructor
-    fn create(offset: usize, prefixed: Prefixed<ARITY, H>) -> Self {
+    fn create(offset: usize, prefixed: Prefixed<ARITY, H, A>) -> Self {
         Self { offset, prefixed }
     }
-    /// hashes a provided hashed data at offset with its siblings
-    fn hash_with_siblings(mut self, word_hash: H::Output) -> Option<H::Output> {
+    /// hashes a provided hashed data and its aggregate at offset with its siblings
+    fn hash_with_siblings(mut self, word_hash: H::Output, aggregate: A) -> Option<(H::Output, A)> {
         self.prefixed.hashes[self.offset] = word_hash;
-        Some(self.prefixed.hash_all())
+        self.prefixed.aggregates[self.offset] = aggregate;
+        Some((self.prefixed.hash_all(), self.prefixed.aggregate()?))
     }
 }
 
-impl<const ARITY: usize, H: HashT> Copy for ProofItem<ARITY, H> {}
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Copy for ProofItem<ARITY, H, A> {}
 
-impl<const ARITY: usize, H: HashT> Clone for ProofItem<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Clone for ProofItem<ARITY, H, A> {
     fn clone(&self) -> Self {
         *self
     }
 }
 
-impl<const ARITY: usize, H: HashT> Default for ProofItem<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Default for ProofItem<ARITY, H, A> {
     fn default() -> Self {
         Self {
             prefixed: Default::default(),
@@ -50,70 +61,108 @@ impl<const ARITY: usize, H: HashT> Default for ProofItem<ARITY, H> {
     }
 }
 
-impl<const ARITY: usize, H: HashT> Debug for ProofItem<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> Debug for ProofItem<ARITY, H, A> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
-        writeln!(f, "{:?}", self.prefixed.hashes)
+        writeln!(f, "{:?}", self.prefixed.hashes)?;
+        if core::mem::size_of::<A>() != 0 {
+            writeln!(f, "{:?}", self.prefixed.aggregates)?;
+        }
+        Ok(())
     }
 }
 
 /// Proof implementation the StaticTree generates
-pub struct Proof<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
-where
+pub struct Proof<
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+    A: NodeAggregate = (),
+> where
     [(); HEIGHT]: Sized,
 {
     root: H::Output,
+    root_aggregate: A,
     height: usize,
-    items: [<Self as ProofBuilder<ARITY, H>>::Item; HEIGHT],
+    items: [<Self as ProofBuilder<ARITY, H, A>>::Item; HEIGHT],
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
-    ProofBuilder<ARITY, H> for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize, A: NodeAggregate>
+    ProofBuilder<ARITY, H, A> for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
 where
     [(); HEIGHT]: Sized,
 {
-    type Item = ProofItem<ARITY, H>;
+    type Item = ProofItem<ARITY, H, A>;
 
     fn from_root(root: H::Output) -> Self {
         Self {
             root,
+            root_aggregate: Default::default(),
             items: [ProofItem::default(); HEIGHT],
             height: 0,
         }
     }
 
-    fn push(&mut self, offset: usize, prefixed: Prefixed<ARITY, H>) {
+    fn set_root_aggregate(&mut self, aggregate: A) {
+        self.root_aggregate = aggregate;
+    }
+
+    fn push(&mut self, offset: usize, prefixed: Prefixed<ARITY, H, A>) {
         self.items[self.height] = Self::Item::create(offset, prefixed);
         self.height += 1;
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> ProofValidator
-    for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize, A: NodeAggregate>
+    ProofValidator for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
 where
     [(); HEIGHT]: Sized,
 {
-    /// verifies that the input was contained in the Merkle tree that generated this proof
+    /// verifies that the input was contained in the Merkle tree that generated this proof,
+    /// the input's aggregate is derived by [NodeAggregate::from_input]
     fn validate(self, input: &[u8]) -> bool {
-        let mut curr_hash = Some(Self::hash_as_leaf(input));
+        self.validate_with(input, A::from_input(input))
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize, A: NodeAggregate>
+    Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that the input with its aggregate was contained in the Merkle tree that generated this proof
+    /// and that the aggregates along the path sum up to the root aggregate
+    pub fn validate_with(self, input: &[u8], aggregate: impl Into<A>) -> bool {
+        self.validate_leaf(Self::hash_as_leaf(input), aggregate.into())
+    }
+
+    /// verifies that the value with the salt was contained in the Merkle tree that generated this proof
+    /// as a salted leaf, the value's aggregate is derived by [NodeAggregate::from_input]
+    pub fn validate_salted(self, salt: &[u8], value: &[u8]) -> bool {
+        1 + salt.len() + value.len() <= MAX_INPUT_LEN
+            && self.validate_leaf(Self::hash_as_salted_leaf(salt, value), A::from_input(value))
+    }
+
+    pub(crate) fn validate_leaf(self, leaf: H::Output, aggregate: A) -> bool {
+        let mut curr = Some((leaf, aggregate));
 
         // start from the base layer,
         // and for every item in the proof
-        // put the hash derived from input into the proof item
+        // put the hash and the aggregate derived from input into the proof item
         // at index stored in the proof item
         // and hash it with the siblings
         for item in &self.items[..self.height] {
-            curr_hash = curr_hash.and_then(|h| item.hash_with_siblings(h));
+            curr = curr.and_then(|(h, a)| item.hash_with_siblings(h, a));
         }
-        // validated iff the resulting hash is identical to the root
-        curr_hash.as_ref() == Some(&self.root)
+        // validated iff the resulting hash and aggregate are identical to the root ones
+        curr == Some((self.root, self.root_aggregate))
+    }
+
+    /// returns the aggregate of the proof's root
+    pub fn root_aggregate(&self) -> A {
+        self.root_aggregate
     }
-}
 
-impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
-    Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
-where
-    [(); HEIGHT]: Sized,
-{
     /// returns the proof's length
     pub fn height(&self) -> usize {
         self.height
@@ -124,9 +173,9 @@ where
         self.root
     }
 
-    /// returns the proof's path
-    pub fn path(&self) -> &[<Self as ProofBuilder<ARITY, H>>::Item] {
-        &self.items
+    /// returns the proof's path (as long as the proof's height)
+    pub fn path(&self) -> &[<Self as ProofBuilder<ARITY, H, A>>::Item] {
+        &self.items[..self.height]
     }
 
     /// returns the proof's arity
@@ -136,13 +185,12 @@ where
 
     /// prepends input with leaf prefix and hashes it
     pub fn hash_as_leaf(input: &[u8]) -> H::Output {
-        let start_index = if input.len() < MAX_INPUT_LEN { 1 } else { 0 };
-
-        let n = input.len() + start_index;
-        let mut prefixed = [crate::LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN];
-        prefixed[start_index..n].copy_from_slice(input);
-
-        H::hash(&prefixed[0..n])
+        crate::utils::hash_leaf::<H, MAX_INPUT_LEN>(input)
+    }
+    /// prepends the salt and the value with leaf prefix and hashes them,
+    /// panics unless salt and value are shorter than MAX_INPUT_LEN
+    pub fn hash_as_salted_leaf(salt: &[u8], value: &[u8]) -> H::Output {
+        crate::utils::hash_salted_leaf::<H, MAX_INPUT_LEN>(salt, value)
     }
     /// returns the index of claim as tree's leaf
     pub fn claim_index(&self) -> usize {
@@ -156,49 +204,55 @@ where
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Default
-    for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize, A: NodeAggregate>
+    Default for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
 where
     [(); HEIGHT]: Sized,
 {
     fn default() -> Self {
         Self {
             root: Default::default(),
+            root_aggregate: Default::default(),
             items: [Default::default(); HEIGHT],
             height: 0,
         }
     }
 }
 
-impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Debug
-    for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize, A: NodeAggregate>
+    Debug for Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
 where
     [(); HEIGHT]: Sized,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
         writeln!(f, "[proof height]:   {:?}", self.height)?;
         writeln!(f, "[proof root]:   {:?}", self.root)?;
+        if core::mem::size_of::<A>() != 0 {
+            writeln!(f, "[proof root aggregate]:   {:?}", self.root_aggregate)?;
+        }
         write!(f, "{:?}", self.items)
     }
 }
 /// Chains two proofs into one
-/// The second root becomes the root of the target proof
+/// The second root (and its aggregate) becomes the root of the target proof
 pub fn chain_proofs<
     const ARITY: usize,
     const HEIGHT1: usize,
     const HEIGHT2: usize,
     H: HashT,
     const MAX_INPUT_LEN: usize,
+    A: NodeAggregate,
 >(
-    proof1: Proof<ARITY, HEIGHT1, H, MAX_INPUT_LEN>,
-    proof2: Proof<ARITY, HEIGHT2, H, MAX_INPUT_LEN>,
-) -> Proof<ARITY, { HEIGHT1 + HEIGHT2 }, H, MAX_INPUT_LEN>
+    proof1: Proof<ARITY, HEIGHT1, H, MAX_INPUT_LEN, A>,
+    proof2: Proof<ARITY, HEIGHT2, H, MAX_INPUT_LEN, A>,
+) -> Proof<ARITY, { HEIGHT1 + HEIGHT2 }, H, MAX_INPUT_LEN, A>
 where
     [(); HEIGHT1]: Sized,
     [(); HEIGHT2]: Sized,
     [(); HEIGHT1 + HEIGHT2]: Sized,
 {
     let mut proof = Proof::from_root(proof2.root());
+    proof.set_root_aggregate(proof2.root_aggregate());
     proof.height = proof1.height + proof2.height;
     for i in 0..proof1.height {
         proof.items[i] = proof1.items[i];
@@ -208,3 +262,241 @@ where
     }
     proof
 }
+
+/// Composition of a sequence of proofs of possibly different heights and arities (up to MAX_ARITY)
+/// into a single [MixedProof] of up to HEIGHT items, e.g. of nested commitments: a leaf in a subtree,
+/// the subtree's root in a tree, the tree's root in a summit tree.
+/// Every proof chained proves the root of the chain (and its aggregate) as its leaf,
+/// the root (and its aggregate) of the last proof chained becomes the root of the target proof
+pub struct ProofChain<
+    const MAX_ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+    A: NodeAggregate = (),
+> where
+    [(); HEIGHT]: Sized,
+{
+    proof: MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>,
+}
+
+impl<
+        const MAX_ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        A: NodeAggregate,
+    > ProofChain<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// starts a chain with the proof of a leaf in a tree of any arity up to MAX_ARITY,
+    /// fails if the proof is longer than HEIGHT
+    pub fn new<const ARITY: usize, const LEN: usize>(
+        proof: Proof<ARITY, LEN, H, MAX_INPUT_LEN, A>,
+    ) -> Result<Self, Error>
+    where
+        [(); LEN]: Sized,
+        Assert<{ ARITY <= MAX_ARITY }>: IsTrue,
+    {
+        Self {
+            proof: MixedProof::default(),
+        }
+        .append(proof)
+    }
+
+    /// chains the proof of the root of the chain as a leaf of an upper tree of any arity up to MAX_ARITY,
+    /// fails unless the leaf (and its aggregate) the proof was generated for is the root of the chain
+    /// or if the chain grows longer than HEIGHT
+    pub fn then<const ARITY: usize, const LEN: usize>(
+        self,
+        proof: Proof<ARITY, LEN, H, MAX_INPUT_LEN, A>,
+    ) -> Result<Self, Error>
+    where
+        [(); LEN]: Sized,
+        Assert<{ ARITY <= MAX_ARITY }>: IsTrue,
+    {
+        let leaf = proof.path().first().map(|item| {
+            (
+                item.hashes()[item.offset()],
+                item.aggregates()[item.offset()],
+            )
+        });
+        (leaf == Some((self.proof.root, self.proof.root_aggregate)))
+            .then_some(self)
+            .ok_or(Error::Chain)
+            .and_then(|this| this.append(proof))
+    }
+
+    fn append<const ARITY: usize, const LEN: usize>(
+        mut self,
+        proof: Proof<ARITY, LEN, H, MAX_INPUT_LEN, A>,
+    ) -> Result<Self, Error>
+    where
+        [(); LEN]: Sized,
+        Assert<{ ARITY <= MAX_ARITY }>: IsTrue,
+    {
+        if self.proof.height + proof.height > HEIGHT {
+            return Err(Error::Chain);
+        }
+        for item in proof.path() {
+            ProofBuilder::<ARITY, H, A>::push(&mut self.proof, item.offset, item.prefixed);
+        }
+        self.proof.root = proof.root;
+        self.proof.root_aggregate = proof.root_aggregate;
+        Ok(self)
+    }
+
+    /// returns the length of the chain
+    pub fn height(&self) -> usize {
+        self.proof.height
+    }
+
+    /// returns the root of the chain
+    pub fn root(&self) -> H::Output {
+        self.proof.root
+    }
+
+    /// returns the aggregate of the root of the chain
+    pub fn root_aggregate(&self) -> A {
+        self.proof.root_aggregate
+    }
+
+    /// returns the proof of the leaf of the first proof chained against the root of the last one
+    pub fn finish(self) -> MixedProof<MAX_ARITY, HEIGHT, H, MAX_INPUT_LEN, A> {
+        self.proof
+    }
+}
+
+/// Proof of a contiguous span of leaves.
+/// Consists of the proofs of the left-most and the right-most leaves of the span,
+/// i.e. the boundary co-paths (at most 2*HEIGHT items),
+/// the inner nodes are recomputed from the span of inputs on validation
+pub struct RangeProof<PB> {
+    range: Range<usize>,
+    left: PB,
+    right: PB,
+}
+
+impl<PB> RangeProof<PB> {
+    /// creates a range proof from the proofs of the boundary leaves of the range
+    pub fn from_boundaries(range: Range<usize>, left: PB, right: PB) -> Self {
+        Self { range, left, right }
+    }
+
+    /// returns the span of leaf indices the proof was generated for
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// returns the proof of the left-most leaf of the span
+    pub fn left(&self) -> &PB {
+        &self.left
+    }
+
+    /// returns the proof of the right-most leaf of the span
+    pub fn right(&self) -> &PB {
+        &self.right
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    RangeProof<Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that the inputs were contained in the Merkle tree that generated this proof
+    /// as the contiguous span of leaves at the proof's range
+    pub fn validate<T: AsRef<[u8]>>(self, inputs: &[T]) -> bool {
+        self.validate_iter(inputs.iter().map(|d| d.as_ref()))
+    }
+
+    pub(crate) fn validate_iter<'a>(self, inputs: impl Iterator<Item = &'a [u8]>) -> bool {
+        let height = self.left.height;
+        if self.range.start >= self.range.end
+            || height == 0
+            || height != self.right.height
+            || self.left.root != self.right.root
+            || self.range.end > pow_arity::<ARITY>(height)
+        {
+            return false;
+        }
+        // the end of the span on every layer
+        let mut ends = [0usize; HEIGHT];
+        let mut end = self.range.end;
+        for e in ends.iter_mut().take(height) {
+            *e = end;
+            end = div_arity::<ARITY>(end - 1) + 1;
+        }
+        // the node under construction on every layer,
+        // the siblings on the left of the span are taken from the left co-path
+        let mut nodes = self.left.items.map(|item| item.prefixed);
+        let mut root = None;
+        let mut len = 0;
+
+        for input in inputs {
+            let mut pos = self.range.start + len;
+            if pos >= self.range.end {
+                return false;
+            }
+            len += 1;
+
+            let mut hash = Proof::<ARITY, HEIGHT, H, MAX_INPUT_LEN>::hash_as_leaf(input);
+            for (layer, node) in nodes.iter_mut().enumerate().take(height) {
+                let offset = mod_arity::<ARITY>(pos);
+                node.hashes[offset] = hash;
+
+                if pos == ends[layer] - 1 {
+                    // the siblings on the right of the span are taken from the right co-path
+                    node.hashes[offset + 1..]
+                        .copy_from_slice(&self.right.items[layer].hashes()[offset + 1..]);
+                } else if offset != ARITY - 1 {
+                    // the node is not complete yet
+                    break;
+                }
+                hash = node.hash_all();
+                pos = div_arity::<ARITY>(pos);
+
+                if layer == height - 1 {
+                    root = Some(hash);
+                }
+            }
+        }
+        // validated iff the whole span was provided and the resulting hash is identical to the root
+        len == self.range.len() && root == Some(self.left.root)
+    }
+
+    /// checks that no leaf follows the span, i.e. the next leaf position
+    /// (if any) is padding on every layer.
+    /// Meaningful only for a validated proof of a tree with no gaps between leaves
+    pub fn ends_with_last_leaf(&self) -> bool {
+        let mut pos = self.range.end - 1;
+
+        for item in self.right.path() {
+            let offset = mod_arity::<ARITY>(pos);
+            if offset != ARITY - 1 {
+                return item.hashes()[offset + 1] == Prefixed::<ARITY, H>::default_hash();
+            }
+            pos = div_arity::<ARITY>(pos);
+        }
+        true
+    }
+
+    /// returns the proof's length
+    pub fn height(&self) -> usize {
+        self.left.height
+    }
+
+    /// returns the proof's root
+    pub fn root(&self) -> H::Output {
+        self.left.root
+    }
+}
+
+impl<PB: Debug> Debug for RangeProof<PB> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[proof range]:   {:?}", self.range)?;
+        writeln!(f, "{:?}", self.left)?;
+        write!(f, "{:?}", self.right)
+    }
+}
@@ -0,0 +1,397 @@
+//! Resizable tree has a maximum capacity determined at compile time by ```MAX_HEIGHT```,
+//! while its current height is determined at runtime.
+//! Growing and shrinking are performed in place, so the type of the tree does not change.
+//! Proofs are generated for the current height.
+//! ```rust
+//! use merkle_heapless::resizable::{ResizableTree};
+//!
+//! const ARITY: usize = 2;
+//! const MAX_HEIGHT: usize = 5;
+//! const MAX_WORD_LEN: usize = 10;
+//!
+//! let mut rt = ResizableTree::<ARITY, MAX_HEIGHT, StdHash, MAX_WORD_LEN>::try_from::<&[u8]>(&[
+//!     b"apple", b"apricot", b"banana",
+//! ]).unwrap();
+//! // the smallest height that fits the input
+//! assert_eq!(rt.height(), 2);
+//!
+//! rt.try_append(b"cherry").unwrap();
+//! assert!(rt.try_append(b"kiwi").is_err());
+//! // now there is a room for four more leaves
+//! rt.try_grow().unwrap();
+//! rt.try_append(b"kiwi").unwrap();
+//! assert_eq!(rt.generate_proof(4).height(), 3);
+//!
+//! rt.remove(0);
+//! rt.remove(1);
+//! // compacts the leaves and reduces the height
+//! rt.try_shrink().unwrap();
+//! assert_eq!(rt.height(), 2);
+//! ```
+//!
+
+use crate::traits::{AppendOnly, CanRemove, TreeNodes};
+use crate::utils;
+use crate::{
+    is_arity, layer_size, location_in_prefixed, max_leaves, num_of_prefixed, Assert, Error, HashT,
+    IsTrue, Prefixed, Proof, ProofBuilder, StaticTreeTrait,
+};
+use core::fmt::Debug;
+use core::mem::size_of;
+use core::ops::Deref;
+
+/// Tree with a fixed capacity and a height changeable at runtime
+pub struct ResizableTree<
+    const ARITY: usize,
+    const MAX_HEIGHT: usize,
+    H,
+    const MAX_INPUT_LEN: usize,
+    PB = Proof<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN>,
+> where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    root: H::Output,
+    height: usize,
+    // the tree of the current height occupies the beginning of the buffer
+    prefixed: [Prefixed<ARITY, H>; num_of_prefixed!(ARITY, MAX_HEIGHT)],
+    num_of_leaves: usize,
+    // index next to the right-most present leaf
+    end: usize,
+    leaves_present: [bool; max_leaves!(ARITY, MAX_HEIGHT)],
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    // the smallest height which can hold that many leaves
+    fn height_for(num_of_leaves: usize) -> Result<usize, Error> {
+        let mut height = 1;
+        while max_leaves!(ARITY, height) < num_of_leaves {
+            height += 1;
+        }
+        (height <= MAX_HEIGHT).then_some(height).ok_or(Error::Create)
+    }
+
+    fn with_height(height: usize) -> Self {
+        Self {
+            root: Default::default(),
+            height,
+            prefixed: [Default::default(); num_of_prefixed!(ARITY, MAX_HEIGHT)],
+            num_of_leaves: 0,
+            end: 0,
+            leaves_present: [false; max_leaves!(ARITY, MAX_HEIGHT)],
+        }
+    }
+
+    /// creates a tree of the smallest height that fits the input if possible
+    pub fn try_from<T: AsRef<[u8]> + Deref<Target = [u8]>>(input: &[T]) -> Result<Self, Error> {
+        let mut this = Self::with_height(Self::height_for(input.len())?);
+
+        for (i, d) in input.iter().enumerate() {
+            let (index, offset) = location_in_prefixed::<ARITY>(i);
+            this.prefixed[index].hashes[offset] = utils::hash_leaf::<H, MAX_INPUT_LEN>(d);
+            this.leaves_present[i] = true;
+        }
+        this.num_of_leaves = input.len();
+        this.end = input.len();
+        this.rebuild();
+        Ok(this)
+    }
+
+    /// creates a tree of the smallest height that fits hashed leaves (of another tree) if possible
+    pub fn try_from_leaves(prefixed: &[Prefixed<ARITY, H>]) -> Result<Self, Error> {
+        let mut this = Self::with_height(Self::height_for(prefixed.len() * ARITY)?);
+        let default_hash = Prefixed::<ARITY, H>::default_hash();
+
+        for (i, h) in prefixed.iter().flat_map(|leaf| leaf.hashes).enumerate() {
+            if h != default_hash {
+                let (index, offset) = location_in_prefixed::<ARITY>(i);
+                this.prefixed[index].hashes[offset] = h;
+                this.leaves_present[i] = true;
+                this.num_of_leaves += 1;
+                this.end = i + 1;
+            }
+        }
+        this.rebuild();
+        Ok(this)
+    }
+
+    // pads the base layer of the current height and recalculates the rest of the layers
+    fn rebuild(&mut self) {
+        utils::pad_leaves(&mut self.prefixed[..], self.height, self.end)
+            .expect("in-memory nodes are writable. qed");
+        (self.root, ()) = utils::fill_layers(&mut self.prefixed, self.height, self.end)
+            .expect("no aggregates to combine. qed");
+    }
+
+    /// number of leaves currently stored in the tree
+    pub fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+
+    /// maximum number of leaves the tree can hold at its current height
+    pub fn capacity(&self) -> usize {
+        max_leaves!(ARITY, self.height)
+    }
+
+    /// increases the height by one in place unless it is already the maximum height.
+    /// The leaves keep their indices.
+    pub fn try_grow(&mut self) -> Result<(), Error> {
+        if self.height >= MAX_HEIGHT {
+            return Err(Error::Resize);
+        }
+        // the base layer stays at the beginning of the buffer,
+        // the previous upper layers are overwritten by padding
+        self.height += 1;
+        self.rebuild();
+        Ok(())
+    }
+
+    /// compacts the leaves and decreases the height by one in place if the leaves fit.
+    /// The leaves keep their relative order, but not necessarily their indices.
+    pub fn try_shrink(&mut self) -> Result<(), Error> {
+        if self.height == 1 || self.num_of_leaves > max_leaves!(ARITY, self.height - 1) {
+            return Err(Error::Resize);
+        }
+        self.compact_leaves();
+        self.height -= 1;
+        self.rebuild();
+        Ok(())
+    }
+
+    /// move all existing leaves leftwards
+    pub fn compact(&mut self) {
+        self.compact_leaves();
+        self.rebuild();
+    }
+
+    fn compact_leaves(&mut self) {
+        let mut j = 0;
+        for i in 0..self.end {
+            if self.leaves_present[i] {
+                let (old_index, old_offset) = location_in_prefixed::<ARITY>(i);
+                let (new_index, new_offset) = location_in_prefixed::<ARITY>(j);
+
+                self.prefixed[new_index].hashes[new_offset] =
+                    self.prefixed[old_index].hashes[old_offset];
+                self.leaves_present[j] = true;
+                j += 1;
+            }
+        }
+        for present in self.leaves_present[j..self.end].iter_mut() {
+            *present = false;
+        }
+        assert_eq!(self.num_of_leaves, j);
+        self.end = j;
+    }
+
+    fn set_leaf(&mut self, index: usize, leaf: H::Output) {
+        assert!(index < self.capacity(), "index is out of leaf layer bound");
+
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        self.prefixed[prefixed_index].hashes[offset] = leaf;
+        (self.root, ()) = utils::replace_inner(&mut self.prefixed, self.height, index)
+            .expect("no aggregates to combine. qed");
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    StaticTreeTrait<ARITY, H, PB> for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// generate proof at given index on base layer, its length is the current height
+    fn generate_proof(&self, index: usize) -> PB {
+        utils::generate_proof(&self.prefixed, self.height, self.root, index)
+            .expect("in-memory nodes are readable. qed")
+    }
+    /// replace an element at index with input
+    /// panics if index is out of leaf layer bound
+    fn replace(&mut self, index: usize, input: &[u8]) {
+        self.replace_leaf(index, utils::hash_leaf::<H, MAX_INPUT_LEN>(input));
+    }
+    fn replace_leaf(&mut self, index: usize, leaf: H::Output) {
+        self.set_leaf(index, leaf);
+
+        if !self.leaves_present[index] {
+            self.num_of_leaves += 1;
+        }
+        self.leaves_present[index] = true;
+        self.end = core::cmp::max(self.end, index + 1);
+    }
+    fn root(&self) -> H::Output {
+        self.root
+    }
+    fn leaves(&self) -> &[Prefixed<ARITY, H>] {
+        &self.prefixed[..layer_size!(ARITY, self.height, 0)]
+    }
+    fn base_layer_size(&self) -> usize {
+        layer_size!(ARITY, self.height, 0)
+    }
+    fn height(&self) -> usize {
+        self.height
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    TreeNodes<ARITY, H> for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        utils::hash_leaf::<H, MAX_INPUT_LEN>(input)
+    }
+    fn nodes(&self) -> &[Prefixed<ARITY, H>] {
+        &self.prefixed[..num_of_prefixed!(ARITY, self.height)]
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> AppendOnly
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// appends next to the right-most leaf, does not grow the tree if it is full
+    fn try_append(&mut self, input: &[u8]) -> Result<(), Error> {
+        if self.end >= self.capacity() {
+            return Err(Error::Append);
+        }
+        self.replace(self.end, input);
+        Ok(())
+    }
+    fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> CanRemove
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    // remove element by replacing it with padding
+    // panics if index is out of leaf layer bound
+    fn remove(&mut self, index: usize) {
+        self.set_leaf(index, Prefixed::<ARITY, H>::default_hash());
+
+        if self.leaves_present[index] {
+            self.num_of_leaves -= 1;
+        }
+        self.leaves_present[index] = false;
+        while self.end > 0 && !self.leaves_present[self.end - 1] {
+            self.end -= 1;
+        }
+    }
+
+    fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Clone
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Copy
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Default
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// empty tree of height 1
+    fn default() -> Self {
+        let mut this = Self::with_height(1);
+        this.rebuild();
+        this
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> PartialEq
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.root() == other.root() && self.height() == other.height()
+    }
+}
+
+impl<const ARITY: usize, const MAX_HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB> Debug
+    for ResizableTree<ARITY, MAX_HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, MAX_HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, MAX_HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[arity]:   {ARITY}")?;
+        writeln!(f, "[height]:          {}", self.height)?;
+        writeln!(f, "[max height]:      {MAX_HEIGHT}")?;
+        writeln!(f, "[num of prefixed]: {}", num_of_prefixed!(ARITY, self.height))?;
+        writeln!(
+            f,
+            "[total bytes]:     {}",
+            size_of::<H::Output>()
+                + size_of::<Prefixed<ARITY, H>>() * num_of_prefixed!(ARITY, MAX_HEIGHT)
+        )?;
+        writeln!(f, "[hash output len]: {} bytes", size_of::<H::Output>())?;
+        writeln!(f, "[num of leaves]: {:?}", self.num_of_leaves)?;
+        write!(f, "{:?}", &self.prefixed[..num_of_prefixed!(ARITY, self.height)])
+    }
+}
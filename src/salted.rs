@@ -0,0 +1,206 @@
+//! Salted leaves are hashed as ```LEAF_HASH_PREPEND_VALUE || salt || value```, so a value of low entropy
+//! cannot be guessed from its leaf hash nor from the proof items disclosed along with another leaf.
+//! A [crate::salted::Disclosure] bundles a proof with the salt and the value of a single leaf,
+//! revealing that leaf only, the other leaves stay hidden behind their salted hashes.
+//!
+//! The salt is of a fixed length SALT_LEN and the prefix is never omitted,
+//! so the salt and the value together should be shorter than MAX_INPUT_LEN.
+//! ```rust
+//! use merkle_heapless::StaticTree;
+//!
+//! const SALT_LEN: usize = 16;
+//! // the salts should be random and kept secret by the holder of the tree
+//! let tree = StaticTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from_salted::<SALT_LEN, &[u8]>(&[
+//!     (salt0, b"name: Alice"), (salt1, b"born: 1990"), (salt2, b"country: NZ"),
+//! ]).unwrap();
+//! let root = tree.root();
+//!
+//! // reveals the country only
+//! let disclosure = tree.disclose(2, salt2, b"country: NZ").unwrap();
+//! // on the verifier's side
+//! assert!(disclosure.verify(&root));
+//! ```
+
+use crate::proof::Proof;
+use crate::traits::{NodeAggregate, ProofBuilder, StaticTreeTrait};
+use crate::utils::{self, location_in_prefixed};
+use crate::{is_arity, layer_size, num_of_prefixed, Assert, Error, HashT, IsTrue, StaticTree};
+use core::fmt::Debug;
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB, A>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+{
+    /// creates a tree from values paired with their salts if possible,
+    /// leaf aggregates are derived from the values by [NodeAggregate::from_input].
+    /// Fails unless every salt and value together are shorter than MAX_INPUT_LEN
+    pub fn try_from_salted<const SALT_LEN: usize, T: AsRef<[u8]>>(
+        input: &[([u8; SALT_LEN], T)],
+    ) -> Result<Self, Error> {
+        if input.len() > layer_size!(ARITY, HEIGHT, 0) * ARITY
+            || input
+                .iter()
+                .any(|(_, value)| SALT_LEN + value.as_ref().len() >= MAX_INPUT_LEN)
+        {
+            return Err(Error::Create);
+        }
+        let mut this = Self::default();
+        for (i, (salt, value)) in input.iter().enumerate() {
+            let (index, offset) = location_in_prefixed::<ARITY>(i);
+            this.prefixed[index].hashes[offset] =
+                utils::hash_salted_leaf::<H, MAX_INPUT_LEN>(salt, value.as_ref());
+            this.prefixed[index].aggregates[offset] = A::from_input(value.as_ref());
+        }
+        this.pad_leaves(input.len());
+        this.fill_layers(input.len())?;
+        Ok(this)
+    }
+
+    /// replaces an element at index with a value and its salt,
+    /// the value's aggregate is derived by [NodeAggregate::from_input]
+    /// panics if index is out of leaf layer bound, salt and value are not shorter than MAX_INPUT_LEN
+    /// or aggregates cannot be combined
+    pub fn replace_salted(&mut self, index: usize, salt: &[u8], value: &[u8]) {
+        self.try_replace_inner(
+            index,
+            utils::hash_salted_leaf::<H, MAX_INPUT_LEN>(salt, value),
+            A::from_input(value),
+        )
+        .expect("aggregates are combinable");
+    }
+
+    /// returns the hash of a salted leaf as it is stored in the tree
+    pub fn salted_leaf_hash(&self, salt: &[u8], value: &[u8]) -> H::Output {
+        utils::hash_salted_leaf::<H, MAX_INPUT_LEN>(salt, value)
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, A>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>, A>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); HEIGHT]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    A: NodeAggregate,
+{
+    /// discloses a salted leaf at index with its salt and value,
+    /// fails if they do not match the leaf or are not shorter than MAX_INPUT_LEN
+    pub fn disclose<const SALT_LEN: usize>(
+        &self,
+        index: usize,
+        salt: [u8; SALT_LEN],
+        value: &[u8],
+    ) -> Result<Disclosure<ARITY, HEIGHT, H, MAX_INPUT_LEN, SALT_LEN, A>, Error> {
+        if index >= layer_size!(ARITY, HEIGHT, 0) * ARITY || SALT_LEN + value.len() >= MAX_INPUT_LEN
+        {
+            return Err(Error::Create);
+        }
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        if self.leaves()[prefixed_index].hashes[offset] != self.salted_leaf_hash(&salt, value) {
+            return Err(Error::Create);
+        }
+        Disclosure::new(self.generate_proof(index), salt, value)
+    }
+}
+
+/// Proof of a single salted leaf bundled with its salt and value
+pub struct Disclosure<
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+    const SALT_LEN: usize,
+    A: NodeAggregate = (),
+> where
+    [(); HEIGHT]: Sized,
+{
+    proof: Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>,
+    salt: [u8; SALT_LEN],
+    value: [u8; MAX_INPUT_LEN],
+    value_len: usize,
+}
+
+impl<
+        const ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        const SALT_LEN: usize,
+        A: NodeAggregate,
+    > Disclosure<ARITY, HEIGHT, H, MAX_INPUT_LEN, SALT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// bundles a proof with the salt and the value of the leaf it was generated for,
+    /// fails unless salt and value are shorter than MAX_INPUT_LEN
+    pub fn new(
+        proof: Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A>,
+        salt: [u8; SALT_LEN],
+        value: &[u8],
+    ) -> Result<Self, Error> {
+        if SALT_LEN + value.len() >= MAX_INPUT_LEN {
+            return Err(Error::Create);
+        }
+        let mut bytes = [0u8; MAX_INPUT_LEN];
+        bytes[..value.len()].copy_from_slice(value);
+        Ok(Self {
+            proof,
+            salt,
+            value: bytes,
+            value_len: value.len(),
+        })
+    }
+
+    /// verifies that the disclosed value with its salt was contained in the tree with the root
+    pub fn verify(self, root: &H::Output) -> bool {
+        &self.proof.root() == root
+            && self
+                .proof
+                .validate_salted(&self.salt, &self.value[..self.value_len])
+    }
+
+    /// returns the disclosed value
+    pub fn value(&self) -> &[u8] {
+        &self.value[..self.value_len]
+    }
+
+    /// returns the salt of the disclosed value
+    pub fn salt(&self) -> &[u8; SALT_LEN] {
+        &self.salt
+    }
+
+    /// returns the index of the disclosed leaf
+    pub fn index(&self) -> usize {
+        self.proof.claim_index()
+    }
+
+    /// returns the proof of the disclosed leaf
+    pub fn proof(&self) -> &Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, A> {
+        &self.proof
+    }
+}
+
+impl<
+        const ARITY: usize,
+        const HEIGHT: usize,
+        H: HashT,
+        const MAX_INPUT_LEN: usize,
+        const SALT_LEN: usize,
+        A: NodeAggregate,
+    > Debug for Disclosure<ARITY, HEIGHT, H, MAX_INPUT_LEN, SALT_LEN, A>
+where
+    [(); HEIGHT]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[index]:   {}", self.index())?;
+        writeln!(f, "[salt]:   {:?}", self.salt)?;
+        writeln!(f, "[value]:   {:?}", self.value())?;
+        write!(f, "{:?}", self.proof)
+    }
+}
@@ -0,0 +1,413 @@
+//! Sorted Merkle Tree keeps its leaves in the ascending order of keys (compared as byte strings)
+//! with no gaps between them, so besides inclusion it can prove that keys are absent.
+//! A [crate::sorted::SortedRangeProof] proves all the keys in an inclusive range [from, to] with a single combined proof:
+//! the span of matching leaves is extended with the adjacent leaves bracketing the range (if any).
+//! An exclusion proof is a range proof with no matching keys, i.e. two consecutive leaves whose keys bracket the missing key.
+//! Like any proof it is validated against the root it carries, which the verifier compares with a trusted root.
+//!
+//! Numeric keys should be encoded big endian to keep their natural order.
+//! ```rust
+//! use merkle_heapless::sorted::{SortedTree};
+//!
+//! const ARITY: usize = 2;
+//! const HEIGHT: usize = 4;
+//! const MAX_WORD_LEN: usize = 10;
+//!
+//! let mut tree = SortedTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from::<&[u8]>(&[
+//!     b"kiwi", b"apple", b"cherry",
+//! ]).unwrap();
+//! tree.insert(b"banana").unwrap();
+//!
+//! let proof = tree.generate_exclusion_proof(b"blueberry").unwrap();
+//! // the root is trusted
+//! assert_eq!(proof.root(), tree.root());
+//! assert!(proof.validate_exclusion(b"blueberry"));
+//!
+//! let proof = tree.generate_range_proof(b"b", b"d");
+//! assert!(proof.validate(b"b", b"d", &[b"banana", b"cherry"]));
+//! ```
+
+use crate::proof::RangeProof;
+use crate::utils::{self, location_in_prefixed};
+use crate::{
+    is_arity, layer_size, max_leaves, num_of_prefixed, Assert, Error, HashT, IsTrue, Prefixed, Proof,
+    StaticTree, StaticTreeTrait,
+};
+use core::cmp::Ordering;
+use core::fmt::Debug;
+use core::ops::Deref;
+
+/// key of a sorted tree stored inline, ordered as a byte string
+#[derive(Clone, Copy)]
+pub struct Key<const MAX_INPUT_LEN: usize> {
+    bytes: [u8; MAX_INPUT_LEN],
+    len: usize,
+}
+
+impl<const MAX_INPUT_LEN: usize> Key<MAX_INPUT_LEN> {
+    /// creates a key from input
+    /// panics if input is longer than MAX_INPUT_LEN
+    pub fn new(input: &[u8]) -> Self {
+        let mut bytes = [0u8; MAX_INPUT_LEN];
+        bytes[..input.len()].copy_from_slice(input);
+        Self {
+            bytes,
+            len: input.len(),
+        }
+    }
+    /// returns the key as a byte slice
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+impl<const MAX_INPUT_LEN: usize> Default for Key<MAX_INPUT_LEN> {
+    fn default() -> Self {
+        Self {
+            bytes: [0u8; MAX_INPUT_LEN],
+            len: 0,
+        }
+    }
+}
+
+impl<const MAX_INPUT_LEN: usize> PartialEq for Key<MAX_INPUT_LEN> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_slice() == other.as_slice()
+    }
+}
+impl<const MAX_INPUT_LEN: usize> Eq for Key<MAX_INPUT_LEN> {}
+
+impl<const MAX_INPUT_LEN: usize> PartialOrd for Key<MAX_INPUT_LEN> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<const MAX_INPUT_LEN: usize> Ord for Key<MAX_INPUT_LEN> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_slice().cmp(other.as_slice())
+    }
+}
+
+impl<const MAX_INPUT_LEN: usize> Debug for Key<MAX_INPUT_LEN> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        write!(f, "{:?}", self.as_slice())
+    }
+}
+
+/// Merkle Tree keeping its leaves sorted by key
+pub struct SortedTree<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    tree: StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+    keys: [Key<MAX_INPUT_LEN>; max_leaves!(ARITY, HEIGHT)],
+    num_of_leaves: usize,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize>
+    SortedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    /// creates a tree from unsorted keys if possible,
+    /// fails on duplicate keys
+    pub fn try_from<T: AsRef<[u8]> + Deref<Target = [u8]>>(input: &[T]) -> Result<Self, Error> {
+        if input.len() > max_leaves!(ARITY, HEIGHT) {
+            return Err(Error::Create);
+        }
+        let mut this = Self::default();
+        for d in input {
+            let index = this.search(d).err().ok_or(Error::Duplicate)?;
+            this.insert_key(index, d);
+        }
+        this.rebuild(0);
+        Ok(this)
+    }
+
+    /// index of the key if present,
+    /// otherwise the index where it would be inserted to keep the order
+    pub fn search(&self, key: &[u8]) -> Result<usize, usize> {
+        self.keys().binary_search_by(|k| k.as_slice().cmp(key))
+    }
+
+    /// checks if the key is present
+    pub fn contains(&self, key: &[u8]) -> bool {
+        self.search(key).is_ok()
+    }
+
+    /// inserts a key keeping the order,
+    /// fails if the tree is full or the key is already present
+    pub fn insert(&mut self, key: &[u8]) -> Result<(), Error> {
+        let index = self.search(key).err().ok_or(Error::Duplicate)?;
+        if self.num_of_leaves == max_leaves!(ARITY, HEIGHT) {
+            return Err(Error::Append);
+        }
+        self.insert_key(index, key);
+        self.rebuild(index);
+        Ok(())
+    }
+
+    /// removes a key keeping the order, returns false if the key is absent
+    pub fn remove(&mut self, key: &[u8]) -> bool {
+        match self.search(key) {
+            Ok(index) => {
+                self.keys.copy_within(index + 1..self.num_of_leaves, index);
+                self.num_of_leaves -= 1;
+                self.rebuild(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn insert_key(&mut self, index: usize, key: &[u8]) {
+        self.keys
+            .copy_within(index..self.num_of_leaves, index + 1);
+        self.keys[index] = Key::new(key);
+        self.num_of_leaves += 1;
+    }
+
+    // rehashes the leaves shifted from index and recalculates the layers
+    fn rebuild(&mut self, from_index: usize) {
+        for i in from_index..self.num_of_leaves {
+            let (index, offset) = location_in_prefixed::<ARITY>(i);
+            self.tree.prefixed[index].hashes[offset] =
+                utils::hash_leaf::<H, MAX_INPUT_LEN>(self.keys[i].as_slice());
+        }
+        self.tree.pad_leaves(self.num_of_leaves);
+        self.tree
+            .fill_layers(self.num_of_leaves)
+            .expect("no aggregates to combine. qed");
+    }
+
+    /// returns the keys in ascending order
+    pub fn keys(&self) -> &[Key<MAX_INPUT_LEN>] {
+        &self.keys[..self.num_of_leaves]
+    }
+
+    /// number of leaves currently stored in the tree
+    pub fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+
+    /// returns the root
+    pub fn root(&self) -> H::Output {
+        self.tree.root()
+    }
+
+    /// returns tree's height (determined at compile time)
+    pub fn height(&self) -> usize {
+        HEIGHT
+    }
+
+    /// returns the underlying tree, e.g. to iterate over its leaves and nodes
+    pub fn tree(&self) -> &StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN> {
+        &self.tree
+    }
+
+    /// generates an inclusion proof for a leaf at index
+    pub fn generate_proof(&self, index: usize) -> Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN> {
+        self.tree.generate_proof(index)
+    }
+
+    /// generates a proof of all the keys in the inclusive range [from, to]
+    pub fn generate_range_proof(
+        &self,
+        from: &[u8],
+        to: &[u8],
+    ) -> SortedRangeProof<ARITY, HEIGHT, H, MAX_INPUT_LEN> {
+        let keys = self.keys();
+        // span of the keys within the range
+        let start = keys.partition_point(|k| k.as_slice() < from);
+        let end = keys.partition_point(|k| k.as_slice() <= to).max(start);
+        // extended with the adjacent keys bracketing the range
+        let left = (start > 0).then(|| keys[start - 1]);
+        let right = (end < keys.len()).then(|| keys[end]);
+        let span = start - left.is_some() as usize..end + right.is_some() as usize;
+
+        SortedRangeProof {
+            root: self.root(),
+            left,
+            right,
+            proof: (!span.is_empty()).then(|| self.tree.generate_range_proof(span)),
+        }
+    }
+
+    /// generates a proof of absence of the key, None if the key is present
+    pub fn generate_exclusion_proof(
+        &self,
+        key: &[u8],
+    ) -> Option<SortedRangeProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>> {
+        (!self.contains(key)).then(|| self.generate_range_proof(key, key))
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Clone
+    for SortedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Copy
+    for SortedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Default
+    for SortedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn default() -> Self {
+        let mut this = Self {
+            tree: StaticTree::default(),
+            keys: [Key::default(); max_leaves!(ARITY, HEIGHT)],
+            num_of_leaves: 0,
+        };
+        this.rebuild(0);
+        this
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> PartialEq
+    for SortedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.tree == other.tree
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Debug
+    for SortedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    [(); max_leaves!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[num of leaves]:   {}", self.num_of_leaves)?;
+        writeln!(f, "[keys]:   {:?}", self.keys())?;
+        write!(f, "{:?}", self.tree)
+    }
+}
+
+/// Proof of all the keys of a [SortedTree] in an inclusive range,
+/// the span of the keys is extended with the adjacent keys bracketing the range.
+/// A proof is checked against the root it carries, so a verifier compares [SortedRangeProof::root]
+/// with a root it trusts, otherwise a proof made up for any other tree validates
+pub struct SortedRangeProof<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+where
+    [(); HEIGHT]: Sized,
+{
+    root: H::Output,
+    left: Option<Key<MAX_INPUT_LEN>>,
+    right: Option<Key<MAX_INPUT_LEN>>,
+    // None for an empty tree
+    proof: Option<RangeProof<Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>>>,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    SortedRangeProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that keys (in ascending order) are all the keys in the inclusive range [from, to]
+    /// of the sorted tree that generated this proof,
+    /// the tree is the trusted one only if the proof's root is checked to be its root
+    pub fn validate<T: AsRef<[u8]>>(self, from: &[u8], to: &[u8], keys: &[T]) -> bool {
+        // keys are strictly ascending and within the range
+        let in_range = keys.iter().all(|k| (from..=to).contains(&k.as_ref()))
+            && keys.windows(2).all(|w| w[0].as_ref() < w[1].as_ref());
+        // the adjacent keys bracket the range
+        let bracketed = self.left.map_or(true, |k| k.as_slice() < from)
+            && self.right.map_or(true, |k| k.as_slice() > to);
+        if !in_range || !bracketed {
+            return false;
+        }
+
+        match self.proof {
+            Some(proof) => {
+                // no left adjacent key iff the span starts at the first leaf,
+                // no right adjacent key iff the span ends at the last leaf
+                let starts_first = proof.range().start == 0;
+                let ends_last = proof.ends_with_last_leaf();
+                proof.root() == self.root
+                    && (self.left.is_some() || starts_first)
+                    && (self.right.is_some() || ends_last)
+                    && proof.validate_iter(
+                        self.left
+                            .iter()
+                            .map(|k| k.as_slice())
+                            .chain(keys.iter().map(|k| k.as_ref()))
+                            .chain(self.right.iter().map(|k| k.as_slice())),
+                    )
+            }
+            // the tree is empty, its root is the hash of all the leaves padded
+            None => {
+                keys.is_empty()
+                    && self.left.is_none()
+                    && self.right.is_none()
+                    && self.root == Prefixed::<ARITY, H>::default().hash_all()
+            }
+        }
+    }
+
+    /// verifies that the key is absent from the sorted tree that generated this proof,
+    /// the tree is the trusted one only if the proof's root is checked to be its root
+    pub fn validate_exclusion(self, key: &[u8]) -> bool {
+        self.validate::<&[u8]>(key, key, &[])
+    }
+
+    /// returns the proof's root
+    pub fn root(&self) -> H::Output {
+        self.root
+    }
+
+    /// returns the adjacent keys bracketing the range
+    pub fn bracketing_keys(&self) -> (Option<&[u8]>, Option<&[u8]>) {
+        (
+            self.left.as_ref().map(|k| k.as_slice()),
+            self.right.as_ref().map(|k| k.as_slice()),
+        )
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Debug
+    for SortedRangeProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[proof root]:   {:?}", self.root)?;
+        writeln!(f, "[left key]:   {:?}", self.left)?;
+        writeln!(f, "[right key]:   {:?}", self.right)?;
+        write!(f, "{:?}", self.proof)
+    }
+}
@@ -0,0 +1,458 @@
+//! Storage of the nodes of a tree behind a [crate::store::NodeStore] trait,
+//! so the layers are filled, a leaf is replaced and a proof is generated the same way
+//! on an in-memory array (as [crate::StaticTree] does) or on a block device (flash, EEPROM, a file).
+//! A [crate::store::StoredTree] keeps only its root in RAM and reads and writes the nodes of the store on demand.
+//!
+//! With the std feature a [crate::store::FileStore] lays the nodes out in fixed-size pages (no node straddles a page),
+//! reading and writing them through a few buffered pages: while the layers are filled the pages of a batch of children
+//! and the page of their parents stay buffered, so a page is written back once when evicted (least recently used first)
+//! or on flush rather than every few nodes.
+//! ```rust
+//! use merkle_heapless::store::{FileStore, StoredTree};
+//!
+//! let store = FileStore::<ARITY, StdHash>::create("tree.bin", num_of_prefixed!(ARITY, HEIGHT)).unwrap();
+//! let mut tree = StoredTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN, _>::try_from::<&[u8]>(
+//!     store, &[b"apple", b"banana"],
+//! ).unwrap();
+//! tree.try_replace(5, b"cherry").unwrap();
+//! assert!(tree.generate_proof(5).unwrap().validate(b"cherry"));
+//!
+//! // after a restart
+//! let store = FileStore::<ARITY, StdHash>::open("tree.bin", num_of_prefixed!(ARITY, HEIGHT)).unwrap();
+//! let tree = StoredTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN, _>::open(store).unwrap();
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::proof::Proof;
+use crate::traits::{HashT, NodeAggregate, ProofBuilder};
+use crate::utils::{self, location_in_prefixed};
+use crate::{is_arity, layer_size, max_leaves, num_of_prefixed, Assert, Error, IsTrue};
+use core::marker::PhantomData;
+
+/// trait for a storage of the nodes of a tree addressed by index (layers from the leaves up)
+pub trait NodeStore<const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    /// reads a node at index
+    fn read(&self, index: usize) -> Result<Prefixed<ARITY, H, A>, Error>;
+    /// writes a node at index
+    fn write(&mut self, index: usize, node: &Prefixed<ARITY, H, A>) -> Result<(), Error>;
+    /// persists the buffered writes, nothing to do for an in-memory store
+    fn flush(&mut self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> NodeStore<ARITY, H, A>
+    for [Prefixed<ARITY, H, A>]
+{
+    #[inline]
+    fn read(&self, index: usize) -> Result<Prefixed<ARITY, H, A>, Error> {
+        Ok(self[index])
+    }
+    #[inline]
+    fn write(&mut self, index: usize, node: &Prefixed<ARITY, H, A>) -> Result<(), Error> {
+        self[index] = *node;
+        Ok(())
+    }
+}
+
+impl<const ARITY: usize, const N: usize, H: HashT, A: NodeAggregate> NodeStore<ARITY, H, A>
+    for [Prefixed<ARITY, H, A>; N]
+{
+    #[inline]
+    fn read(&self, index: usize) -> Result<Prefixed<ARITY, H, A>, Error> {
+        Ok(self[index])
+    }
+    #[inline]
+    fn write(&mut self, index: usize, node: &Prefixed<ARITY, H, A>) -> Result<(), Error> {
+        self[index] = *node;
+        Ok(())
+    }
+}
+
+// a store staging the writes of a path (a node per layer) in memory on top of another store
+struct Staged<'a, const ARITY: usize, const HEIGHT: usize, H: HashT, S: ?Sized> {
+    store: &'a S,
+    nodes: [(usize, Prefixed<ARITY, H>); HEIGHT],
+    len: usize,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, S: NodeStore<ARITY, H> + ?Sized>
+    NodeStore<ARITY, H> for Staged<'_, ARITY, HEIGHT, H, S>
+{
+    fn read(&self, index: usize) -> Result<Prefixed<ARITY, H>, Error> {
+        match self.nodes[..self.len].iter().find(|(i, _)| *i == index) {
+            Some((_, node)) => Ok(*node),
+            None => self.store.read(index),
+        }
+    }
+    // fails if more nodes than a path are written
+    fn write(&mut self, index: usize, node: &Prefixed<ARITY, H>) -> Result<(), Error> {
+        if let Some((_, staged)) = self.nodes[..self.len].iter_mut().find(|(i, _)| *i == index) {
+            *staged = *node;
+            return Ok(());
+        }
+        *self.nodes.get_mut(self.len).ok_or(Error::Store)? = (index, *node);
+        self.len += 1;
+        Ok(())
+    }
+}
+
+/// Merkle Tree keeping its root in RAM and its nodes in a [NodeStore]
+pub struct StoredTree<
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+    S,
+    PB = Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+> {
+    root: H::Output,
+    store: S,
+    _marker: PhantomData<PB>,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, S, PB>
+    StoredTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, S, PB>
+where
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    S: NodeStore<ARITY, H>,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// creates a tree from an input in a store of at least num_of_prefixed!(ARITY, HEIGHT) nodes,
+    /// fails if the input exceeds the capacity of the tree or the store fails
+    pub fn try_from<T: AsRef<[u8]>>(mut store: S, input: &[T]) -> Result<Self, Error> {
+        if input.len() > max_leaves!(ARITY, HEIGHT) {
+            return Err(Error::Create);
+        }
+        // the leaves are written a node at a time, padded with default hashes
+        for index in 0..layer_size!(ARITY, HEIGHT, 0) {
+            let mut node = Prefixed::<ARITY, H>::default();
+            for (offset, d) in input.iter().skip(index * ARITY).take(ARITY).enumerate() {
+                node.hashes[offset] = utils::hash_leaf::<H, MAX_INPUT_LEN>(d.as_ref());
+            }
+            store.write(index, &node)?;
+        }
+        let (root, ()) = utils::fill_layers(&mut store, HEIGHT, input.len())?;
+        store.flush()?;
+
+        Ok(Self {
+            root,
+            store,
+            _marker: PhantomData,
+        })
+    }
+
+    /// opens a tree previously created in a store
+    pub fn open(store: S) -> Result<Self, Error> {
+        Ok(Self {
+            root: store.read(num_of_prefixed!(ARITY, HEIGHT) - 1)?.hash_all(),
+            store,
+            _marker: PhantomData,
+        })
+    }
+
+    /// replaces an element at index with input,
+    /// fails if the store fails
+    /// panics if index is out of leaf layer bound
+    pub fn try_replace(&mut self, index: usize, input: &[u8]) -> Result<(), Error> {
+        self.try_replace_leaf(index, utils::hash_leaf::<H, MAX_INPUT_LEN>(input))
+    }
+
+    /// replaces a hashed leaf at index,
+    /// fails if the store fails.
+    /// The path is computed in memory before it is written, so a failing read leaves the tree untouched,
+    /// a failing write or flush may leave the path partially written, repaired by [StoredTree::try_rebuild]
+    /// panics if index is out of leaf layer bound
+    pub fn try_replace_leaf(&mut self, index: usize, leaf: H::Output) -> Result<(), Error> {
+        assert!(
+            index < max_leaves!(ARITY, HEIGHT),
+            "index is out of leaf layer bound"
+        );
+        let mut staged = Staged::<ARITY, HEIGHT, H, S> {
+            store: &self.store,
+            nodes: [(0, Prefixed::default()); HEIGHT],
+            len: 0,
+        };
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        let mut node = staged.read(prefixed_index)?;
+        node.hashes[offset] = leaf;
+        staged.write(prefixed_index, &node)?;
+        let (root, ()) = utils::replace_inner(&mut staged, HEIGHT, index)?;
+
+        let Staged { nodes, len, .. } = staged;
+        for (index, node) in &nodes[..len] {
+            self.store.write(*index, node)?;
+        }
+        self.store.flush()?;
+        self.root = root;
+        Ok(())
+    }
+
+    /// rehashes all the layers from the stored leaves and updates the root,
+    /// e.g. to repair a path left partially written by a failing store,
+    /// fails if the store fails
+    pub fn try_rebuild(&mut self) -> Result<(), Error> {
+        let (root, ()) = utils::fill_layers(&mut self.store, HEIGHT, max_leaves!(ARITY, HEIGHT))?;
+        self.store.flush()?;
+        self.root = root;
+        Ok(())
+    }
+
+    /// generates proof at given index on base layer,
+    /// fails if the store fails
+    pub fn generate_proof(&self, index: usize) -> Result<PB, Error> {
+        utils::generate_proof(&self.store, HEIGHT, self.root, index)
+    }
+
+    /// returns the leaf at index
+    pub fn leaf(&self, index: usize) -> Result<H::Output, Error> {
+        let (prefixed_index, offset) = location_in_prefixed::<ARITY>(index);
+        Ok(self.store.read(prefixed_index)?.hashes[offset])
+    }
+
+    /// returns the root
+    pub fn root(&self) -> H::Output {
+        self.root
+    }
+
+    /// returns the store of the nodes
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// returns the store of the nodes dropping the tree
+    pub fn into_store(self) -> S {
+        self.store
+    }
+
+    /// returns tree's height (determined at compile time)
+    pub fn height(&self) -> usize {
+        HEIGHT
+    }
+}
+
+#[cfg(feature = "std")]
+pub use file::FileStore;
+
+#[cfg(feature = "std")]
+mod file {
+    use super::NodeStore;
+    use crate::prefixed::Prefixed;
+    use crate::traits::{HashT, PlainBytes};
+    use crate::utils::HASH_LANES;
+    use crate::{Error, INNER_HASH_PREPEND_VALUE};
+    use core::cell::RefCell;
+    use core::marker::PhantomData;
+    use core::mem::size_of;
+    use std::fs::{File, OpenOptions};
+    use std::io::{Read, Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::vec::Vec;
+
+    // a page held in a buffer
+    struct Page {
+        number: Option<usize>,
+        bytes: Vec<u8>,
+        dirty: bool,
+        // the tick of the last access
+        used: u64,
+    }
+
+    // the buffered pages, evicted least recently used first
+    struct Pages {
+        pages: Vec<Page>,
+        tick: u64,
+    }
+
+    /// [NodeStore] in a file of fixed-size pages, PAGE_SIZE is the erase block of the device it stands for.
+    /// A node is stored as its prefix followed by its hashes, so the hash output is [PlainBytes]
+    pub struct FileStore<const ARITY: usize, H: HashT, const PAGE_SIZE: usize = 4096> {
+        file: File,
+        pages: RefCell<Pages>,
+        _marker: PhantomData<H>,
+    }
+
+    impl<const ARITY: usize, H: HashT, const PAGE_SIZE: usize> FileStore<ARITY, H, PAGE_SIZE> {
+        const NODE_SIZE: usize = size_of::<H::Output>() * (ARITY + 1);
+        const NODES_PER_PAGE: usize = PAGE_SIZE / Self::NODE_SIZE;
+        // the pages a batch of children spans and the page of their parents,
+        // none if a node does not fit into a page (such a store is not created)
+        const BUFFERED_PAGES: usize = match (HASH_LANES - 1).checked_div(Self::NODES_PER_PAGE) {
+            Some(pages) => pages + 2 + 1,
+            None => 0,
+        };
+
+        /// creates (or truncates) a file able to keep a number of nodes,
+        /// fails if the file cannot be created or a node does not fit into a page
+        pub fn create<P: AsRef<Path>>(path: P, num_of_nodes: usize) -> Result<Self, Error> {
+            if Self::NODES_PER_PAGE == 0 {
+                return Err(Error::Create);
+            }
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)
+                .map_err(|_| Error::Store)?;
+            file.set_len(Self::file_len(num_of_nodes))
+                .map_err(|_| Error::Store)?;
+            Ok(Self::with_file(file))
+        }
+
+        /// opens a file created previously to keep a number of nodes,
+        /// fails if a node does not fit into a page, the file cannot be opened
+        /// or its length is not the one of a file created for that number of nodes
+        pub fn open<P: AsRef<Path>>(path: P, num_of_nodes: usize) -> Result<Self, Error> {
+            if Self::NODES_PER_PAGE == 0 {
+                return Err(Error::Create);
+            }
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open(path)
+                .map_err(|_| Error::Store)?;
+            let len = file.metadata().map_err(|_| Error::Store)?.len();
+            if len != Self::file_len(num_of_nodes) {
+                return Err(Error::Store);
+            }
+            Ok(Self::with_file(file))
+        }
+
+        // length of a file keeping a number of nodes, in whole pages
+        fn file_len(num_of_nodes: usize) -> u64 {
+            (num_of_nodes.div_ceil(Self::NODES_PER_PAGE) * PAGE_SIZE) as u64
+        }
+
+        fn with_file(file: File) -> Self {
+            let pages = (0..Self::BUFFERED_PAGES)
+                .map(|_| Page {
+                    number: None,
+                    bytes: std::vec![0u8; PAGE_SIZE],
+                    dirty: false,
+                    used: 0,
+                })
+                .collect();
+            Self {
+                file,
+                pages: RefCell::new(Pages { pages, tick: 0 }),
+                _marker: PhantomData,
+            }
+        }
+
+        // writes the buffered page back if it was written to
+        fn write_back(&self, page: &mut Page) -> Result<(), Error> {
+            if let (Some(number), true) = (page.number, page.dirty) {
+                let mut file = &self.file;
+                file.seek(SeekFrom::Start((number * PAGE_SIZE) as u64))
+                    .and_then(|_| file.write_all(&page.bytes))
+                    .map_err(|_| Error::Store)?;
+                page.dirty = false;
+            }
+            Ok(())
+        }
+
+        // writes all the buffered pages back in the order of the file
+        fn write_back_all(&self, pages: &mut Pages) -> Result<(), Error> {
+            pages.pages.sort_unstable_by_key(|page| page.number);
+            pages
+                .pages
+                .iter_mut()
+                .try_for_each(|page| self.write_back(page))
+        }
+
+        // buffers the page holding a node in place of the least recently used one,
+        // returns the buffered page and the offset of the node within it
+        fn load<'a>(
+            &self,
+            pages: &'a mut Pages,
+            index: usize,
+        ) -> Result<(&'a mut Page, usize), Error> {
+            let number = index / Self::NODES_PER_PAGE;
+            pages.tick += 1;
+            let buffered = pages
+                .pages
+                .iter()
+                .position(|page| page.number == Some(number));
+            let slot = match buffered {
+                Some(slot) => slot,
+                None => {
+                    let (slot, page) = pages
+                        .pages
+                        .iter_mut()
+                        .enumerate()
+                        .min_by_key(|(_, page)| page.used)
+                        .expect("some pages are buffered. qed");
+                    self.write_back(page)?;
+                    page.number = None;
+                    let mut file = &self.file;
+                    file.seek(SeekFrom::Start((number * PAGE_SIZE) as u64))
+                        .and_then(|_| file.read_exact(&mut page.bytes))
+                        .map_err(|_| Error::Store)?;
+                    page.number = Some(number);
+                    slot
+                }
+            };
+            let page = &mut pages.pages[slot];
+            page.used = pages.tick;
+            Ok((page, (index % Self::NODES_PER_PAGE) * Self::NODE_SIZE))
+        }
+
+        /// returns the number of nodes in a page
+        pub fn nodes_per_page() -> usize {
+            Self::NODES_PER_PAGE
+        }
+    }
+
+    impl<const ARITY: usize, H: HashT, const PAGE_SIZE: usize> NodeStore<ARITY, H>
+        for FileStore<ARITY, H, PAGE_SIZE>
+    where
+        H::Output: PlainBytes,
+    {
+        fn read(&self, index: usize) -> Result<Prefixed<ARITY, H>, Error> {
+            let mut pages = self.pages.borrow_mut();
+            let (page, offset) = self.load(&mut pages, index)?;
+
+            let bytes = &page.bytes[offset..offset + Self::NODE_SIZE];
+            let mut chunks = bytes.chunks_exact(size_of::<H::Output>());
+            // a node of another shape or a corrupted one
+            if chunks.next() != Some(H::Output::from(INNER_HASH_PREPEND_VALUE).as_bytes()) {
+                return Err(Error::Store);
+            }
+            let mut node = Prefixed::<ARITY, H>::default();
+            for (hash, chunk) in node.hashes.iter_mut().zip(chunks) {
+                hash.as_bytes_mut().copy_from_slice(chunk);
+            }
+            Ok(node)
+        }
+
+        fn write(&mut self, index: usize, node: &Prefixed<ARITY, H>) -> Result<(), Error> {
+            let mut pages = self.pages.borrow_mut();
+            let (page, offset) = self.load(&mut pages, index)?;
+
+            let bytes = &mut page.bytes[offset..offset + Self::NODE_SIZE];
+            let mut chunks = bytes.chunks_exact_mut(size_of::<H::Output>());
+            chunks
+                .next()
+                .expect("a node is its prefix and ARITY hashes. qed")
+                .copy_from_slice(H::Output::from(INNER_HASH_PREPEND_VALUE).as_bytes());
+            for (chunk, hash) in chunks.zip(node.hashes()) {
+                chunk.copy_from_slice(hash.as_bytes());
+            }
+            page.dirty = true;
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Error> {
+            self.write_back_all(&mut self.pages.borrow_mut())?;
+            self.file.sync_data().map_err(|_| Error::Store)
+        }
+    }
+
+    impl<const ARITY: usize, H: HashT, const PAGE_SIZE: usize> Drop for FileStore<ARITY, H, PAGE_SIZE> {
+        fn drop(&mut self) {
+            let _ = self.write_back_all(&mut self.pages.borrow_mut());
+        }
+    }
+}
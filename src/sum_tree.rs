@@ -0,0 +1,243 @@
+//! Merkle Sum Tree: every node carries a hash and a sum of balances of its subtree.
+//! It wraps a [crate::StaticTree] aggregating [crate::sum_tree::Sum] over the nodes:
+//! the sums of the children are committed into the parent's hash, so the root hash commits to the total.
+//! A proof validates both the hash path and the total at the root,
+//! sums are unsigned and an overflow on any level invalidates the proof.
+//! ```rust
+//! use merkle_heapless::sum_tree::{SumTree};
+//!
+//! const ARITY: usize = 2;
+//! const HEIGHT: usize = 3;
+//! const MAX_WORD_LEN: usize = 10;
+//!
+//! let tree = SumTree::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from::<&[u8]>(&[
+//!     (b"alice", 100), (b"bob", 20), (b"carol", 3),
+//! ]).unwrap();
+//! assert_eq!(tree.total(), 123);
+//!
+//! let proof = tree.generate_proof(1);
+//! assert!(proof.validate(b"bob", 20));
+//! assert!(!tree.generate_proof(1).validate(b"bob", 21));
+//! ```
+
+use crate::proof::{Proof, ProofItem};
+use crate::traits::{NodeAggregate, StaticTreeTrait};
+use crate::{is_arity, num_of_prefixed, Assert, Error, HashT, IsTrue, Prefixed, StaticTree};
+use core::fmt::Debug;
+use core::ops::Deref;
+
+/// unsigned sum of balances, encoded little endian
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Sum(u64);
+
+impl Sum {
+    /// returns the value of the sum
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl From<u64> for Sum {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl NodeAggregate for Sum {
+    type Encoded = [u8; 8];
+    /// sum of the children, None on overflow
+    #[inline]
+    fn combine(children: &[Self]) -> Option<Self> {
+        children
+            .iter()
+            .try_fold(0u64, |acc, s| acc.checked_add(s.0))
+            .map(Self)
+    }
+    #[inline]
+    fn encode(&self) -> Self::Encoded {
+        self.0.to_le_bytes()
+    }
+}
+
+/// Merkle Tree whose nodes carry sums of balances
+pub struct SumTree<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    tree: StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, Sum>, Sum>,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize>
+    SumTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    /// creates a tree from an input of (value, balance) pairs if possible.
+    /// Fails if the input does not fit or the total overflows
+    pub fn try_from<T: AsRef<[u8]> + Deref<Target = [u8]>>(
+        input: &[(T, u64)],
+    ) -> Result<Self, Error> {
+        StaticTree::try_from_aggregated(input).map(|tree| Self { tree })
+    }
+
+    /// replace an element at index with input and its balance.
+    /// Fails if the new total overflows, in that case the tree is not modified.
+    /// panics if index is out of leaf layer bound
+    pub fn replace(&mut self, index: usize, input: &[u8], balance: u64) -> Result<(), Error> {
+        self.tree.try_replace_with(index, input, balance)
+    }
+
+    /// remove an element at index by replacing it with padding
+    /// panics if index is out of leaf layer bound
+    pub fn remove(&mut self, index: usize) {
+        self.tree
+            .try_replace_leaf_with(index, Prefixed::<ARITY, H, Sum>::default_hash(), Sum::default())
+            .expect("total cannot overflow when decreased. qed");
+    }
+
+    /// generate proof at given index on base layer
+    pub fn generate_proof(&self, index: usize) -> SumProof<ARITY, HEIGHT, H, MAX_INPUT_LEN> {
+        SumProof {
+            proof: self.tree.generate_proof(index),
+        }
+    }
+
+    /// return a root hash
+    pub fn root(&self) -> H::Output {
+        self.tree.root()
+    }
+    /// return the sum of all balances
+    pub fn total(&self) -> u64 {
+        self.tree.root_aggregate().value()
+    }
+    /// balance of a leaf at index
+    /// panics if index is out of leaf layer bound
+    pub fn balance(&self, index: usize) -> u64 {
+        self.tree.aggregate(index).value()
+    }
+    /// return a slice of leaves
+    pub fn leaves(&self) -> &[Prefixed<ARITY, H, Sum>] {
+        self.tree.leaves()
+    }
+    /// returns tree's height (determined at compile time)
+    pub fn height(&self) -> usize {
+        HEIGHT
+    }
+    /// returns the underlying tree aggregating the sums
+    pub fn tree(
+        &self,
+    ) -> &StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, Sum>, Sum>
+    {
+        &self.tree
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Clone
+    for SumTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Copy
+    for SumTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Default
+    for SumTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    /// empty tree with the zero total
+    fn default() -> Self {
+        Self::try_from::<&[u8]>(&[]).expect("zero sums don't overflow. qed")
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> PartialEq
+    for SumTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.root() == other.root() && self.total() == other.total()
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> Debug
+    for SumTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[total]:           {}", self.total())?;
+        write!(f, "{:?}", self.tree)
+    }
+}
+
+/// Proof of inclusion of a value with its balance, the [SumTree] generates
+pub struct SumProof<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+where
+    [(); HEIGHT]: Sized,
+{
+    proof: Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN, Sum>,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    SumProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that the input with the balance was contained in the Merkle tree that generated this proof
+    /// and that the balance is accounted for in the total
+    pub fn validate(self, input: &[u8], balance: u64) -> bool {
+        self.proof.validate_with(input, balance)
+    }
+    /// returns the proof's length
+    pub fn height(&self) -> usize {
+        self.proof.height()
+    }
+    /// returns the proof's root
+    pub fn root(&self) -> H::Output {
+        self.proof.root()
+    }
+    /// returns the total the proof claims
+    pub fn total(&self) -> u64 {
+        self.proof.root_aggregate().value()
+    }
+    /// returns the proof's path
+    pub fn path(&self) -> &[ProofItem<ARITY, H, Sum>] {
+        self.proof.path()
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Debug
+    for SumProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[proof total]:   {:?}", self.total())?;
+        write!(f, "{:?}", self.proof)
+    }
+}
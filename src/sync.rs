@@ -0,0 +1,526 @@
+//! Anti-entropy sync of two replicas of the same tree shape over any transport.
+//! The [crate::sync::Initiator] descends from the root into the nodes mismatching the [crate::sync::Responder]'s ones,
+//! requesting node hashes layer-by-layer, and adopts the responder's differing leaves (layer 0)
+//! until both roots match. Both sides are plain state machines doing no I/O,
+//! every [crate::sync::Message] (of a hash whose output is [crate::traits::PlainBytes]) encodes into a frame of [crate::sync::Message::ENCODED_LEN] bytes.
+//! A node is sent a child hash per message, so the frame is a 7-byte header and a single hash whatever the arity,
+//! e.g. 39 bytes for a 32-byte hash: it fits a 64-byte CAN FD frame or a BLE ATT MTU raised past 39 bytes,
+//! yet not the default 23-byte BLE ATT MTU, which leaves no room for a 32-byte hash.
+//! ```rust
+//! let mut initiator = Initiator::<ARITY, HEIGHT, StdHash>::new();
+//! let mut responder = Responder::<ARITY, HEIGHT, StdHash>::new();
+//! let mut frame = [0u8; Message::<ARITY, StdHash>::ENCODED_LEN];
+//!
+//! initiator.start().encode(&mut frame).unwrap();
+//! loop {
+//!     // on the peer's side
+//!     responder.handle(&remote, Message::decode(&frame).unwrap()).unwrap();
+//!     // back on this side, a message per child of the node requested
+//!     let mut request = None;
+//!     while let Some(response) = responder.poll(&remote) {
+//!         response.encode(&mut frame).unwrap();
+//!         request = initiator.handle(&mut local, Message::decode(&frame).unwrap()).unwrap();
+//!     }
+//!     let Some(request) = request else { break };
+//!     request.encode(&mut frame).unwrap();
+//! }
+//! assert_eq!(local.root(), remote.root());
+//! ```
+
+use crate::diff::Cursor;
+use crate::traits::{HashT, PlainBytes};
+use core::fmt::Debug;
+use core::mem::size_of;
+
+/// replica of a tree taking part in the sync
+pub trait SyncReplica<const ARITY: usize, const HEIGHT: usize, H: HashT> {
+    /// returns the hashes of a node at index within a layer (layers are counted from the leaves)
+    fn node_hashes(&self, layer: usize, index: usize) -> Option<[H::Output; ARITY]>;
+    /// replaces a hashed leaf at index with the peer's one
+    fn adopt_leaf(&mut self, index: usize, leaf: H::Output);
+}
+
+/// errors of the sync
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SyncError {
+    /// frame is too short or of unknown kind
+    Decode,
+    /// field does not fit its width in the frame
+    Encode,
+    /// message out of the protocol order
+    Unexpected,
+    /// peer rejected the request, e.g. replicas differ in shape
+    Rejected,
+    /// roots still differ after all mismatching leaves were adopted
+    Diverged,
+}
+
+const REQUEST: u8 = 0;
+const NODES: u8 = 1;
+const DONE: u8 = 2;
+const REJECT: u8 = 3;
+
+// kind, height, layer, index, count
+const REQUEST_LEN: usize = 1 + 1 + 1 + 4 + 2;
+// kind, layer, index, offset, the hash follows
+const NODES_HEADER_LEN: usize = 1 + 1 + 4 + 1;
+
+/// message exchanged by the [Initiator] and the [Responder]
+pub enum Message<const ARITY: usize, H: HashT> {
+    /// request of the node hashes at a layer within index..index + count,
+    /// height is of the requesting replica
+    Request {
+        /// height of the requesting replica
+        height: usize,
+        /// layer counted from the leaves
+        layer: usize,
+        /// index of the first node within the layer
+        index: usize,
+        /// number of the nodes requested
+        count: usize,
+    },
+    /// hash of a child at offset of a node at index within a layer,
+    /// ARITY messages per node requested in the order of the offsets
+    Nodes {
+        /// layer counted from the leaves
+        layer: usize,
+        /// index of the node within the layer
+        index: usize,
+        /// offset of the child within the node
+        offset: usize,
+        /// hash of the child, a hashed leaf at layer 0
+        hash: H::Output,
+    },
+    /// both roots match
+    Done,
+    /// request cannot be served
+    Reject,
+}
+
+impl<const ARITY: usize, H: HashT> Message<ARITY, H> {
+    /// fixed size of an encoded message, a header and a single hash independent of the arity
+    pub const ENCODED_LEN: usize = if NODES_HEADER_LEN + size_of::<H::Output>() > REQUEST_LEN {
+        NODES_HEADER_LEN + size_of::<H::Output>()
+    } else {
+        REQUEST_LEN
+    };
+}
+
+impl<const ARITY: usize, H: HashT> Message<ARITY, H>
+where
+    H::Output: PlainBytes,
+{
+    /// encodes the message into the first [Self::ENCODED_LEN] bytes of a frame, unused bytes are zeroed
+    pub fn encode(&self, frame: &mut [u8]) -> Result<(), SyncError> {
+        let frame = frame
+            .get_mut(..Self::ENCODED_LEN)
+            .ok_or(SyncError::Encode)?;
+        frame.fill(0);
+
+        match self {
+            Self::Request {
+                height,
+                layer,
+                index,
+                count,
+            } => {
+                frame[0] = REQUEST;
+                frame[1] = u8::try_from(*height).map_err(|_| SyncError::Encode)?;
+                frame[2] = u8::try_from(*layer).map_err(|_| SyncError::Encode)?;
+                frame[3..7].copy_from_slice(
+                    &u32::try_from(*index)
+                        .map_err(|_| SyncError::Encode)?
+                        .to_le_bytes(),
+                );
+                frame[7..9].copy_from_slice(
+                    &u16::try_from(*count)
+                        .map_err(|_| SyncError::Encode)?
+                        .to_le_bytes(),
+                );
+            }
+            Self::Nodes {
+                layer,
+                index,
+                offset,
+                hash,
+            } => {
+                frame[0] = NODES;
+                frame[1] = u8::try_from(*layer).map_err(|_| SyncError::Encode)?;
+                frame[2..6].copy_from_slice(
+                    &u32::try_from(*index)
+                        .map_err(|_| SyncError::Encode)?
+                        .to_le_bytes(),
+                );
+                frame[6] = u8::try_from(*offset).map_err(|_| SyncError::Encode)?;
+                frame[NODES_HEADER_LEN..NODES_HEADER_LEN + size_of::<H::Output>()]
+                    .copy_from_slice(hash.as_bytes());
+            }
+            Self::Done => frame[0] = DONE,
+            Self::Reject => frame[0] = REJECT,
+        }
+        Ok(())
+    }
+
+    /// decodes a message from the first [Self::ENCODED_LEN] bytes of a frame
+    pub fn decode(frame: &[u8]) -> Result<Self, SyncError> {
+        let frame = frame.get(..Self::ENCODED_LEN).ok_or(SyncError::Decode)?;
+
+        match frame[0] {
+            REQUEST => Ok(Self::Request {
+                height: frame[1] as usize,
+                layer: frame[2] as usize,
+                index: u32::from_le_bytes([frame[3], frame[4], frame[5], frame[6]]) as usize,
+                count: u16::from_le_bytes([frame[7], frame[8]]) as usize,
+            }),
+            NODES => {
+                let mut hash = H::Output::default();
+                hash.as_bytes_mut().copy_from_slice(
+                    &frame[NODES_HEADER_LEN..NODES_HEADER_LEN + size_of::<H::Output>()],
+                );
+                Ok(Self::Nodes {
+                    layer: frame[1] as usize,
+                    index: u32::from_le_bytes([frame[2], frame[3], frame[4], frame[5]]) as usize,
+                    offset: frame[6] as usize,
+                    hash,
+                })
+            }
+            DONE => Ok(Self::Done),
+            REJECT => Ok(Self::Reject),
+            _ => Err(SyncError::Decode),
+        }
+    }
+}
+
+impl<const ARITY: usize, H: HashT> Clone for Message<ARITY, H> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<const ARITY: usize, H: HashT> Copy for Message<ARITY, H> {}
+
+impl<const ARITY: usize, H: HashT> PartialEq for Message<ARITY, H> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Request {
+                    height,
+                    layer,
+                    index,
+                    count,
+                },
+                Self::Request {
+                    height: other_height,
+                    layer: other_layer,
+                    index: other_index,
+                    count: other_count,
+                },
+            ) => {
+                height == other_height
+                    && layer == other_layer
+                    && index == other_index
+                    && count == other_count
+            }
+            (
+                Self::Nodes {
+                    layer,
+                    index,
+                    offset,
+                    hash,
+                },
+                Self::Nodes {
+                    layer: other_layer,
+                    index: other_index,
+                    offset: other_offset,
+                    hash: other_hash,
+                },
+            ) => {
+                layer == other_layer
+                    && index == other_index
+                    && offset == other_offset
+                    && hash == other_hash
+            }
+            (Self::Done, Self::Done) | (Self::Reject, Self::Reject) => true,
+            _ => false,
+        }
+    }
+}
+
+impl<const ARITY: usize, H: HashT> Debug for Message<ARITY, H> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        match self {
+            Self::Request {
+                height,
+                layer,
+                index,
+                count,
+            } => write!(
+                f,
+                "[request] height: {height}, layer: {layer}, nodes: {index}..{}",
+                index + count
+            ),
+            Self::Nodes {
+                layer,
+                index,
+                offset,
+                hash,
+            } => write!(
+                f,
+                "[nodes] layer: {layer}, index: {index}, offset: {offset}, hash: {hash:?}"
+            ),
+            Self::Done => write!(f, "[done]"),
+            Self::Reject => write!(f, "[reject]"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum InitiatorState {
+    Idle,
+    Awaiting(Cursor),
+    Done,
+}
+
+/// side of the sync that pulls the peer's leaves differing from its own
+pub struct Initiator<const ARITY: usize, const HEIGHT: usize, H: HashT> {
+    // peer's nodes along the current path
+    fetched: [[H::Output; ARITY]; HEIGHT],
+    // number of the child hashes of the requested node received so far
+    received: usize,
+    state: InitiatorState,
+    adopted: usize,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT> Initiator<ARITY, HEIGHT, H> {
+    /// creates an initiator
+    pub fn new() -> Self {
+        Self {
+            fetched: [[H::Output::default(); ARITY]; HEIGHT],
+            received: 0,
+            state: InitiatorState::Idle,
+            adopted: 0,
+        }
+    }
+
+    /// returns the first message to send, a request of the peer's root node
+    pub fn start(&mut self) -> Message<ARITY, H> {
+        let cursor = Cursor::new(HEIGHT);
+        self.state = InitiatorState::Awaiting(cursor);
+        Self::request(&cursor)
+    }
+
+    /// handles the peer's response and returns the next message to send once the requested node is complete,
+    /// a request of the next mismatching node or [Message::Done] once both roots match,
+    /// None while the rest of the node's hashes are awaited
+    pub fn handle<T: SyncReplica<ARITY, HEIGHT, H>>(
+        &mut self,
+        replica: &mut T,
+        message: Message<ARITY, H>,
+    ) -> Result<Option<Message<ARITY, H>>, SyncError> {
+        let InitiatorState::Awaiting(mut cursor) = self.state else {
+            return Err(SyncError::Unexpected);
+        };
+        match message {
+            Message::Nodes {
+                layer,
+                index,
+                offset,
+                hash,
+            } if layer == cursor.layer && index == cursor.node && offset == self.received => {
+                self.fetched[layer][offset] = hash;
+                self.received += 1;
+                if self.received < ARITY {
+                    return Ok(None);
+                }
+                self.received = 0;
+            }
+            Message::Reject => {
+                self.state = InitiatorState::Done;
+                return Err(SyncError::Rejected);
+            }
+            _ => return Err(SyncError::Unexpected),
+        }
+
+        loop {
+            if cursor.offset == ARITY {
+                if !cursor.up::<ARITY>(HEIGHT) {
+                    self.state = InitiatorState::Done;
+                    return (replica.node_hashes(HEIGHT - 1, 0) == Some(self.fetched[HEIGHT - 1]))
+                        .then_some(Some(Message::Done))
+                        .ok_or(SyncError::Diverged);
+                }
+                continue;
+            }
+            let local = replica
+                .node_hashes(cursor.layer, cursor.node)
+                .expect("replicas are of the same shape. qed");
+            let remote = self.fetched[cursor.layer][cursor.offset];
+
+            if local[cursor.offset] == remote {
+                cursor.offset += 1;
+            } else if cursor.layer == 0 {
+                replica.adopt_leaf(cursor.node * ARITY + cursor.offset, remote);
+                self.adopted += 1;
+                cursor.offset += 1;
+            } else {
+                cursor.down::<ARITY>();
+                self.state = InitiatorState::Awaiting(cursor);
+                return Ok(Some(Self::request(&cursor)));
+            }
+        }
+    }
+
+    /// true if the sync is over
+    pub fn is_done(&self) -> bool {
+        matches!(self.state, InitiatorState::Done)
+    }
+
+    /// returns the number of leaves adopted from the peer so far
+    pub fn adopted(&self) -> usize {
+        self.adopted
+    }
+
+    fn request(cursor: &Cursor) -> Message<ARITY, H> {
+        Message::Request {
+            height: HEIGHT,
+            layer: cursor.layer,
+            index: cursor.node,
+            count: 1,
+        }
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT> Default for Initiator<ARITY, HEIGHT, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Pending {
+    Nodes {
+        layer: usize,
+        next: usize,
+        end: usize,
+        offset: usize,
+    },
+    Reject,
+}
+
+/// side of the sync that answers the node requests of the peer
+pub struct Responder<const ARITY: usize, const HEIGHT: usize, H: HashT> {
+    pending: Option<Pending>,
+    done: bool,
+    _marker: core::marker::PhantomData<H>,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT> Responder<ARITY, HEIGHT, H> {
+    /// creates a responder
+    pub fn new() -> Self {
+        Self {
+            pending: None,
+            done: false,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// handles the peer's message, the responses are then taken by [Self::poll].
+    /// A request of a different tree shape or out of the layer bounds is answered by [Message::Reject]
+    pub fn handle<T: SyncReplica<ARITY, HEIGHT, H>>(
+        &mut self,
+        replica: &T,
+        message: Message<ARITY, H>,
+    ) -> Result<(), SyncError> {
+        if self.pending.is_some() || self.done {
+            return Err(SyncError::Unexpected);
+        }
+        match message {
+            Message::Request {
+                height,
+                layer,
+                index,
+                count,
+            } => {
+                let valid = height == HEIGHT
+                    && count > 0
+                    && index
+                        .checked_add(count - 1)
+                        .and_then(|last| replica.node_hashes(layer, last))
+                        .is_some();
+
+                self.pending = Some(if valid {
+                    Pending::Nodes {
+                        layer,
+                        next: index,
+                        end: index + count,
+                        offset: 0,
+                    }
+                } else {
+                    Pending::Reject
+                });
+                Ok(())
+            }
+            Message::Done => {
+                self.done = true;
+                Ok(())
+            }
+            _ => Err(SyncError::Unexpected),
+        }
+    }
+
+    /// returns the next message to send in response to the last request, if any
+    pub fn poll<T: SyncReplica<ARITY, HEIGHT, H>>(
+        &mut self,
+        replica: &T,
+    ) -> Option<Message<ARITY, H>> {
+        match self.pending? {
+            Pending::Nodes {
+                layer,
+                next,
+                end,
+                offset,
+            } => {
+                // the children of a node in order, then the next node
+                self.pending = if offset + 1 < ARITY {
+                    Some(Pending::Nodes {
+                        layer,
+                        next,
+                        end,
+                        offset: offset + 1,
+                    })
+                } else {
+                    (next + 1 < end).then_some(Pending::Nodes {
+                        layer,
+                        next: next + 1,
+                        end,
+                        offset: 0,
+                    })
+                };
+                let hashes = replica
+                    .node_hashes(layer, next)
+                    .expect("the range was checked on request. qed");
+                Some(Message::Nodes {
+                    layer,
+                    index: next,
+                    offset,
+                    hash: hashes[offset],
+                })
+            }
+            Pending::Reject => {
+                self.pending = None;
+                Some(Message::Reject)
+            }
+        }
+    }
+
+    /// true if the peer reported both roots match
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT> Default for Responder<ARITY, HEIGHT, H> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,11 +1,14 @@
+use crate::iter::{LeafHashes, Layers, Order, Walk};
 use crate::prefixed::Prefixed;
+use crate::proof::RangeProof;
 use crate::Error;
 use core::fmt::Debug;
 use core::hash::Hash;
+use core::ops::Range;
 
-impl<const ARITY: usize, H: HashT> PartialEq for Prefixed<ARITY, H> {
+impl<const ARITY: usize, H: HashT, A: NodeAggregate> PartialEq for Prefixed<ARITY, H, A> {
     fn eq(&self, other: &Self) -> bool {
-        self.hashes == other.hashes
+        self.hashes == other.hashes && self.aggregates == other.aggregates
     }
 }
 
@@ -25,25 +28,128 @@ pub trait HashT {
             ))
         }
     }
+    /// hashes independent inputs at once into the outputs of the same length,
+    /// the default implementation hashes them one by one.
+    /// Hashers computing several messages in parallel lanes (SIMD, SHA extensions) override it,
+    /// the leaves and the nodes with aggregates of a tree are fed to it in batches
+    fn hash_many(inputs: &[&[u8]], outputs: &mut [Self::Output]) {
+        for (input, output) in inputs.iter().zip(outputs) {
+            *output = Self::hash(input);
+        }
+    }
+    /// concatenates and hashes independent groups of hashes at once into the outputs of the same length,
+    /// the default implementation calls [HashT::concat_then_hash] on them one by one.
+    /// The nodes without aggregates of a tree are fed to it in batches, so a hasher overriding
+    /// [HashT::hash_many] overrides it as well, consistently with its [HashT::concat_then_hash]
+    fn concat_then_hash_many(groups: &[&[Self::Output]], outputs: &mut [Self::Output]) {
+        for (group, output) in groups.iter().zip(outputs) {
+            *output = Self::concat_then_hash(group);
+        }
+    }
 }
+/// trait for a hash output (or an encoded aggregate) that is a plain array of bytes, so it is encoded, decoded and stored
+/// by its memory representation (by [crate::sync], [crate::store], [crate::view], [crate::compressed]).
+/// A struct wrapping a byte array implements it with `unsafe impl PlainBytes for Wrapped32 {}`
+///
+/// # Safety
+///
+/// The implementor must have all its bytes initialized (no padding), hold no pointers
+/// and be valid for every bit pattern, e.g. a `#[repr(transparent)]` wrapper of a byte array
+pub unsafe trait PlainBytes: Copy {
+    /// returns the bytes of the value
+    fn as_bytes(&self) -> &[u8] {
+        // the implementor is plain initialized bytes
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const Self as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+    /// returns the bytes of the value to be overwritten
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        // the implementor is plain bytes valid for every bit pattern
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self as *mut Self as *mut u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+}
+
+unsafe impl<const N: usize> PlainBytes for [u8; N] {}
+
+/// trait for a hash evaluable in a const context, so a tree is built at compile time
+/// by [crate::StaticTree::from_const]. The implementor should agree with its [HashT] implementation
+/// (and with the [From] and [Default] implementations of the output), so the tree built at compile time
+/// is identical to the one built at runtime
+#[cfg(feature = "const")]
+#[const_trait]
+pub trait ConstHashT: HashT {
+    /// hash implementation evaluable at compile time
+    fn const_hash(input: &[u8]) -> Self::Output;
+    /// concatenation of hashes then hashing evaluable at compile time, agreeing with [HashT::concat_then_hash]
+    /// (a hasher keeping the default [HashT::concat_then_hash] hashes their concatenated bytes by [ConstHashT::const_hash])
+    fn const_concat_then_hash(hashes: &[Self::Output]) -> Self::Output;
+    /// conversion of a byte into an output evaluable at compile time
+    fn const_from(value: u8) -> Self::Output;
+    /// default output evaluable at compile time
+    fn const_default() -> Self::Output;
+}
+/// trait for a value aggregated over the children of a node along with their hashes,
+/// e.g. a sum of balances, a number of present leaves or an interval of keys.
+/// Aggregates of the children are committed into the hash of their parent node,
+/// so a proof validates them together with the hash path.
+/// `()` stands for no aggregate, with it the nodes are hashed exactly as in a plain Merkle tree.
+pub trait NodeAggregate: Copy + Default + PartialEq + Debug {
+    /// fixed length encoding of an aggregate, a byte array such as `[u8; 8]`
+    type Encoded: PlainBytes;
+    /// combines aggregates of ARITY siblings into the aggregate of their parent,
+    /// None if they cannot be combined (e.g. a sum overflows)
+    fn combine(children: &[Self]) -> Option<Self>;
+    /// encodes the aggregate into the bytes hashed along with the hashes of the siblings,
+    /// platform independent (e.g. little endian) for the roots to agree across platforms
+    fn encode(&self) -> Self::Encoded;
+    /// aggregate of a leaf created from input, the empty aggregate by default
+    fn from_input(_input: &[u8]) -> Self {
+        Self::default()
+    }
+}
+
+impl NodeAggregate for () {
+    type Encoded = [u8; 0];
+    #[inline]
+    fn combine(_children: &[Self]) -> Option<Self> {
+        Some(())
+    }
+    #[inline]
+    fn encode(&self) -> Self::Encoded {
+        []
+    }
+}
+
 /// trait that an item contained in a proof implements.
 /// [ProofBuilder] implementor relies on it
-pub trait ProofItemT<const ARITY: usize, H: HashT>: Clone + Default + Debug {
+pub trait ProofItemT<const ARITY: usize, H: HashT, A: NodeAggregate = ()>: Clone + Default + Debug {
     /// consructor
-    fn create(offset: usize, prefixed: Prefixed<ARITY, H>) -> Self;
-    /// hash the hashed input with its siblings
-    fn hash_with_siblings(self, word_hash: H::Output) -> Option<H::Output>;
+    fn create(offset: usize, prefixed: Prefixed<ARITY, H, A>) -> Self;
+    /// hash the hashed input and its aggregate with the siblings,
+    /// returns the parent's hash and aggregate
+    fn hash_with_siblings(self, word_hash: H::Output, aggregate: A) -> Option<(H::Output, A)>;
 }
 /// trait for building a proof
 /// a proof is normally supposed to implement both [ProofBuilder] and [ProofValidator]
 /// [StaticTreeTrait.generate_proof]
-pub trait ProofBuilder<const ARITY: usize, H: HashT>: Default {
+pub trait ProofBuilder<const ARITY: usize, H: HashT, A: NodeAggregate = ()>: Default {
     /// type of the item this proof contains
-    type Item: ProofItemT<ARITY, H>;
+    type Item: ProofItemT<ARITY, H, A>;
     /// create a proof instance and assign its root
     fn from_root(root: H::Output) -> Self;
+    /// assign the aggregate of the root, proofs without aggregates can ignore it
+    fn set_root_aggregate(&mut self, _aggregate: A) {}
     /// add a new item to proof
-    fn push(&mut self, offset: usize, prefixed: Prefixed<ARITY, H>);
+    fn push(&mut self, offset: usize, prefixed: Prefixed<ARITY, H, A>);
 }
 
 /// trait for verifying a proof generated by a [ProofBuilder] implementor
@@ -52,17 +158,43 @@ pub trait ProofValidator {
     fn validate(self, input: &[u8]) -> bool;
 }
 /// trait for a basic Merkle Tree functionality
-pub trait StaticTreeTrait<const ARITY: usize, H: HashT, PB: ProofBuilder<ARITY, H>> {
+pub trait StaticTreeTrait<
+    const ARITY: usize,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H, A>,
+    A: NodeAggregate = (),
+>
+{
     /// generate a proof for a leaf at index
     fn generate_proof(&self, index: usize) -> PB;
+    /// generate a proof for a contiguous span of leaves,
+    /// made up of the proofs of the boundary leaves of the span
+    /// panics if the range is empty or out of leaf layer bound
+    fn generate_range_proof(&self, range: Range<usize>) -> RangeProof<PB> {
+        assert!(range.start < range.end && range.end <= self.base_layer_size() * ARITY);
+        RangeProof::from_boundaries(
+            range.clone(),
+            self.generate_proof(range.start),
+            self.generate_proof(range.end - 1),
+        )
+    }
     /// replace a leaf at index with a new value
     fn replace(&mut self, index: usize, input: &[u8]);
     /// replace a hashed leaf at index with a new hashed value
     fn replace_leaf(&mut self, index: usize, leaf: H::Output);
+    /// return the index of the first leaf holding the hashed value, scans the leaves
+    fn position_of_hash(&self, leaf: &H::Output) -> Option<usize> {
+        self.leaf_hashes()
+            .find_map(|(index, hash)| (&hash == leaf).then_some(index))
+    }
     /// return a root
     fn root(&self) -> H::Output;
     /// return a slice of leaves
-    fn leaves(&self) -> &[Prefixed<ARITY, H>];
+    fn leaves(&self) -> &[Prefixed<ARITY, H, A>];
+    /// iterate over the hashed leaves with their indices, including the empty ones
+    fn leaf_hashes(&self) -> LeafHashes<'_, ARITY, H, A> {
+        LeafHashes::new(self.leaves())
+    }
     /// a size (not necessarily a number of currently present leaves) of the leaf layer
     fn base_layer_size(&self) -> usize;
     /// returns tree's arity (determined at compile time)
@@ -72,6 +204,52 @@ pub trait StaticTreeTrait<const ARITY: usize, H: HashT, PB: ProofBuilder<ARITY,
     /// returns tree's height (determined at compile time)
     fn height(&self) -> usize;
 }
+/// trait for a tree exposing all its nodes and hashing inputs into leaves,
+/// such a tree gets the lookups and the iterations of [StaticTreeExt]
+pub trait TreeNodes<const ARITY: usize, H: HashT, A: NodeAggregate = ()> {
+    /// hash an input into a leaf the way the tree does on replace
+    fn leaf_hash(&self, input: &[u8]) -> H::Output;
+    /// return a slice of all nodes, layer after layer from the leaves up
+    fn nodes(&self) -> &[Prefixed<ARITY, H, A>];
+}
+/// lookups and iterations of a [StaticTreeTrait] implementor exposing its nodes by [TreeNodes],
+/// implemented for every such tree
+pub trait StaticTreeExt<
+    const ARITY: usize,
+    H: HashT,
+    PB: ProofBuilder<ARITY, H, A>,
+    A: NodeAggregate = (),
+>: StaticTreeTrait<ARITY, H, PB, A> + TreeNodes<ARITY, H, A>
+{
+    /// return the index of the first leaf holding the input
+    fn position(&self, input: &[u8]) -> Option<usize> {
+        self.position_of_hash(&self.leaf_hash(input))
+    }
+    /// iterate over the layers from the leaves up, each as a slice of nodes
+    fn layers(&self) -> Layers<'_, ARITY, H, A> {
+        Layers::new(self.nodes(), self.height())
+    }
+    /// iterate over (layer, index, hash) of every node in the given order,
+    /// the root is at layer = height
+    fn walk(&self, order: Order) -> Walk<'_, ARITY, H, A> {
+        Walk::new(self.nodes(), self.root(), self.height(), order)
+    }
+    /// call the visitor with (layer, index, hash) of every node in the given order
+    fn visit(&self, order: Order, visitor: &mut dyn FnMut(usize, usize, H::Output)) {
+        for (layer, index, hash) in self.walk(order) {
+            visitor(layer, index, hash);
+        }
+    }
+}
+
+impl<const ARITY: usize, H, PB, A, T> StaticTreeExt<ARITY, H, PB, A> for T
+where
+    H: HashT,
+    PB: ProofBuilder<ARITY, H, A>,
+    A: NodeAggregate,
+    T: StaticTreeTrait<ARITY, H, PB, A> + TreeNodes<ARITY, H, A> + ?Sized,
+{
+}
 /// trait for append-only Merkle tree semantics
 pub trait AppendOnly {
     /// appends a new leaf into the tree unless it's full
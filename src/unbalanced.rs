@@ -0,0 +1,270 @@
+//! Tree wrapper whose logical shape follows the number of leaves rather than the capacity of the tree,
+//! so a proof of a leaf in a tree of n leaves is at most ceil(log(n)) items long whatever the HEIGHT.
+//! The nodes are kept in the static backing array of a [crate::StaticTree],
+//! yet a node whose children past the first one are all padding is collapsed into its first child,
+//! i.e. it passes the hash of the first child up unchanged and is left out of the proofs.
+//! For a binary tree the shape is the one of RFC 6962: the leaves are split at the largest power of 2
+//! less than their number, the left subtree is perfect and the right one is shaped recursively.
+//! As the shape depends on the number of leaves, a [crate::unbalanced::UnbalancedProof] carries it with the index of the leaf
+//! and checks its path against the one they determine, so a verifier compares them with trusted ones along with the root.
+//! ```rust
+//! use merkle_heapless::unbalanced::UnbalancedTree;
+//!
+//! let mut tree = UnbalancedTree::<2, 10, StdHash, MAX_WORD_LEN>::try_from(&[b"apple", b"banana"]).unwrap();
+//! tree.try_append(b"kiwi").unwrap();
+//! // a single item rather than 10
+//! let proof = tree.generate_proof(2);
+//! assert_eq!(proof.height(), 1);
+//! assert_eq!((proof.num_of_leaves(), proof.claim_index()), (3, 2));
+//! assert!(proof.validate(b"kiwi"));
+//! ```
+
+use crate::proof::{Proof, ProofItem};
+use crate::traits::{AppendOnly, HashT, ProofBuilder, ProofValidator};
+use crate::utils::{self, location_in_prefixed, pow_arity};
+use crate::{is_arity, layer_size, max_leaves, num_of_prefixed, Assert, Error, IsTrue, StaticTree};
+use core::fmt::Debug;
+use core::ops::Deref;
+
+// a node at index within a layer is collapsed unless a leaf is past its first child
+fn is_collapsed<const ARITY: usize>(num_of_leaves: usize, layer: usize, index: usize) -> bool {
+    (index * ARITY + 1) * pow_arity::<ARITY>(layer) >= num_of_leaves
+}
+
+/// Proof of a leaf of an [UnbalancedTree], as long as the number of non-collapsed nodes on its path.
+/// Carries the number of leaves and the index of the leaf, which determine the nodes collapsed,
+/// and validates only if the offsets of its items are the ones of the leaf on the non-collapsed layers.
+/// A verifier compares [UnbalancedProof::root] and [UnbalancedProof::num_of_leaves] with trusted ones
+pub struct UnbalancedProof<
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+> where
+    [(); HEIGHT]: Sized,
+{
+    proof: Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+    num_of_leaves: usize,
+    index: usize,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> ProofValidator
+    for UnbalancedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    /// verifies that the input was contained in the unbalanced tree of the proof's number of leaves
+    /// at the proof's index
+    fn validate(self, input: &[u8]) -> bool {
+        self.is_shaped() && self.proof.validate(input)
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize>
+    UnbalancedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    // checks the path against the one of the leaf at index in the tree of num_of_leaves leaves
+    fn is_shaped(&self) -> bool {
+        if self.index >= self.num_of_leaves || self.num_of_leaves > max_leaves!(ARITY, HEIGHT) {
+            return false;
+        }
+        let mut path = self.proof.path().iter();
+        let (mut j, mut offset) = location_in_prefixed::<ARITY>(self.index);
+        for layer in 0..HEIGHT {
+            if !is_collapsed::<ARITY>(self.num_of_leaves, layer, j)
+                && path.next().map(|item| item.offset()) != Some(offset)
+            {
+                return false;
+            }
+            (j, offset) = location_in_prefixed::<ARITY>(j);
+        }
+        path.next().is_none()
+    }
+
+    /// returns the number of leaves of the tree that generated this proof
+    pub fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+
+    /// returns the index of claim as tree's leaf
+    pub fn claim_index(&self) -> usize {
+        self.index
+    }
+
+    /// returns the proof's length
+    pub fn height(&self) -> usize {
+        self.proof.height()
+    }
+
+    /// returns the proof's root
+    pub fn root(&self) -> H::Output {
+        self.proof.root()
+    }
+
+    /// returns the proof's path (as long as the proof's height)
+    pub fn path(&self) -> &[ProofItem<ARITY, H>] {
+        self.proof.path()
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H: HashT, const MAX_INPUT_LEN: usize> Debug
+    for UnbalancedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); HEIGHT]: Sized,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> Result<(), core::fmt::Error> {
+        writeln!(f, "[proof leaves]:   {:?}", self.num_of_leaves)?;
+        writeln!(f, "[proof index]:   {:?}", self.index)?;
+        write!(f, "{:?}", self.proof)
+    }
+}
+
+/// Tree wrapper collapsing the nodes with padding past their first child
+pub struct UnbalancedTree<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    tree: StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+    num_of_leaves: usize,
+    root: H::Output,
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize>
+    UnbalancedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    /// creates a tree from an input if possible
+    pub fn try_from<T: AsRef<[u8]> + Deref<Target = [u8]>>(input: &[T]) -> Result<Self, Error> {
+        let mut tree = StaticTree::create(input.len())?;
+        let omit_prefix = input.iter().map(|d| d.len()).max() >= Some(MAX_INPUT_LEN);
+        // fill the base layer
+        utils::hash_leaves::<H, MAX_INPUT_LEN>(
+            input.iter().map(|d| d.as_ref()),
+            omit_prefix,
+            |i, hash| {
+                let (index, offset) = location_in_prefixed::<ARITY>(i);
+                tree.prefixed[index].hashes[offset] = hash;
+            },
+        );
+        let mut this = Self {
+            tree,
+            num_of_leaves: input.len(),
+            root: Default::default(),
+        };
+        // fill the rest of layers, the padding nodes are left default
+        let mut layer_base = 0;
+        let mut data_nodes = input.len().div_ceil(ARITY);
+        for layer in 0..HEIGHT - 1 {
+            let parent_layer_base = layer_base + layer_size!(ARITY, HEIGHT, layer);
+            for j in 0..data_nodes {
+                let (parent, offset) = location_in_prefixed::<ARITY>(j);
+                this.tree.prefixed[parent_layer_base + parent].hashes[offset] =
+                    this.hash_of(layer_base, layer, j);
+            }
+            layer_base = parent_layer_base;
+            data_nodes = data_nodes.div_ceil(ARITY);
+        }
+        this.root = this.hash_of(layer_base, HEIGHT - 1, 0);
+        Ok(this)
+    }
+
+    /// replaces an element at index with input
+    /// panics unless index is less than the number of leaves
+    pub fn replace(&mut self, index: usize, input: &[u8]) {
+        assert!(index < self.num_of_leaves, "index is out of leaves bound");
+        self.set_leaf(index, utils::hash_leaf::<H, MAX_INPUT_LEN>(input));
+    }
+
+    /// generates the proof of a leaf at index, as long as the number of non-collapsed nodes on its path
+    /// panics unless index is less than the number of leaves
+    pub fn generate_proof(&self, index: usize) -> UnbalancedProof<ARITY, HEIGHT, H, MAX_INPUT_LEN> {
+        assert!(index < self.num_of_leaves, "index is out of leaves bound");
+        let mut proof = Proof::from_root(self.root);
+        let (mut j, mut offset) = location_in_prefixed::<ARITY>(index);
+        let mut layer_base = 0;
+        for layer in 0..HEIGHT {
+            if !is_collapsed::<ARITY>(self.num_of_leaves, layer, j) {
+                proof.push(offset, self.tree.prefixed[layer_base + j]);
+            }
+            layer_base += pow_arity::<ARITY>(HEIGHT - layer - 1);
+            (j, offset) = location_in_prefixed::<ARITY>(j);
+        }
+        UnbalancedProof {
+            proof,
+            num_of_leaves: self.num_of_leaves,
+            index,
+        }
+    }
+
+    /// returns the root, the hash of the only leaf of a tree of a single leaf
+    /// and the default hash of an empty tree
+    pub fn root(&self) -> H::Output {
+        self.root
+    }
+
+    /// returns the hashed leaf at index
+    /// panics if index is out of leaf layer bound
+    pub fn leaf(&self, index: usize) -> H::Output {
+        let (index, offset) = location_in_prefixed::<ARITY>(index);
+        self.tree.prefixed[index].hashes[offset]
+    }
+
+    // the hash of a node passed up to its parent
+    fn hash_of(&self, layer_base: usize, layer: usize, index: usize) -> H::Output {
+        let node = &self.tree.prefixed[layer_base + index];
+        if is_collapsed::<ARITY>(self.num_of_leaves, layer, index) {
+            node.hashes[0]
+        } else {
+            node.hash_all()
+        }
+    }
+
+    // sets a leaf and rehashes its path, the collapsed nodes on it included
+    fn set_leaf(&mut self, index: usize, leaf: H::Output) {
+        let (mut j, offset) = location_in_prefixed::<ARITY>(index);
+        self.tree.prefixed[j].hashes[offset] = leaf;
+
+        let mut layer_base = 0;
+        for layer in 0..HEIGHT - 1 {
+            let parent_layer_base = layer_base + layer_size!(ARITY, HEIGHT, layer);
+            let (parent, offset) = location_in_prefixed::<ARITY>(j);
+            self.tree.prefixed[parent_layer_base + parent].hashes[offset] =
+                self.hash_of(layer_base, layer, j);
+            layer_base = parent_layer_base;
+            j = parent;
+        }
+        self.root = self.hash_of(layer_base, HEIGHT - 1, 0);
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize> AppendOnly
+    for UnbalancedTree<ARITY, HEIGHT, H, MAX_INPUT_LEN>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+{
+    // only the nodes on the path of the appended leaf change their shape
+    fn try_append(&mut self, input: &[u8]) -> Result<(), Error> {
+        if self.num_of_leaves >= max_leaves!(ARITY, HEIGHT) {
+            return Err(Error::Append);
+        }
+        self.num_of_leaves += 1;
+        self.set_leaf(
+            self.num_of_leaves - 1,
+            utils::hash_leaf::<H, MAX_INPUT_LEN>(input),
+        );
+        Ok(())
+    }
+
+    fn num_of_leaves(&self) -> usize {
+        self.num_of_leaves
+    }
+}
@@ -1,17 +1,48 @@
-//use crate::HashT;
+use crate::prefixed::Prefixed;
+use crate::store::NodeStore;
+use crate::traits::{HashT, NodeAggregate, PlainBytes, ProofBuilder};
+use crate::Error;
 
 #[inline]
 pub fn location_in_prefixed<const ARITY: usize>(index: usize) -> (usize, usize) {
-    let offset = index & (ARITY - 1); // index modulo ARITY
-    let index = index >> ARITY.trailing_zeros();
-    (index, offset)
+    (div_arity::<ARITY>(index), mod_arity::<ARITY>(index))
+}
+
+/// index divided by the arity, a shift if the arity is a power of 2
+#[inline]
+pub(crate) const fn div_arity<const ARITY: usize>(index: usize) -> usize {
+    if crate::is_pow2!(ARITY) {
+        index >> ARITY.trailing_zeros()
+    } else {
+        index / ARITY
+    }
+}
+
+/// index modulo the arity, a mask if the arity is a power of 2
+#[inline]
+pub(crate) const fn mod_arity<const ARITY: usize>(index: usize) -> usize {
+    if crate::is_pow2!(ARITY) {
+        index & (ARITY - 1)
+    } else {
+        index % ARITY
+    }
+}
+
+/// the arity raised to a power, a shift if the arity is a power of 2
+#[inline]
+pub(crate) const fn pow_arity<const ARITY: usize>(exp: usize) -> usize {
+    if crate::is_pow2!(ARITY) {
+        1 << (ARITY.trailing_zeros() as usize * exp)
+    } else {
+        ARITY.pow(exp as u32)
+    }
 }
 
 #[macro_export]
-///
+/// number of prefixed nodes in a tree with given arity and height
 macro_rules! num_of_prefixed {
     ($arity:expr, $height:expr) => {
-        ((1 << ($arity.trailing_zeros() as usize * ($height))) - 1) / ($arity - 1)
+        ($arity.pow(($height) as u32) - 1) / ($arity - 1)
     };
 }
 
@@ -19,7 +50,7 @@ macro_rules! num_of_prefixed {
 /// total size of elements in a tree with given arity and height
 macro_rules! total_size {
     ($arity:expr, $height:expr) => {
-        ((1 << ($arity.trailing_zeros() as usize * ($height + 1))) - 1) / ($arity - 1)
+        ($arity.pow(($height + 1) as u32) - 1) / ($arity - 1)
     };
 }
 
@@ -27,8 +58,7 @@ macro_rules! total_size {
 /// size of a layer at index in a tree with given arity and height
 macro_rules! layer_size {
     ($arity:expr, $height:expr, $layer_index:expr) => {
-        //1 << ($arity.trailing_zeros() as usize * ($height - $layer_index))
-        1 << ($arity.trailing_zeros() as usize * ($height - $layer_index - 1))
+        $arity.pow(($height - $layer_index - 1) as u32)
     };
 }
 
@@ -47,6 +77,351 @@ macro_rules! is_pow2 {
         ($x.leading_zeros() + $x.trailing_zeros()) as usize == 8 * core::mem::size_of::<usize>() - 1
     };
 }
+
+#[macro_export]
+/// determines if a number is a valid arity of a tree, i.e. at least 2
+macro_rules! is_arity {
+    ($x:expr) => {
+        $x >= 2
+    };
+}
+/// prepends input with leaf prefix and hashes it
+/// the prefix is omitted for an input occupying the whole input buffer
+#[inline]
+pub(crate) fn hash_leaf<H: HashT, const MAX_INPUT_LEN: usize>(input: &[u8]) -> H::Output {
+    let start_index = if input.len() < MAX_INPUT_LEN { 1 } else { 0 };
+
+    let n = input.len() + start_index;
+    let mut prefixed = [crate::LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN];
+    prefixed[start_index..n].copy_from_slice(input);
+
+    H::hash(&prefixed[0..n])
+}
+
+/// number of the independent inputs fed to [HashT::hash_many] at once
+pub(crate) const HASH_LANES: usize = 8;
+
+/// hashes inputs as leaves in batches of [HASH_LANES] and passes every hash to sink along with its input's index,
+/// the inputs are prepended with the leaf prefix unless it is omitted for all of them
+/// or an input is of MAX_INPUT_LEN (see [hash_leaf])
+pub(crate) fn hash_leaves<'a, H: HashT, const MAX_INPUT_LEN: usize>(
+    mut inputs: impl Iterator<Item = &'a [u8]>,
+    omit_prefix: bool,
+    mut sink: impl FnMut(usize, H::Output),
+) {
+    let mut prefixed = [[crate::LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN]; HASH_LANES];
+    let mut lens = [0; HASH_LANES];
+    let mut hashes = [H::Output::default(); HASH_LANES];
+    let mut index = 0;
+    loop {
+        let mut batch = 0;
+        for (p, len) in prefixed.iter_mut().zip(lens.iter_mut()) {
+            let Some(input) = inputs.next() else {
+                break;
+            };
+            let start_index = if omit_prefix || input.len() >= MAX_INPUT_LEN {
+                0
+            } else {
+                1
+            };
+            *len = input.len() + start_index;
+            p[start_index..*len].copy_from_slice(input);
+            batch += 1;
+        }
+        if batch == 0 {
+            return;
+        }
+        let batched: [&[u8]; HASH_LANES] = core::array::from_fn(|i| &prefixed[i][..lens[i]]);
+        H::hash_many(&batched[..batch], &mut hashes[..batch]);
+        for hash in &hashes[..batch] {
+            sink(index, *hash);
+            index += 1;
+        }
+    }
+}
+
+/// hashes nodes like [Prefixed::hash_all] in batches of [HASH_LANES] into the outputs of the same length:
+/// the nodes without aggregates by [HashT::concat_then_hash_many], the others by [HashT::hash_many]
+pub(crate) fn hash_nodes<const ARITY: usize, H: HashT, A: NodeAggregate>(
+    nodes: &[Prefixed<ARITY, H, A>],
+    outputs: &mut [H::Output],
+) {
+    for (nodes, outputs) in nodes.chunks(HASH_LANES).zip(outputs.chunks_mut(HASH_LANES)) {
+        if core::mem::size_of::<A>() == 0 {
+            let batched: [&[H::Output]; HASH_LANES] =
+                core::array::from_fn(|i| nodes.get(i).map_or(&[][..], Prefixed::concat_input));
+            H::concat_then_hash_many(&batched[..nodes.len()], outputs);
+        } else {
+            let inputs: [_; HASH_LANES] =
+                core::array::from_fn(|i| nodes.get(i).copied().unwrap_or_default().hash_input());
+            let batched: [&[u8]; HASH_LANES] = core::array::from_fn(|i| inputs[i].as_bytes());
+            H::hash_many(&batched[..nodes.len()], outputs);
+        }
+    }
+}
+
+/// hashes a salted leaf as the leaf prefix followed by the salt and the value,
+/// the prefix is never omitted so panics unless salt and value are shorter than MAX_INPUT_LEN
+#[inline]
+pub(crate) fn hash_salted_leaf<H: HashT, const MAX_INPUT_LEN: usize>(
+    salt: &[u8],
+    value: &[u8],
+) -> H::Output {
+    let n = 1 + salt.len() + value.len();
+    let mut prefixed = [crate::LEAF_HASH_PREPEND_VALUE; MAX_INPUT_LEN];
+    prefixed[1..1 + salt.len()].copy_from_slice(salt);
+    prefixed[1 + salt.len()..n].copy_from_slice(value);
+
+    H::hash(&prefixed[0..n])
+}
+
+/// index of the parent prefixed and the base index of the parent layer
+#[inline]
+pub(crate) fn parent_index_and_base<const ARITY: usize>(
+    height: usize,
+    index: usize,
+    layer: usize,
+    layer_base: usize,
+) -> (usize, usize) {
+    let curr_layer_len = pow_arity::<ARITY>(height - layer - 1);
+    let parent_layer_base = layer_base + curr_layer_len;
+    let parent_index = parent_layer_base + div_arity::<ARITY>(index - layer_base);
+
+    (parent_index, parent_layer_base)
+}
+
+/// index of the first prefixed of a layer in a tree of given height
+#[inline]
+pub(crate) fn layer_base<const ARITY: usize>(height: usize, layer: usize) -> usize {
+    (0..layer).map(|l| pow_arity::<ARITY>(height - l - 1)).sum()
+}
+
+/// pads the base layer of a tree of given height with default hashes
+/// and empty aggregates starting at from_index,
+/// fails if the store fails
+#[inline]
+pub(crate) fn pad_leaves<const ARITY: usize, H, A, S>(
+    store: &mut S,
+    height: usize,
+    from_index: usize,
+) -> Result<(), Error>
+where
+    H: HashT,
+    A: NodeAggregate,
+    S: NodeStore<ARITY, H, A> + ?Sized,
+{
+    let default_hash = Prefixed::<ARITY, H, A>::default_hash();
+    let default_hashes = [default_hash; ARITY];
+    let to_index = core::cmp::min((from_index / ARITY + 1) * ARITY, max_leaves!(ARITY, height));
+    // pad first partial prefixed hashes in the base layer
+    for i in from_index..to_index {
+        let (index, offset) = location_in_prefixed::<ARITY>(i);
+        let mut node = store.read(index)?;
+        node.hashes[offset] = default_hash;
+        node.aggregates[offset] = A::default();
+        store.write(index, &node)?;
+    }
+    // pad the rest of hashes in the base layer
+    for index in to_index / ARITY..layer_size!(ARITY, height, 0) {
+        let mut node = store.read(index)?;
+        node.hashes = default_hashes;
+        node.aggregates = [A::default(); ARITY];
+        store.write(index, &node)?;
+    }
+    Ok(())
+}
+
+/// hashes the layers of a tree of given height bottom-up and returns the root with its aggregate,
+/// fails if aggregates cannot be combined or the store fails
+pub(crate) fn fill_layers<const ARITY: usize, H, A, S>(
+    store: &mut S,
+    height: usize,
+    data_len: usize,
+) -> Result<(H::Output, A), Error>
+where
+    H: HashT,
+    A: NodeAggregate,
+    S: NodeStore<ARITY, H, A> + ?Sized,
+{
+    let mut layer_base = 0;
+    // number of the nodes holding data in the current layer, the rest are padding
+    let mut data_nodes = data_len.div_ceil(ARITY);
+    let mut children = [Prefixed::<ARITY, H, A>::default(); HASH_LANES];
+    let mut hashes = [H::Output::default(); HASH_LANES];
+
+    for layer in 0..height - 1 {
+        let layer_len = layer_size!(ARITY, height, layer);
+        let parent_layer_base = layer_base + layer_len;
+        // hash the siblings of the current layer in batches and fill the upper layer,
+        // a parent is written once with the hashes of all its children
+        let mut node = Prefixed::<ARITY, H, A>::default();
+        for start in (0..layer_len).step_by(HASH_LANES) {
+            let batch = core::cmp::min(HASH_LANES, layer_len - start);
+            let data = core::cmp::min(batch, data_nodes.saturating_sub(start));
+            for (i, child) in children[..data].iter_mut().enumerate() {
+                *child = store.read(layer_base + start + i)?;
+            }
+            hash_nodes(&children[..data], &mut hashes[..data]);
+
+            for i in 0..batch {
+                let offset = mod_arity::<ARITY>(start + i);
+                // a data node with all its children padding is padding, as on a replace
+                if i < data && !children[i].is_padding() {
+                    node.hashes[offset] = hashes[i];
+                    node.aggregates[offset] = children[i].aggregate().ok_or(Error::Overflow)?;
+                }
+                if offset == ARITY - 1 {
+                    let parent = div_arity::<ARITY>(start + i);
+                    store.write(parent_layer_base + parent, &node)?;
+                    node = Prefixed::default();
+                }
+            }
+        }
+        // move on to the upper layer
+        layer_base = parent_layer_base;
+        data_nodes = data_nodes.div_ceil(ARITY);
+    }
+
+    let top = store.read(layer_base)?;
+    Ok((top.hash_all(), top.aggregate().ok_or(Error::Overflow)?))
+}
+
+/// propagates the new hash and aggregate of a leaf at index upwards and returns the new root with its aggregate,
+/// fails if aggregates cannot be combined (the path is then updated only partially) or the store fails
+pub(crate) fn replace_inner<const ARITY: usize, H, A, S>(
+    store: &mut S,
+    height: usize,
+    index: usize,
+) -> Result<(H::Output, A), Error>
+where
+    H: HashT,
+    A: NodeAggregate,
+    S: NodeStore<ARITY, H, A> + ?Sized,
+{
+    let mut layer_base = 0;
+    let mut j = index / ARITY;
+    let mut node = store.read(j)?;
+
+    // start from the base layer and propagate the new hashes upwords
+    for layer in 0..height - 1 {
+        // an emptied node becomes padding again, so removing leaves restores
+        // the root of the tree filled without them
+        let (parent_hashed, parent_aggregate) = if node.is_padding() {
+            (Prefixed::<ARITY, H, A>::default_hash(), A::default())
+        } else {
+            (node.hash_all(), node.aggregate().ok_or(Error::Overflow)?)
+        };
+
+        let offset = mod_arity::<ARITY>(j);
+        (j, layer_base) = parent_index_and_base::<ARITY>(height, j, layer, layer_base);
+
+        node = store.read(j)?;
+        node.hashes[offset] = parent_hashed;
+        node.aggregates[offset] = parent_aggregate;
+        store.write(j, &node)?;
+    }
+    Ok((node.hash_all(), node.aggregate().ok_or(Error::Overflow)?))
+}
+
+/// propagates the new hashes and aggregates of the leaves at indices (at most [HASH_LANES]) upwards
+/// like [replace_inner], the nodes on their paths are hashed a layer at a time in a batch,
+/// returns the new root and its aggregate.
+/// fails if aggregates cannot be combined (the paths are then updated only partially) or the store fails
+pub(crate) fn replace_batch<const ARITY: usize, H, A, S>(
+    store: &mut S,
+    height: usize,
+    indices: &[usize],
+) -> Result<(H::Output, A), Error>
+where
+    H: HashT,
+    A: NodeAggregate,
+    S: NodeStore<ARITY, H, A> + ?Sized,
+{
+    // indices of the nodes on the paths within the current layer, sorted with no duplicates
+    let mut nodes = [0; HASH_LANES];
+    for (node, index) in nodes.iter_mut().zip(indices) {
+        *node = div_arity::<ARITY>(*index);
+    }
+    let mut len = sorted_unique(&mut nodes[..indices.len()]);
+    let mut children = [Prefixed::<ARITY, H, A>::default(); HASH_LANES];
+    let mut hashes = [H::Output::default(); HASH_LANES];
+    let mut layer_base = 0;
+
+    for layer in 0..height - 1 {
+        let parent_layer_base = layer_base + pow_arity::<ARITY>(height - layer - 1);
+        for (child, node) in children.iter_mut().zip(&nodes[..len]) {
+            *child = store.read(layer_base + node)?;
+        }
+        hash_nodes(&children[..len], &mut hashes[..len]);
+
+        for ((child, hash), node) in children.iter().zip(hashes).zip(nodes.iter_mut()).take(len) {
+            // an emptied node becomes padding again, as on a single replace
+            let (hash, aggregate) = if child.is_padding() {
+                (Prefixed::<ARITY, H, A>::default_hash(), A::default())
+            } else {
+                (hash, child.aggregate().ok_or(Error::Overflow)?)
+            };
+            let (parent, offset) = location_in_prefixed::<ARITY>(*node);
+            let mut parent_node = store.read(parent_layer_base + parent)?;
+            parent_node.hashes[offset] = hash;
+            parent_node.aggregates[offset] = aggregate;
+            store.write(parent_layer_base + parent, &parent_node)?;
+            *node = parent;
+        }
+        // the sorted nodes sharing a parent are adjacent
+        len = sorted_unique(&mut nodes[..len]);
+        layer_base = parent_layer_base;
+    }
+    let top = store.read(layer_base)?;
+    Ok((top.hash_all(), top.aggregate().ok_or(Error::Overflow)?))
+}
+
+// sorts a few indices and moves the distinct ones to the front, returns their number
+fn sorted_unique(indices: &mut [usize]) -> usize {
+    indices.sort_unstable();
+    let mut len = 0;
+    for i in 0..indices.len() {
+        if len == 0 || indices[i] != indices[len - 1] {
+            indices[len] = indices[i];
+            len += 1;
+        }
+    }
+    len
+}
+
+/// generates proof for a leaf at index of a tree of given height,
+/// fails if the store fails
+pub(crate) fn generate_proof<const ARITY: usize, H, A, PB, S>(
+    store: &S,
+    height: usize,
+    root: H::Output,
+    index: usize,
+) -> Result<PB, Error>
+where
+    H: HashT,
+    A: NodeAggregate,
+    PB: ProofBuilder<ARITY, H, A>,
+    S: NodeStore<ARITY, H, A> + ?Sized,
+{
+    let mut proof = PB::from_root(root);
+    proof.set_root_aggregate(
+        store
+            .read(num_of_prefixed!(ARITY, height) - 1)?
+            .aggregate()
+            .expect("aggregates of a tree are combinable. qed"),
+    );
+    let mut layer_base = 0;
+    let (mut j, mut offset) = location_in_prefixed::<ARITY>(index);
+
+    for layer in 0..height {
+        proof.push(offset, store.read(j)?);
+
+        offset = mod_arity::<ARITY>(j);
+        (j, layer_base) = parent_index_and_base::<ARITY>(height, j, layer, layer_base);
+    }
+    Ok(proof)
+}
+
 /// auxiliary struct to impose boolean constraints at compile-time
 pub struct Assert<const COND: bool>;
 /// companion for boolean [Assert]
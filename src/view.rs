@@ -0,0 +1,188 @@
+//! Zero-copy view of a serialized tree, e.g. memory-mapped from a file produced by a build server.
+//! The serialized tree is a header followed by the nodes in the exact memory layout of a [crate::StaticTree]:
+//! - arity, height and hash length as little endian u32
+//! - root
+//! - the nodes (layers from the leaves up)
+//!
+//! The bytes are validated once on creating a [crate::view::StaticTreeRef]:
+//! the header should match the type of the view, the length should match the nodes,
+//! every node should start with the inner node prefix and the root should be the hash of the top node.
+//! The nodes are borrowed as they are, so the hash output is [crate::traits::PlainBytes].
+//! ```rust
+//! use merkle_heapless::view::StaticTreeRef;
+//!
+//! // on the build server
+//! let mut bytes = vec![0u8; tree.serialized_len()];
+//! tree.serialize(&mut bytes).unwrap();
+//!
+//! // on the device
+//! let view = StaticTreeRef::<ARITY, HEIGHT, StdHash, MAX_WORD_LEN>::try_from_bytes(&mmapped).unwrap();
+//! assert!(view.generate_proof(2).validate(b"banana"));
+//! ```
+
+use crate::prefixed::Prefixed;
+use crate::proof::Proof;
+use crate::traits::{HashT, PlainBytes, ProofBuilder, StaticTreeTrait, TreeNodes};
+use crate::utils;
+use crate::{
+    is_arity, layer_size, num_of_prefixed, Assert, Error, IsTrue, StaticTree,
+    INNER_HASH_PREPEND_VALUE,
+};
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+
+// arity, height and hash length
+const HEADER_FIELDS_LEN: usize = 3 * size_of::<u32>();
+
+/// Read-only tree borrowing its nodes from serialized bytes
+pub struct StaticTreeRef<
+    'a,
+    const ARITY: usize,
+    const HEIGHT: usize,
+    H: HashT,
+    const MAX_INPUT_LEN: usize,
+    PB = Proof<ARITY, HEIGHT, H, MAX_INPUT_LEN>,
+> {
+    root: H::Output,
+    prefixed: &'a [Prefixed<ARITY, H>],
+    _marker: PhantomData<PB>,
+}
+
+impl<'a, const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    StaticTreeRef<'a, ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    H::Output: PlainBytes,
+    PB: ProofBuilder<ARITY, H>,
+{
+    const NODES_OFFSET: usize = HEADER_FIELDS_LEN + size_of::<H::Output>();
+
+    /// returns the length of a serialized tree of this type
+    pub const fn serialized_len() -> usize {
+        Self::NODES_OFFSET + num_of_prefixed!(ARITY, HEIGHT) * size_of::<Prefixed<ARITY, H>>()
+    }
+
+    /// creates a view of a serialized tree,
+    /// fails if the header does not match the type of the view, the length does not match the nodes,
+    /// the nodes are misaligned, a node does not start with the inner node prefix
+    /// or the root is not the hash of the top node
+    pub fn try_from_bytes(bytes: &'a [u8]) -> Result<Self, Error> {
+        if bytes.len() != Self::serialized_len() {
+            return Err(Error::Create);
+        }
+        let field = |i: usize| {
+            let mut le = [0u8; size_of::<u32>()];
+            le.copy_from_slice(&bytes[i * size_of::<u32>()..(i + 1) * size_of::<u32>()]);
+            u32::from_le_bytes(le) as usize
+        };
+        if field(0) != ARITY || field(1) != HEIGHT || field(2) != size_of::<H::Output>() {
+            return Err(Error::Create);
+        }
+        let nodes = &bytes[Self::NODES_OFFSET..];
+        if nodes.as_ptr() as usize % align_of::<Prefixed<ARITY, H>>() != 0 {
+            return Err(Error::Create);
+        }
+
+        let prefix = H::Output::from(INNER_HASH_PREPEND_VALUE);
+        if nodes
+            .chunks_exact(size_of::<Prefixed<ARITY, H>>())
+            .any(|node| &node[..size_of::<H::Output>()] != prefix.as_bytes())
+        {
+            return Err(Error::Create);
+        }
+
+        let mut root = H::Output::default();
+        root.as_bytes_mut()
+            .copy_from_slice(&bytes[HEADER_FIELDS_LEN..Self::NODES_OFFSET]);
+        // a node without aggregates is its prefix followed by its hashes, all plain bytes,
+        // and the bytes are of the exact length and aligned
+        let prefixed = unsafe {
+            core::slice::from_raw_parts(
+                nodes.as_ptr() as *const Prefixed<ARITY, H>,
+                num_of_prefixed!(ARITY, HEIGHT),
+            )
+        };
+
+        (prefixed[prefixed.len() - 1].hash_all() == root)
+            .then_some(Self {
+                root,
+                prefixed,
+                _marker: PhantomData,
+            })
+            .ok_or(Error::Create)
+    }
+
+    /// generate proof at given index on base layer
+    pub fn generate_proof(&self, index: usize) -> PB {
+        utils::generate_proof(self.prefixed, HEIGHT, self.root, index)
+            .expect("in-memory nodes are readable. qed")
+    }
+
+    /// returns the root
+    pub fn root(&self) -> H::Output {
+        self.root
+    }
+
+    /// returns the leaves (in nodes of ARITY hashes)
+    pub fn leaves(&self) -> &'a [Prefixed<ARITY, H>] {
+        &self.prefixed[..layer_size!(ARITY, HEIGHT, 0)]
+    }
+
+    /// returns all the nodes, layers from the leaves up
+    pub fn nodes(&self) -> &'a [Prefixed<ARITY, H>] {
+        self.prefixed
+    }
+
+    /// returns the hash of an input as a leaf of the tree
+    pub fn leaf_hash(&self, input: &[u8]) -> H::Output {
+        utils::hash_leaf::<H, MAX_INPUT_LEN>(input)
+    }
+
+    /// returns tree's height (determined at compile time)
+    pub fn height(&self) -> usize {
+        HEIGHT
+    }
+}
+
+impl<const ARITY: usize, const HEIGHT: usize, H, const MAX_INPUT_LEN: usize, PB>
+    StaticTree<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>
+where
+    [(); num_of_prefixed!(ARITY, HEIGHT)]: Sized,
+    Assert<{ is_arity!(ARITY) }>: IsTrue,
+    H: HashT,
+    H::Output: PlainBytes,
+    PB: ProofBuilder<ARITY, H>,
+{
+    /// returns the length of the tree serialized for a [StaticTreeRef]
+    pub fn serialized_len(&self) -> usize {
+        StaticTreeRef::<ARITY, HEIGHT, H, MAX_INPUT_LEN, PB>::serialized_len()
+    }
+
+    /// serializes the tree for a [StaticTreeRef] and returns the number of bytes written,
+    /// fails if the output is shorter than the serialized tree
+    pub fn serialize(&self, out: &mut [u8]) -> Result<usize, Error> {
+        let len = self.serialized_len();
+        if out.len() < len {
+            return Err(Error::Create);
+        }
+        let fields = [ARITY, HEIGHT, size_of::<H::Output>()];
+        for (chunk, field) in out.chunks_exact_mut(size_of::<u32>()).zip(fields) {
+            chunk.copy_from_slice(&(field as u32).to_le_bytes());
+        }
+        let mut hashes = out[HEADER_FIELDS_LEN..len].chunks_exact_mut(size_of::<H::Output>());
+        let mut put = |hash: &H::Output| {
+            hashes
+                .next()
+                .expect("the length covers the root and the nodes. qed")
+                .copy_from_slice(hash.as_bytes())
+        };
+        put(&self.root());
+        let prefix = H::Output::from(INNER_HASH_PREPEND_VALUE);
+        for node in self.nodes() {
+            put(&prefix);
+            node.hashes().iter().for_each(&mut put);
+        }
+        Ok(len)
+    }
+}
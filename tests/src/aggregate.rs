@@ -0,0 +1,212 @@
+#[cfg(test)]
+mod aggregate_tests {
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::traits::{NodeAggregate, ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::{Error, StaticTree};
+    use crate::fixture::StdHash;
+
+    // number of non-empty leaves in a subtree
+    #[derive(Clone, Copy, Default, PartialEq, Debug)]
+    pub struct Count(u32);
+    impl Count {
+        fn value(&self) -> u32 {
+            self.0
+        }
+    }
+    impl NodeAggregate for Count {
+        type Encoded = [u8; 4];
+        fn combine(children: &[Self]) -> Option<Self> {
+            children
+                .iter()
+                .try_fold(0u32, |acc, c| acc.checked_add(c.0))
+                .map(Self)
+        }
+        fn encode(&self) -> Self::Encoded {
+            self.0.to_le_bytes()
+        }
+        fn from_input(input: &[u8]) -> Self {
+            Self(!input.is_empty() as u32)
+        }
+    }
+
+    // interval of keys in a subtree, empty if lower bound is greater than upper one
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    pub struct Interval([u8; 2]);
+    impl Default for Interval {
+        fn default() -> Self {
+            Self([u8::MAX, 0])
+        }
+    }
+    impl From<u8> for Interval {
+        fn from(key: u8) -> Self {
+            Self([key, key])
+        }
+    }
+    impl NodeAggregate for Interval {
+        type Encoded = [u8; 2];
+        fn combine(children: &[Self]) -> Option<Self> {
+            Some(Self([
+                children.iter().map(|i| i.0[0]).min()?,
+                children.iter().map(|i| i.0[1]).max()?,
+            ]))
+        }
+        fn encode(&self) -> Self::Encoded {
+            self.0
+        }
+    }
+
+    type CountTree<const ARITY: usize, const HEIGHT: usize> =
+        StaticTree<ARITY, HEIGHT, StdHash, 10, Proof<ARITY, HEIGHT, StdHash, 10, Count>, Count>;
+    type IntervalTree<const ARITY: usize, const HEIGHT: usize> =
+        StaticTree<ARITY, HEIGHT, StdHash, 10, Proof<ARITY, HEIGHT, StdHash, 10, Interval>, Interval>;
+
+    #[test]
+    fn no_aggregate_keeps_plain_hashing() {
+        const ARITY: usize = 4;
+        const HEIGHT: usize = 3;
+        let words: &[&str] = &["apple", "apricot", "banana", "cherry", "kiwi"];
+        let input = words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>();
+
+        let plain = StaticTree::<ARITY, HEIGHT, StdHash, 10>::try_from::<&[u8]>(&input).unwrap();
+        let aggregated =
+            StaticTree::<ARITY, HEIGHT, StdHash, 10, Proof<ARITY, HEIGHT, StdHash, 10, ()>, ()>::try_from::<
+                &[u8],
+            >(&input)
+            .unwrap();
+        assert_eq!(plain.root(), aggregated.root());
+
+        let counted = CountTree::<ARITY, HEIGHT>::try_from::<&[u8]>(&input).unwrap();
+        assert_ne!(plain.root(), counted.root());
+
+        for (i, w) in words.iter().enumerate() {
+            assert!(plain.generate_proof(i).validate(w.as_bytes()));
+            assert!(counted.generate_proof(i).validate(w.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn count_leaves() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 4;
+        let words: &[&str] = &["apple", "apricot", "banana", "cherry", "kiwi"];
+
+        let mut tree = CountTree::<ARITY, HEIGHT>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(tree.root_aggregate().value(), 5);
+
+        tree.replace(10, b"lemon");
+        assert_eq!(tree.root_aggregate().value(), 6);
+        assert_eq!(tree.generate_proof(10).root_aggregate().value(), 6);
+        assert!(tree.generate_proof(10).validate(b"lemon"));
+
+        tree.replace(1, &[]);
+        assert_eq!(tree.root_aggregate().value(), 5);
+        assert!(tree.generate_proof(0).validate(b"apple"));
+        // a proof claiming a different count is not valid
+        let proof = tree.generate_proof(0);
+        assert!(!proof.validate_with(b"apple", Count(2)));
+    }
+
+    #[test]
+    fn interval_of_keys() {
+        const ARITY: usize = 4;
+        const HEIGHT: usize = 3;
+        let entries: &[(&[u8], u8)] = &[(b"apple", 7), (b"banana", 3), (b"cherry", 42)];
+
+        let mut tree = IntervalTree::<ARITY, HEIGHT>::try_from_aggregated(entries).unwrap();
+        assert_eq!(tree.root_aggregate(), Interval([3, 42]));
+        assert_eq!(tree.aggregate(1), Interval::from(3));
+
+        for (i, (word, key)) in entries.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert_eq!(proof.root_aggregate(), Interval([3, 42]));
+            assert!(proof.validate_with(word, *key));
+            assert!(!tree.generate_proof(i).validate_with(word, key + 1));
+        }
+
+        tree.try_replace_with(12, b"kiwi", 100).unwrap();
+        assert_eq!(tree.root_aggregate(), Interval([3, 100]));
+        assert!(tree.generate_proof(12).validate_with(b"kiwi", 100));
+        assert!(tree.generate_proof(0).validate_with(b"apple", 7));
+    }
+
+    #[test]
+    fn fail_on_uncombinable_aggregates() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 2;
+        let max = Count(u32::MAX);
+        let one = Count(1);
+
+        assert!(matches!(
+            CountTree::<ARITY, HEIGHT>::try_from_aggregated::<&[u8], Count>(&[
+                (b"apple", max),
+                (b"apricot", one)
+            ]),
+            Err(Error::Overflow)
+        ));
+
+        let mut tree =
+            CountTree::<ARITY, HEIGHT>::try_from_aggregated::<&[u8], Count>(&[(b"apple", max)])
+                .unwrap();
+        let root = tree.root();
+        assert!(matches!(
+            tree.try_replace_with(3, b"kiwi", one),
+            Err(Error::Overflow)
+        ));
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.aggregate(3), Count::default());
+        assert!(tree.generate_proof(0).validate_with(b"apple", max));
+    }
+
+    #[test]
+    fn emptied_nodes_become_padding() {
+        let words: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+        // a plain tree with its last leaves emptied is the tree filled without them
+        let mut plain = StaticTree::<2, 4, StdHash, 10>::try_from(words).unwrap();
+        let shorter = StaticTree::<2, 4, StdHash, 10>::try_from(&words[..2]).unwrap();
+        for i in 2..words.len() {
+            plain.replace_leaf(i, Default::default());
+        }
+        assert_eq!(plain.root(), shorter.root());
+        assert_eq!(plain.nodes(), shorter.nodes());
+
+        // so is an aggregated one, the aggregates emptied along with the hashes
+        let mut counted = CountTree::<2, 4>::try_from(words).unwrap();
+        for i in 0..words.len() {
+            counted
+                .try_replace_leaf_with(i, Default::default(), Count::default())
+                .unwrap();
+        }
+        let empty = CountTree::<2, 4>::try_from::<&[u8]>(&[]).unwrap();
+        assert_eq!(counted.root(), empty.root());
+        assert_eq!(counted.root_aggregate(), Count::default());
+    }
+
+    #[test]
+    fn edited_as_rebuilt() {
+        let words: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+        // a plain tree with the leaves of a non-trailing node emptied
+        let mut plain = StaticTree::<2, 4, StdHash, 10>::try_from(words).unwrap();
+        plain.replace_leaf(2, Default::default());
+        plain.replace_leaf(3, Default::default());
+        let rebuilt =
+            StaticTree::<2, 4, StdHash, 10>::try_from_leaves(&plain.leaves()[..3]).unwrap();
+        assert_eq!(plain.root(), rebuilt.root());
+        assert_eq!(plain.nodes(), rebuilt.nodes());
+
+        // an aggregated one, the aggregates emptied along with the hashes
+        let mut counted = CountTree::<2, 4>::try_from(words).unwrap();
+        for i in 0..2 {
+            counted
+                .try_replace_leaf_with(i, Default::default(), Count::default())
+                .unwrap();
+        }
+        let rebuilt = CountTree::<2, 4>::try_from_leaves(&counted.leaves()[..3]).unwrap();
+        assert_eq!(counted.root(), rebuilt.root());
+        assert_eq!(counted.root_aggregate(), rebuilt.root_aggregate());
+    }
+}
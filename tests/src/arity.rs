@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod arity_tests {
+    use merkle_heapless::augmentable::DefaultAugmentable;
+    use merkle_heapless::layout::{Blocked, Layout};
+    use merkle_heapless::proof::{Proof, ProofChain};
+    use merkle_heapless::traits::{AppendOnly, HashT, ProofValidator, StaticTreeTrait};
+    use merkle_heapless::{layer_size, max_leaves, num_of_prefixed, StaticTree};
+    use crate::fixture::{StdHash, Wrapped8};
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    // hash of a node as the prefix followed by the hashes
+    fn hash_node(hashes: &[Wrapped8]) -> Wrapped8 {
+        let mut bytes = vec![1u8, 0, 0, 0, 0, 0, 0, 0];
+        for h in hashes {
+            bytes.extend_from_slice(&h.0);
+        }
+        StdHash::hash(&bytes)
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(num_of_prefixed!(3usize, 3), 1 + 3 + 9);
+        assert_eq!(layer_size!(3usize, 3, 0), 9);
+        assert_eq!(max_leaves!(3usize, 3), 27);
+        assert_eq!(num_of_prefixed!(10usize, 3), 111);
+        assert_eq!(max_leaves!(10usize, 2), 100);
+        assert_eq!(num_of_prefixed!(4usize, 5), 341);
+    }
+
+    #[test]
+    fn ternary_tree() {
+        let mut tree = StaticTree::<3, 2, StdHash, 10>::try_from(WORDS).unwrap();
+        let leaf = |i: usize| Proof::<3, 2, StdHash, 10>::hash_as_leaf(WORDS[i]);
+        let empty = Wrapped8::default();
+        let expected = hash_node(&[
+            hash_node(&[leaf(0), leaf(1), leaf(2)]),
+            hash_node(&[leaf(3), leaf(4), empty]),
+            empty,
+        ]);
+        assert_eq!(tree.root(), expected);
+
+        for (i, word) in WORDS.iter().enumerate() {
+            let proof = tree.generate_proof(i);
+            assert_eq!(proof.claim_index(), i);
+            assert!(proof.validate(word));
+        }
+        tree.replace(7, b"lime");
+        assert!(tree.generate_proof(7).validate(b"lime"));
+        assert!(!tree.generate_proof(6).validate(b"lime"));
+        assert_eq!(tree.base_layer_size(), 3);
+    }
+
+    #[test]
+    fn decimal_tree() {
+        let words = (0..250).map(|i| format!("{i}")).collect::<Vec<_>>();
+        let words = words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>();
+        let mut tree = StaticTree::<10, 3, StdHash, 10>::try_from(&words).unwrap();
+        let other = tree;
+        for i in [0, 9, 10, 99, 100, 249] {
+            assert!(tree.generate_proof(i).validate(words[i]));
+        }
+        let proof = tree.generate_range_proof(95..105);
+        assert!(proof.validate(&words[95..105]));
+
+        tree.replace(123, b"changed");
+        tree.replace(999, b"last");
+        assert_eq!(tree.diff(&other).collect::<Vec<_>>(), [123, 999]);
+    }
+
+    #[test]
+    fn append_and_augment() {
+        let mut tree = DefaultAugmentable::<5, 2, StdHash, 10>::try_from(WORDS).unwrap();
+        for _ in WORDS.len()..25 {
+            tree.try_append(b"fig").unwrap();
+        }
+        assert!(tree.try_append(b"fig").is_err());
+
+        let mut tree = tree.augment();
+        tree.try_append(b"plum").unwrap();
+        assert_eq!(tree.num_of_leaves(), 26);
+        assert!(tree.generate_proof(25).validate(b"plum"));
+        assert!(tree.generate_proof(3).validate(b"cherry"));
+    }
+
+    #[test]
+    fn blocked_positions() {
+        for height in 1..6 {
+            let n = num_of_prefixed!(3usize, height);
+            let layout = Blocked::<2>::new::<3>(height);
+            let mut positions = (0..n).map(|i| layout.position(i)).collect::<Vec<_>>();
+            positions.sort();
+            assert_eq!(positions, (0..n).collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn chain_ternary_into_decimal() {
+        let ternary = StaticTree::<3, 2, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut decimal = StaticTree::<10, 2, StdHash, 10>::try_from(WORDS).unwrap();
+        decimal.replace_leaf(42, ternary.root());
+
+        let proof = ProofChain::<10, 4, StdHash, 10>::new(ternary.generate_proof(4))
+            .and_then(|chain| chain.then(decimal.generate_proof(42)))
+            .unwrap()
+            .finish();
+        assert_eq!(proof.claim_index(), 4 + 42 * 9);
+        assert!(proof.validate(b"kiwi"));
+    }
+}
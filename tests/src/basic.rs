@@ -93,7 +93,7 @@ mod basic {
         let word = &[0u8];
         let res = proof.validate(word);
         assert!(!res);
-        
+
         let word_index = 7;
         let proof = mt.as_mut().unwrap().generate_proof(word_index);
         let word = &[];
@@ -0,0 +1,168 @@
+#[cfg(test)]
+mod batched_tests {
+    use std::cell::RefCell;
+
+    use crate::fixture::{StdHash, Wrapped8};
+    use merkle_heapless::lazy::LazyTree;
+    use merkle_heapless::traits::{HashT, ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::StaticTree;
+
+    thread_local! {
+        // sizes of the batches passed to hash_many and concat_then_hash_many
+        static BATCHES: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // the same hash, recording its batches
+    #[derive(Debug)]
+    pub struct LanesHash;
+    impl HashT for LanesHash {
+        type Output = Wrapped8;
+
+        fn hash(input: &[u8]) -> Self::Output {
+            StdHash::hash(input)
+        }
+
+        fn hash_many(inputs: &[&[u8]], outputs: &mut [Self::Output]) {
+            assert_eq!(inputs.len(), outputs.len());
+            BATCHES.with(|b| b.borrow_mut().push(inputs.len()));
+            for (input, output) in inputs.iter().zip(outputs) {
+                *output = Self::hash(input);
+            }
+        }
+
+        fn concat_then_hash_many(groups: &[&[Self::Output]], outputs: &mut [Self::Output]) {
+            assert_eq!(groups.len(), outputs.len());
+            BATCHES.with(|b| b.borrow_mut().push(groups.len()));
+            for (group, output) in groups.iter().zip(outputs) {
+                *output = Self::concat_then_hash(group);
+            }
+        }
+    }
+
+    // a hash concatenating the hashes of a node other than by their bytes
+    #[derive(Debug)]
+    pub struct FoldHash;
+    impl HashT for FoldHash {
+        type Output = Wrapped8;
+
+        fn hash(input: &[u8]) -> Self::Output {
+            StdHash::hash(input)
+        }
+
+        fn concat_then_hash(hashes: &[Self::Output]) -> Self::Output {
+            hashes.iter().fold(Wrapped8::default(), |acc, h| {
+                Self::hash(&[acc.0, h.0].concat())
+            })
+        }
+    }
+
+    const WORDS: &[&[u8]] = &[
+        b"apple", b"apricot", b"banana", b"cherry", b"kiwi", b"lemon", b"lime", b"mango", b"melon",
+        b"orange", b"peach", b"pear",
+    ];
+
+    #[test]
+    fn build_with_batched_hashes() {
+        let scalar = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        BATCHES.with(|b| b.borrow_mut().clear());
+        let mut batched = StaticTree::<4, 3, LanesHash, 10>::try_from(WORDS).unwrap();
+        assert_eq!(batched.root(), scalar.root());
+        assert_eq!(
+            batched
+                .nodes()
+                .iter()
+                .map(|n| *n.hashes())
+                .collect::<Vec<_>>(),
+            scalar
+                .nodes()
+                .iter()
+                .map(|n| *n.hashes())
+                .collect::<Vec<_>>()
+        );
+        // 12 leaves in batches of 8, then 3 data nodes of the base layer and a single one above it
+        BATCHES.with(|b| assert_eq!(*b.borrow(), [8, 4, 3, 1]));
+
+        // a leaf replaced is hashed on its own
+        batched.replace(12, b"plum");
+        assert!(batched.generate_proof(12).validate(b"plum"));
+        let mut scalar = scalar;
+        scalar.replace(12, b"plum");
+        assert_eq!(batched.root(), scalar.root());
+    }
+
+    #[test]
+    fn replace_many_with_batched_hashes() {
+        let mut scalar = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut batched = StaticTree::<4, 3, LanesHash, 10>::try_from(WORDS).unwrap();
+        let updates: &[(usize, &[u8])] = &[
+            (0, b"plum"),
+            (1, b"quince"),
+            (17, b"raisin"),
+            (18, b""),
+            (40, b"sloe"),
+            (63, b"tangerine!"),
+            (1, b"ugli"),
+            (5, b"vanilla"),
+            (6, b"walnut"),
+        ];
+        for (i, word) in updates {
+            scalar.replace(*i, word);
+        }
+        BATCHES.with(|b| b.borrow_mut().clear());
+        batched.replace_many(updates);
+        assert_eq!(batched.root(), scalar.root());
+        // 8 leaves, their 5 nodes in the base layer and 4 above the root,
+        // then the last leaf and its path alone
+        BATCHES.with(|b| assert_eq!(*b.borrow(), [8, 5, 4, 1, 1, 1]));
+        for (i, word) in [(1, &b"ugli"[..]), (63, b"tangerine!"), (6, b"walnut")] {
+            assert!(batched.generate_proof(i).validate(word));
+        }
+    }
+
+    #[test]
+    fn batches_follow_overridden_concat_then_hash() {
+        let tree = StaticTree::<4, 3, FoldHash, 10>::try_from(WORDS).unwrap();
+        // the layers filled in batches agree with a node hashed on its own
+        let mut replaced = StaticTree::<4, 3, FoldHash, 10>::try_from::<&[u8]>(&[]).unwrap();
+        for (i, word) in WORDS.iter().enumerate() {
+            replaced.replace(i, word);
+        }
+        assert_eq!(tree.root(), replaced.root());
+        assert_eq!(tree.nodes(), replaced.nodes());
+        assert_ne!(
+            tree.root(),
+            StaticTree::<4, 3, StdHash, 10>::try_from(WORDS)
+                .unwrap()
+                .root()
+        );
+        for (i, word) in WORDS.iter().enumerate() {
+            assert!(tree.generate_proof(i).validate(word));
+        }
+        let parallel = StaticTree::<4, 3, FoldHash, 10>::par_try_from(WORDS).unwrap();
+        assert_eq!(parallel.root(), tree.root());
+
+        // and so do the batched updates
+        let updates: &[(usize, &[u8])] = &[(3, b"plum"), (17, b"quince"), (40, b"raisin")];
+        let mut eager = tree;
+        let mut many = tree;
+        let mut lazy = LazyTree::new(tree);
+        for (i, word) in updates {
+            eager.replace(*i, word);
+            lazy.replace(*i, word);
+        }
+        many.replace_many(updates);
+        assert_eq!(many.root(), eager.root());
+        assert_eq!(lazy.root(), eager.root());
+        assert!(many.generate_proof(17).validate(b"quince"));
+    }
+
+    #[test]
+    fn build_with_batched_hashes_of_full_input() {
+        // inputs of MAX_INPUT_LEN are not prefixed
+        let input: &[&[u8]] = &[b"apple", b"kiwi", b"cherry", b"banana"];
+        let scalar = StaticTree::<2, 4, StdHash, 6>::try_from(input).unwrap();
+        let batched = StaticTree::<2, 4, LanesHash, 6>::try_from(input).unwrap();
+        assert_eq!(batched.root(), scalar.root());
+        assert!(batched.generate_proof(2).validate(b"cherry"));
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod chain_tests {
+    use merkle_heapless::proof::{chain_proofs, ProofChain};
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait};
+    use merkle_heapless::{Error, StaticTree};
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    // a leaf in a subtree, the subtree's root in a tree, the tree's root in a summit tree
+    fn nested() -> (
+        StaticTree<2, 3, StdHash, 10>,
+        StaticTree<2, 5, StdHash, 10>,
+        StaticTree<2, 2, StdHash, 10>,
+    ) {
+        let subtree = StaticTree::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut tree = StaticTree::<2, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        tree.replace_leaf(9, subtree.root());
+        let mut summit = StaticTree::<2, 2, StdHash, 10>::try_from(&WORDS[..3]).unwrap();
+        summit.replace_leaf(1, tree.root());
+        (subtree, tree, summit)
+    }
+
+    #[test]
+    fn chain_nested_proofs() {
+        let (subtree, tree, summit) = nested();
+
+        let chain = ProofChain::<2, 16, StdHash, 10>::new(subtree.generate_proof(2))
+            .and_then(|chain| chain.then(tree.generate_proof(9)))
+            .and_then(|chain| chain.then(summit.generate_proof(1)))
+            .unwrap();
+        assert_eq!(chain.height(), 3 + 5 + 2);
+        assert_eq!(chain.root(), summit.root());
+
+        let proof = chain.finish();
+        assert_eq!(proof.root(), summit.root());
+        assert_eq!(proof.height(), 10);
+        assert_eq!(proof.claim_index(), 2 + 9 * 8 + 256);
+
+        // the same path as chaining the proofs pairwise
+        let pairwise = chain_proofs(
+            chain_proofs(subtree.generate_proof(2), tree.generate_proof(9)),
+            summit.generate_proof(1),
+        );
+        assert_eq!(pairwise.root(), proof.root());
+        assert_eq!(pairwise.claim_index(), proof.claim_index());
+        assert!(pairwise
+            .path()
+            .iter()
+            .zip(proof.path())
+            .all(|(p, c)| p.offset() == c.offset() && p.hashes()[..] == c.hashes()[..]));
+        assert!(pairwise.validate(b"banana"));
+        assert!(proof.validate(b"banana"));
+    }
+
+    #[test]
+    fn chain_mismatching_proofs() {
+        let (subtree, tree, summit) = nested();
+
+        // the subtree's root is not at leaf 8 of the tree
+        assert!(matches!(
+            ProofChain::<2, 16, StdHash, 10>::new(subtree.generate_proof(2))
+                .and_then(|chain| chain.then(tree.generate_proof(8))),
+            Err(Error::Chain)
+        ));
+        // skipping a tree
+        assert!(matches!(
+            ProofChain::<2, 16, StdHash, 10>::new(subtree.generate_proof(2))
+                .and_then(|chain| chain.then(summit.generate_proof(1))),
+            Err(Error::Chain)
+        ));
+        // longer than the chain
+        assert!(matches!(
+            ProofChain::<2, 9, StdHash, 10>::new(subtree.generate_proof(2))
+                .and_then(|chain| chain.then(tree.generate_proof(9)))
+                .and_then(|chain| chain.then(summit.generate_proof(1))),
+            Err(Error::Chain)
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+#[cfg(test)]
+mod compressed_tests {
+    use merkle_heapless::compactable::DefaultCompactable;
+    use merkle_heapless::compressed::CompressedProof;
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::traits::{CanRemove, ProofValidator, StaticTreeTrait};
+    use merkle_heapless::Error;
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    #[test]
+    fn compress_sparse_proof() {
+        let cmt = DefaultCompactable::<4, 6, StdHash, 10>::try_from(WORDS).unwrap();
+
+        let proof = cmt.generate_proof(3);
+        let compressed = CompressedProof::from(proof);
+        assert_eq!(compressed.height(), 6);
+        assert_eq!(compressed.root(), cmt.root());
+        // three leaves next to the claim, a single non-empty node above it, empty subtrees further up
+        assert_eq!(compressed.siblings().len(), 4);
+        assert_eq!(
+            compressed.items().collect::<Vec<_>>(),
+            [
+                (3, 0b0000),
+                (0, 0b1100),
+                (0, 0b1110),
+                (0, 0b1110),
+                (0, 0b1110),
+                (0, 0b1110)
+            ]
+        );
+
+        let mut buffer = [0u8; 256];
+        let len = compressed.encode(&mut buffer).unwrap();
+        assert_eq!(len, compressed.encoded_len());
+        // the root, the height, 6 offsets and bitmaps, 4 siblings instead of 18
+        assert_eq!(len, 8 + 1 + 6 * 2 + 4 * 8);
+
+        let decoded = CompressedProof::<4, 6, StdHash, 10>::decode(&buffer[..len]).unwrap();
+        assert_eq!(decoded.siblings(), compressed.siblings());
+        assert!(decoded.validate(b"cherry"));
+        assert!(!compressed.validate(b"apricot"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let cmt = DefaultCompactable::<2, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        for (i, word) in WORDS.iter().enumerate() {
+            let restored = Proof::from(CompressedProof::from(cmt.generate_proof(i)));
+            assert_eq!(restored.root(), cmt.root());
+            assert_eq!(restored.height(), 5);
+            assert_eq!(restored.claim_index(), i);
+            assert!(restored.validate(word));
+        }
+        // a removed leaf is the hash of an empty input rather than an empty subtree
+        let mut cmt = cmt;
+        cmt.remove(1);
+        let compressed = CompressedProof::from(cmt.generate_proof(0));
+        assert_eq!(compressed.siblings().len(), 3);
+        assert!(compressed.validate(b"apple"));
+    }
+
+    #[test]
+    fn decode_malformed() {
+        let cmt = DefaultCompactable::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut buffer = [0u8; 64];
+        let len = CompressedProof::from(cmt.generate_proof(0))
+            .encode(&mut buffer)
+            .unwrap();
+
+        assert!(matches!(
+            CompressedProof::<2, 3, StdHash, 10>::decode(&buffer[..len - 1]),
+            Err(Error::Create)
+        ));
+        assert!(matches!(
+            CompressedProof::<2, 2, StdHash, 10>::decode(&buffer[..len]),
+            Err(Error::Create)
+        ));
+        // trailing bytes
+        assert!(matches!(
+            CompressedProof::<2, 3, StdHash, 10>::decode(&buffer[..len + 1]),
+            Err(Error::Create)
+        ));
+        assert!(CompressedProof::<2, 3, StdHash, 10>::decode(&buffer[..len]).is_ok());
+        // the claim marked as an empty sibling
+        let mut marked = buffer;
+        marked[8 + 1 + 1] |= 1;
+        assert!(matches!(
+            CompressedProof::<2, 3, StdHash, 10>::decode(&marked[..len]),
+            Err(Error::Create)
+        ));
+        assert!(
+            CompressedProof::<2, 3, StdHash, 10>::from(cmt.generate_proof(0))
+                .encode(&mut buffer[..len - 1])
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod const_tree_tests {
+    use merkle_heapless::traits::{ConstHashT, HashT, ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::{StaticBinaryTree, StaticTree};
+    use crate::fixture::Wrapped8;
+
+    // FNV-1a, evaluable at compile time
+    #[derive(Debug)]
+    pub struct Fnv64Hash;
+
+    impl HashT for Fnv64Hash {
+        type Output = Wrapped8;
+
+        fn hash(input: &[u8]) -> Self::Output {
+            Self::const_hash(input)
+        }
+
+        fn concat_then_hash(hashes: &[Self::Output]) -> Self::Output {
+            Self::const_concat_then_hash(hashes)
+        }
+    }
+
+    impl const ConstHashT for Fnv64Hash {
+        fn const_hash(input: &[u8]) -> Self::Output {
+            let mut hash: u64 = 0xcbf29ce484222325;
+            let mut i = 0;
+            while i < input.len() {
+                hash ^= input[i] as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+                i += 1;
+            }
+            Wrapped8(hash.to_le_bytes())
+        }
+        // folds the hashes into one another rather than hashing their concatenated bytes
+        fn const_concat_then_hash(hashes: &[Self::Output]) -> Self::Output {
+            let mut acc = [0u8; 16];
+            let mut i = 0;
+            while i < hashes.len() {
+                let mut j = 0;
+                while j < 8 {
+                    acc[8 + j] = hashes[i].0[j];
+                    j += 1;
+                }
+                let folded = Self::const_hash(&acc);
+                j = 0;
+                while j < 8 {
+                    acc[j] = folded.0[j];
+                    j += 1;
+                }
+                i += 1;
+            }
+            Self::const_hash(&acc)
+        }
+        fn const_from(value: u8) -> Self::Output {
+            let mut arr = [0u8; 8];
+            arr[0] = value;
+            Wrapped8(arr)
+        }
+        fn const_default() -> Self::Output {
+            Wrapped8([0u8; 8])
+        }
+    }
+
+    const ALLOWED: &[&[u8]; 5] = &[b"alice", b"bob", b"carol", b"dave", b"eve"];
+
+    static ALLOWLIST: StaticTree<4, 3, Fnv64Hash, 10> = StaticTree::from_const(ALLOWED);
+    const ROOT: Wrapped8 = StaticBinaryTree::<5, Fnv64Hash, 10>::from_const(ALLOWED).const_root();
+
+    #[test]
+    fn build_at_compile_time() {
+        let mt = StaticTree::<4, 3, Fnv64Hash, 10>::try_from(ALLOWED).unwrap();
+        assert_eq!(ALLOWLIST.root(), mt.root());
+        assert_eq!(ALLOWLIST.nodes(), mt.nodes());
+        assert!(ALLOWLIST.generate_proof(3).validate(b"dave"));
+
+        let mt = StaticBinaryTree::<5, Fnv64Hash, 10>::try_from(ALLOWED).unwrap();
+        assert_eq!(ROOT, mt.root());
+
+        // inputs occupying the whole input buffer are not prefixed
+        const FULL: StaticTree<2, 3, Fnv64Hash, 5> =
+            StaticTree::from_const(&[b"alice", b"bob", b"carol"]);
+        let mt = StaticTree::<2, 3, Fnv64Hash, 5>::try_from::<&[u8]>(&[b"alice", b"bob", b"carol"])
+            .unwrap();
+        assert_eq!(FULL.nodes(), mt.nodes());
+
+        // the whole tree
+        const EIGHT: &[&[u8]; 8] = &[b"a", b"b", b"c", b"d", b"e", b"f", b"g", b"h"];
+        const FILLED: StaticBinaryTree<3, Fnv64Hash, 10> = StaticTree::from_const(EIGHT);
+        assert_eq!(
+            FILLED.root(),
+            StaticBinaryTree::<3, Fnv64Hash, 10>::try_from(EIGHT).unwrap().root()
+        );
+    }
+}
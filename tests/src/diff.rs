@@ -0,0 +1,109 @@
+#[cfg(test)]
+mod diff_tests {
+    use merkle_heapless::diff::NodeSource;
+    use merkle_heapless::sum_tree::SumTree;
+    use merkle_heapless::traits::{StaticTreeTrait};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::{StdHash, Wrapped8};
+
+    const ARITY: usize = 4;
+    const HEIGHT: usize = 4;
+    type Tree = StaticTree<ARITY, HEIGHT, StdHash, 100>;
+
+    // peer answering node requests of a replica, counts the requests
+    struct Peer<'a> {
+        tree: &'a Tree,
+        requests: usize,
+        max_requests: usize,
+    }
+
+    impl<'a> NodeSource<ARITY, StdHash> for Peer<'a> {
+        type Error = &'static str;
+
+        fn fetch(&mut self, layer: usize, index: usize) -> Result<[Wrapped8; ARITY], Self::Error> {
+            if self.requests == self.max_requests {
+                return Err("peer is gone");
+            }
+            self.requests += 1;
+            self.tree
+                .node(layer, index)
+                .map(|node| *node.hashes())
+                .ok_or("no such node")
+        }
+    }
+
+    fn words() -> Vec<Vec<u8>> {
+        (0u8..50).map(|i| format!("word{i}").into_bytes()).collect()
+    }
+
+    #[test]
+    fn diff_local_replicas() {
+        let words = words();
+        let mt1 = Tree::try_from(&words).unwrap();
+        let mut mt2 = mt1;
+        assert_eq!(mt1.diff(&mt2).count(), 0);
+
+        for i in [3, 4, 17, 49, 60] {
+            mt2.replace(i, b"changed");
+        }
+        assert_eq!(mt1.diff(&mt2).collect::<Vec<_>>(), vec![3, 4, 17, 49, 60]);
+        assert_eq!(mt2.diff(&mt1).collect::<Vec<_>>(), vec![3, 4, 17, 49, 60]);
+
+        // the same as comparing leaves wholesale
+        let brute = (0..ARITY * mt1.base_layer_size())
+            .filter(|i| {
+                mt1.leaves()[i / ARITY].hashes()[i % ARITY]
+                    != mt2.leaves()[i / ARITY].hashes()[i % ARITY]
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(mt1.diff(&mt2).collect::<Vec<_>>(), brute);
+    }
+
+    #[test]
+    fn diff_remote_replica() {
+        let words = words();
+        let mt1 = Tree::try_from(&words).unwrap();
+        let mut mt2 = mt1;
+
+        let mut peer = Peer { tree: &mt2, requests: 0, max_requests: usize::MAX };
+        assert_eq!(mt1.diff_remote(&mut peer).count(), 0);
+        // only the root node is fetched
+        assert_eq!(peer.requests, 1);
+
+        mt2.replace(5, b"changed");
+        mt2.replace(40, b"changed");
+        let mut peer = Peer { tree: &mt2, requests: 0, max_requests: usize::MAX };
+        let diff = mt1.diff_remote(&mut peer).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(diff, mt1.diff(&mt2).collect::<Vec<_>>());
+        assert_eq!(diff, vec![5, 40]);
+        // the root, the common mismatching node below it
+        // and two mismatching nodes on each of the paths down to the leaves
+        assert_eq!(peer.requests, 6);
+    }
+
+    #[test]
+    fn stop_remote_diff_on_fetch_error() {
+        let words = words();
+        let mt1 = Tree::try_from(&words).unwrap();
+        let mut mt2 = mt1;
+        mt2.replace(5, b"changed");
+        mt2.replace(40, b"changed");
+
+        let mut peer = Peer { tree: &mt2, requests: 0, max_requests: HEIGHT };
+        let mut diff = mt1.diff_remote(&mut peer);
+        assert_eq!(diff.next(), Some(Ok(5)));
+        assert_eq!(diff.next(), Some(Err("peer is gone")));
+        assert_eq!(diff.next(), None);
+    }
+
+    #[test]
+    fn diff_aggregates() {
+        let accounts: &[(&[u8], u64)] = &[(b"alice", 100), (b"bob", 20), (b"carol", 3)];
+        let st1 = SumTree::<2, 3, StdHash, 100>::try_from(accounts).unwrap();
+        let mut st2 = st1;
+        st2.replace(1, b"bob", 21).unwrap();
+
+        assert_ne!(st1.root(), st2.root());
+        assert_eq!(st1.tree().diff(st2.tree()).collect::<Vec<_>>(), vec![1]);
+    }
+}
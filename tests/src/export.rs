@@ -0,0 +1,91 @@
+#[cfg(test)]
+mod export_tests {
+    use merkle_heapless::export;
+    use merkle_heapless::mmr_macro;
+    use merkle_heapless::traits::{StaticTreeTrait};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    fn marked(s: &str, mark: &str) -> Vec<String> {
+        s.lines()
+            .filter(|line| line.ends_with(mark))
+            .map(|line| line.trim_start_matches(|c| "│├└─ ".contains(c)))
+            .map(|line| line.split(' ').next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn render_ascii() {
+        let mt =
+            StaticTree::<2, 3, StdHash, 10>::try_from::<&[u8]>(&[b"apple", b"banana", b"kiwi"])
+                .unwrap();
+
+        let ascii = export::ascii(&mt, None);
+        let lines = ascii.lines().collect::<Vec<_>>();
+        // the empty leaf and the empty subtree are omitted
+        assert_eq!(lines.len(), 7);
+        assert!(lines[0].starts_with("3:0 "));
+        assert!(lines[1].starts_with("└── 2:0 "));
+        assert!(lines[2].starts_with("    ├── 1:0 "));
+        assert!(lines[3].starts_with("    │   ├── 0:0 "));
+        assert!(lines[6].starts_with("        └── 0:2 "));
+
+        let ascii = export::ascii(&mt, Some(2));
+        // the empty sibling of the leaf is a proof item
+        assert_eq!(ascii.lines().count(), 9);
+        assert_eq!(marked(&ascii, " *"), vec!["3:0", "2:0", "1:1", "0:2"]);
+        assert_eq!(marked(&ascii, " +"), vec!["1:0", "0:3", "2:1"]);
+    }
+
+    #[test]
+    fn render_dot() {
+        let mt = StaticTree::<4, 2, StdHash, 10>::try_from::<&[u8]>(&[
+            b"apple", b"banana", b"kiwi", b"lemon", b"mango",
+        ])
+        .unwrap();
+
+        let dot = export::dot(&mt, Some(4));
+        assert!(dot.starts_with("digraph {"));
+        assert!(dot.ends_with("}\n"));
+        // the empty siblings on the proof path are shown as the proof items
+        assert_eq!(dot.matches("[label=").count(), 1 + 4 + 8);
+        assert_eq!(dot.matches(" -> ").count(), 4 + 8);
+        assert_eq!(dot.matches("fillcolor=lightcoral").count(), 3);
+        assert_eq!(dot.matches("fillcolor=lightblue").count(), 3 + 3);
+        assert_eq!(dot.matches("color=red").count(), 2);
+        assert!(dot.contains("\"n2_0\" -> \"n1_1\" [color=red penwidth=2];"));
+    }
+
+    #[test]
+    fn render_mountain_range() {
+        mmr_macro::mmr!(
+            Type = FooMMR,
+            BranchFactor = 2,
+            Peaks = 3,
+            Hash = StdHash,
+            MaxInputWordLength = 10
+        );
+        let mut mmr = FooMMR::default();
+        for word in [b"apple", b"lemon", b"mango", b"peach", b"grape"] {
+            mmr.try_append(word).unwrap();
+        }
+        // peak leaf numbers: [4, 1, 0]
+        let ascii = export::mountain_range_ascii(mmr.peaks(), mmr.summit(), Some(4));
+        assert!(ascii.starts_with("summit\n"));
+        assert!(ascii.contains("\npeak 0\n"));
+        assert!(ascii.contains("\npeak 1\n"));
+        assert!(!ascii.contains("peak 2"));
+
+        let peak1 = ascii.split("peak 1\n").nth(1).unwrap();
+        assert_eq!(
+            marked(peak1, " *"),
+            vec![format!("{}:0", mmr.peaks()[1].height()), "0:0".to_string()]
+        );
+
+        let dot = export::mountain_range_dot(mmr.peaks(), mmr.summit(), Some(4));
+        assert_eq!(dot.matches("subgraph cluster_").count(), 3);
+        assert!(dot.contains("\"s0_0\" -> \"p0_2_0\" [style=dashed];"));
+        assert!(dot.contains("\"s0_1\" -> \"p1_1_0\" [style=dashed];"));
+        assert!(dot.contains("\"p1_1_0\" -> \"p1_0_0\" [color=red penwidth=2];"));
+    }
+}
@@ -0,0 +1,38 @@
+//! Hashes shared by the tests and the benches
+
+/// declares a hash of [std::collections::hash_map::DefaultHasher]
+/// whose output is a byte array of given length (at least 8) wrapped into a struct
+macro_rules! std_hash {
+    ($hash:ident, $output:ident, $len:expr) => {
+        #[derive(Debug)]
+        pub struct $hash;
+        #[derive(Hash, Clone, Copy, Default, PartialEq, Debug)]
+        #[repr(transparent)]
+        pub struct $output(pub [u8; $len]);
+        // a byte array
+        unsafe impl merkle_heapless::traits::PlainBytes for $output {}
+        impl From<u8> for $output {
+            fn from(n: u8) -> Self {
+                let mut arr = [0u8; $len];
+                arr[0] = n;
+                Self(arr)
+            }
+        }
+
+        impl merkle_heapless::traits::HashT for $hash {
+            type Output = $output;
+
+            fn hash(input: &[u8]) -> Self::Output {
+                use std::hash::{Hash, Hasher};
+
+                let mut s = std::collections::hash_map::DefaultHasher::new();
+                input.hash(&mut s);
+                let mut arr = [0u8; $len];
+                arr[..8].copy_from_slice(&s.finish().to_ne_bytes());
+                $output(arr)
+            }
+        }
+    };
+}
+
+std_hash!(StdHash, Wrapped8, 8);
@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod iter_tests {
+    use merkle_heapless::compactable::DefaultCompactable;
+    use merkle_heapless::iter::{MountainPart, Order};
+    use merkle_heapless::mmr_macro;
+    use merkle_heapless::prefixed::Prefixed;
+    use merkle_heapless::resizable::ResizableTree;
+    use merkle_heapless::sorted::SortedTree;
+    use merkle_heapless::traits::{CanRemove, ProofBuilder, StaticTreeExt, StaticTreeTrait};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    const ARITY: usize = 4;
+    const HEIGHT: usize = 3;
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    // checks the layers and the walks of a tree against each other
+    fn check_nodes<T: StaticTreeExt<ARITY, StdHash, PB>, PB: ProofBuilder<ARITY, StdHash>>(
+        tree: &T,
+    ) {
+        let layers = tree.layers().collect::<Vec<_>>();
+        assert_eq!(layers.len(), tree.height());
+        assert_eq!(layers[0], tree.leaves());
+        assert_eq!(layers.last().unwrap().len(), 1);
+        assert_eq!(layers.last().unwrap()[0].hash_all(), tree.root());
+        // every hash of a node is the hash of the node below, the empty nodes have the default hash
+        let default_hash = Prefixed::<ARITY, StdHash>::default_hash();
+        for (lower, upper) in layers.iter().zip(layers.iter().skip(1)) {
+            let hashes = upper.iter().flat_map(|node| node.hashes().iter().copied());
+            assert!(hashes.eq(lower.iter().map(|node| {
+                if node.hashes().iter().all(|h| h == &default_hash) {
+                    default_hash
+                } else {
+                    node.hash_all()
+                }
+            })));
+        }
+
+        let top_down = tree.walk(Order::TopDown).collect::<Vec<_>>();
+        assert_eq!(top_down[0], (tree.height(), 0, tree.root()));
+        assert_eq!(
+            top_down.len(),
+            (0..=tree.height())
+                .map(|l| ARITY.pow(l as u32))
+                .sum::<usize>()
+        );
+        let mut bottom_up = tree.walk(Order::BottomUp).collect::<Vec<_>>();
+        assert_eq!(bottom_up[0], (0, 0, tree.leaf_hashes().next().unwrap().1));
+        assert_eq!(bottom_up.last(), top_down.first());
+
+        // the same nodes layer-by-layer in the opposite order
+        bottom_up.sort_by_key(|&(layer, index, _)| (usize::MAX - layer, index));
+        assert_eq!(bottom_up, top_down);
+
+        let mut visited = Vec::new();
+        tree.visit(Order::TopDown, &mut |layer, index, hash| {
+            visited.push((layer, index, hash))
+        });
+        assert_eq!(visited, top_down);
+    }
+
+    #[test]
+    fn iterate_leaves_and_nodes() {
+        let mt = StaticTree::<ARITY, HEIGHT, StdHash, 100>::try_from(WORDS).unwrap();
+
+        let leaves = mt.leaf_hashes().collect::<Vec<_>>();
+        assert_eq!(leaves.len(), mt.base_layer_size() * ARITY);
+        // the hashed leaves rebuild the tree
+        let mut rebuilt = StaticTree::<ARITY, HEIGHT, StdHash, 100>::default();
+        for &(index, leaf) in leaves.iter().take(WORDS.len()) {
+            rebuilt.replace_leaf(index, leaf);
+        }
+        assert_eq!(rebuilt.root(), mt.root());
+        assert!(leaves[WORDS.len()..]
+            .iter()
+            .all(|(_, leaf)| leaf == &Prefixed::<ARITY, StdHash>::default_hash()));
+
+        check_nodes(&mt);
+    }
+
+    #[test]
+    fn iterate_all_tree_kinds() {
+        let mut cmt = DefaultCompactable::<ARITY, HEIGHT, StdHash, 100>::try_from(WORDS).unwrap();
+        cmt.remove(1);
+        check_nodes(&cmt);
+
+        let rt = ResizableTree::<ARITY, 4, StdHash, 100>::try_from(WORDS).unwrap();
+        assert_eq!(rt.layers().len(), 2);
+        check_nodes(&rt);
+
+        let st = SortedTree::<ARITY, HEIGHT, StdHash, 100>::try_from(WORDS).unwrap();
+        assert_eq!(st.tree().leaf_hashes().len(), 64);
+        check_nodes(st.tree());
+    }
+
+    #[test]
+    fn iterate_mountain_range() {
+        mmr_macro::mmr!(
+            Type = FooMMR,
+            BranchFactor = 2,
+            Peaks = 3,
+            Hash = StdHash,
+            MaxInputWordLength = 100
+        );
+        let mut mmr = FooMMR::default();
+        for word in WORDS {
+            mmr.try_append(word).unwrap();
+        }
+        // peak leaf numbers: [4, 1, 0]
+        let leaves = mmr.leaf_hashes().collect::<Vec<_>>();
+        assert_eq!(leaves.len(), WORDS.len());
+        assert_eq!(leaves[3].1, mmr.peaks()[0].leaf_hashes().nth(3).unwrap().1);
+        assert_eq!(
+            leaves[4],
+            (4, mmr.peaks()[1].leaf_hashes().next().unwrap().1)
+        );
+
+        let mut visited = Vec::new();
+        mmr.visit(Order::TopDown, &mut |part, layer, index, hash| {
+            visited.push((part, layer, index, hash))
+        });
+        let summit = mmr.summit();
+        assert_eq!(
+            visited[0],
+            (MountainPart::Summit, summit.height(), 0, summit.root())
+        );
+
+        let peak_nodes = |i: usize| {
+            visited
+                .iter()
+                .filter(|(part, ..)| part == &MountainPart::Peak(i))
+                .count()
+        };
+        assert_eq!(peak_nodes(0), mmr.peaks()[0].walk(Order::TopDown).count());
+        assert_eq!(peak_nodes(1), mmr.peaks()[1].walk(Order::TopDown).count());
+        assert_eq!(peak_nodes(2), 0);
+
+        let mut visited_bottom_up = Vec::new();
+        mmr.visit(Order::BottomUp, &mut |part, layer, index, hash| {
+            visited_bottom_up.push((part, layer, index, hash))
+        });
+        assert_eq!(visited_bottom_up.len(), visited.len());
+        assert_eq!(visited_bottom_up.last(), visited.first());
+    }
+}
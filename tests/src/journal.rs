@@ -0,0 +1,149 @@
+#[cfg(test)]
+mod journal_tests {
+    use merkle_heapless::augmentable::DefaultAugmentable;
+    use merkle_heapless::compactable::DefaultCompactable;
+    use merkle_heapless::journal::JournaledTree;
+    use merkle_heapless::traits::{AppendOnly, CanRemove, ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry"];
+
+    #[test]
+    fn rollback_to_checkpoints() {
+        let mt = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut journaled = JournaledTree::<4, 64, _, _, _>::new(mt);
+
+        let first = journaled.checkpoint().unwrap();
+        journaled.try_replace(1, b"kiwi").unwrap();
+        journaled.try_replace(9, b"lemon").unwrap();
+        let modified = *journaled.tree();
+
+        let second = journaled.checkpoint().unwrap();
+        journaled.try_replace(1, b"mango").unwrap();
+        journaled.replace(63, b"peach");
+        assert_eq!(journaled.checkpoints(), 2);
+
+        journaled.rollback_to(second).unwrap();
+        assert_eq!(journaled.root(), modified.root());
+        assert_eq!(journaled.nodes(), modified.nodes());
+        assert!(journaled.generate_proof(9).validate(b"lemon"));
+        // the checkpoint is discarded
+        assert!(journaled.rollback_to(second).is_err());
+
+        journaled.rollback_to(first).unwrap();
+        assert_eq!(journaled.root(), mt.root());
+        assert_eq!(journaled.nodes(), mt.nodes());
+        assert_eq!(journaled.checkpoints(), 0);
+        assert_eq!(journaled.journal_len(), 0);
+    }
+
+    #[test]
+    fn commit_modifications() {
+        let mt = StaticTree::<2, 4, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut journaled = JournaledTree::<2, 64, _, _, _>::new(mt);
+
+        // nothing to roll back to, nothing is journaled
+        journaled.try_replace(5, b"kiwi").unwrap();
+        assert_eq!(journaled.journal_len(), 0);
+
+        let checkpoint = journaled.checkpoint().unwrap();
+        journaled.try_replace(6, b"lemon").unwrap();
+        assert_eq!(journaled.journal_len(), 1 + 1 + 4);
+        journaled.commit();
+        assert!(journaled.rollback_to(checkpoint).is_err());
+
+        let tree = journaled.into_inner();
+        assert!(tree.generate_proof(5).validate(b"kiwi"));
+        assert!(tree.generate_proof(6).validate(b"lemon"));
+    }
+
+    #[test]
+    fn fail_on_full_journal() {
+        let mt = StaticTree::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        // a checkpoint and a single modification of a tree of height 3
+        let mut journaled = JournaledTree::<2, 5, _, _, _>::new(mt);
+
+        let checkpoint = journaled.checkpoint().unwrap();
+        journaled.try_replace(0, b"kiwi").unwrap();
+        assert!(journaled.try_replace(1, b"lemon").is_err());
+        assert!(journaled.checkpoint().is_err());
+        assert!(journaled.generate_proof(1).validate(b"apricot"));
+
+        journaled.rollback_to(checkpoint).unwrap();
+        assert_eq!(journaled.root(), mt.root());
+    }
+
+    #[test]
+    fn rollback_appends_and_removals() {
+        let amt = DefaultAugmentable::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut journaled = JournaledTree::<2, 64, _, _, _>::new(amt);
+
+        let checkpoint = journaled.checkpoint().unwrap();
+        journaled.try_append(b"kiwi").unwrap();
+        journaled.try_append(b"lemon").unwrap();
+        assert_eq!(AppendOnly::num_of_leaves(&journaled), 6);
+        journaled.rollback_to(checkpoint).unwrap();
+        assert_eq!(AppendOnly::num_of_leaves(&journaled), 4);
+        assert_eq!(journaled.root(), amt.root());
+        journaled.try_append(b"mango").unwrap();
+        assert!(journaled.generate_proof(4).validate(b"mango"));
+
+        let cmt = DefaultCompactable::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut journaled = JournaledTree::<2, 64, _, _, _>::new(cmt.clone());
+
+        let checkpoint = journaled.checkpoint().unwrap();
+        journaled.try_remove(0).unwrap();
+        journaled.remove(2);
+        journaled.try_replace(6, b"kiwi").unwrap();
+        assert_eq!(CanRemove::num_of_leaves(&journaled), 3);
+        journaled.rollback_to(checkpoint).unwrap();
+        assert_eq!(CanRemove::num_of_leaves(&journaled), 4);
+        assert_eq!(journaled.root(), cmt.root());
+
+        let mut cmt = journaled.into_inner();
+        cmt.compact();
+        assert!(cmt.generate_proof(2).validate(b"banana"));
+    }
+
+    #[test]
+    fn rollback_merges_and_compactions() {
+        let amt = DefaultAugmentable::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let other =
+            DefaultAugmentable::<2, 2, StdHash, 10>::try_from::<&[u8]>(&[b"kiwi", b"lemon"])
+                .unwrap();
+        let mut journaled = JournaledTree::<2, 64, _, _, _>::new(amt);
+
+        let checkpoint = journaled.checkpoint().unwrap();
+        journaled.try_merge(other).unwrap();
+        assert_eq!(AppendOnly::num_of_leaves(&journaled), 6);
+        assert!(journaled.generate_proof(5).validate(b"lemon"));
+        // no room for another one
+        assert!(journaled.try_merge(amt).is_err());
+        journaled.rollback_to(checkpoint).unwrap();
+        assert_eq!(AppendOnly::num_of_leaves(&journaled), 4);
+        assert_eq!(journaled.nodes(), amt.nodes());
+        assert_eq!(journaled.root(), amt.root());
+
+        let cmt = DefaultCompactable::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut journaled = JournaledTree::<2, 64, _, _, _>::new(cmt.clone());
+        journaled.remove(1);
+        journaled.replace(6, b"kiwi");
+        let removed = journaled.tree().clone();
+
+        let checkpoint = journaled.checkpoint().unwrap();
+        journaled.try_compact().unwrap();
+        assert!(journaled.generate_proof(3).validate(b"kiwi"));
+        journaled.rollback_to(checkpoint).unwrap();
+        assert_eq!(CanRemove::num_of_leaves(&journaled), 4);
+        assert_eq!(journaled.nodes(), removed.nodes());
+        assert_eq!(journaled.root(), removed.root());
+        assert!(journaled.generate_proof(6).validate(b"kiwi"));
+
+        // a compaction not fitting into the journal leaves the tree as it was
+        let mut journaled = JournaledTree::<2, 4, _, _, _>::new(removed.clone());
+        journaled.checkpoint().unwrap();
+        assert!(journaled.try_compact().is_err());
+        assert_eq!(journaled.root(), removed.root());
+    }
+}
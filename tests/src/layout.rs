@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod layout_tests {
+    use merkle_heapless::layout::{Blocked, LaidOut, Layered, Layout};
+    use merkle_heapless::prefixed::Prefixed;
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::store::{NodeStore, StoredTree};
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::{num_of_prefixed, StaticTree};
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    fn assert_permutation<const ARITY: usize, L: Layout>(height: usize) {
+        let n = num_of_prefixed!(ARITY, height);
+        let layout = L::new::<ARITY>(height);
+        let mut positions = (0..n).map(|i| layout.position(i)).collect::<Vec<_>>();
+        positions.sort();
+        assert_eq!(positions, (0..n).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn blocked_positions() {
+        for height in 1..10 {
+            assert_permutation::<2, Blocked<1>>(height);
+            assert_permutation::<2, Blocked<3>>(height);
+            assert_permutation::<4, Blocked<2>>(height);
+            assert_permutation::<2, Layered>(height);
+        }
+        for height in 1..6 {
+            assert_permutation::<8, Blocked<4>>(height);
+        }
+        // a binary tree of 4 layers of nodes in bands of 2 layers:
+        // the top band of 3 nodes, then 4 subtrees of 3 nodes, the leaf nodes last in each
+        let layout = Blocked::<2>::new::<2>(4);
+        let positions = (0..15).map(|i| layout.position(i)).collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            [4, 5, 7, 8, 10, 11, 13, 14, 3, 6, 9, 12, 1, 2, 0]
+        );
+    }
+
+    #[test]
+    fn blocked_tree() {
+        let mut mt = StaticTree::<4, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        let store = LaidOut::new(
+            [Prefixed::<4, StdHash>::default(); 341],
+            Blocked::<2>::new::<4>(5),
+        );
+        let mut tree = StoredTree::<4, 5, StdHash, 10, _>::try_from(store, WORDS).unwrap();
+        assert_eq!(tree.root(), mt.root());
+
+        tree.try_replace(200, b"lime").unwrap();
+        mt.replace(200, b"lime");
+        assert_eq!(tree.root(), mt.root());
+        for i in 0..341 {
+            assert_eq!(tree.store().read(i).unwrap(), mt.nodes()[i]);
+        }
+        let proof = tree.generate_proof(200).unwrap();
+        assert_eq!(proof.root(), mt.root());
+        assert!(proof.validate(b"lime"));
+        assert!(tree.generate_proof(2).unwrap().validate(b"banana"));
+
+        let tree = StoredTree::<4, 5, StdHash, 10, _>::open(tree.into_store()).unwrap();
+        assert_eq!(tree.root(), mt.root());
+    }
+
+    #[test]
+    fn blocked_static_tree() {
+        type BlockedTree = StaticTree<4, 5, StdHash, 10, Proof<4, 5, StdHash, 10>, (), Blocked<2>>;
+
+        let mut mt = StaticTree::<4, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut tree = BlockedTree::try_from(WORDS).unwrap();
+        assert_eq!(tree.root(), mt.root());
+        for layer in 0..5 {
+            for i in 0..4usize.pow(4 - layer as u32) {
+                assert_eq!(tree.node(layer, i), mt.node(layer, i));
+            }
+        }
+
+        tree.replace(200, b"lime");
+        mt.replace(200, b"lime");
+        assert_eq!(tree.root(), mt.root());
+        let proof = tree.generate_proof(200);
+        assert_eq!(proof.root(), mt.root());
+        assert!(proof.validate(b"lime"));
+        assert!(tree.generate_proof(2).validate(b"banana"));
+
+        let updates: &[(usize, &[u8])] = &[(7, b"mango"), (300, b"nectarine"), (2, b"")];
+        tree.replace_many(updates);
+        mt.replace_many(updates);
+        assert_eq!(tree.root(), mt.root());
+        assert!(tree.generate_proof(300).validate(b"nectarine"));
+    }
+}
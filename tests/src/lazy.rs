@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod lazy_tests {
+    use merkle_heapless::lazy::LazyTree;
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::traits::{NodeAggregate, ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::{Error, StaticTree};
+    use crate::fixture::StdHash;
+
+    // a sum of small amounts, overflowing a byte
+    #[derive(Clone, Copy, Default, PartialEq, Debug)]
+    pub struct Amount(u8);
+    impl NodeAggregate for Amount {
+        type Encoded = [u8; 1];
+        fn combine(children: &[Self]) -> Option<Self> {
+            children
+                .iter()
+                .try_fold(0u8, |acc, c| acc.checked_add(c.0))
+                .map(Self)
+        }
+        fn encode(&self) -> Self::Encoded {
+            [self.0]
+        }
+    }
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    #[test]
+    fn flush_as_eager() {
+        let mut eager = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut lazy = LazyTree::new(eager);
+        assert!(!lazy.is_dirty());
+
+        let updates: &[(usize, &[u8])] = &[
+            (0, b"plum"),
+            (1, b"pear"),
+            (17, b"lime"),
+            (63, b"melon"),
+            (0, b"peach"),
+            (4, b""),
+        ];
+        for (i, word) in updates {
+            eager.replace(*i, word);
+            lazy.replace(*i, word);
+        }
+        assert!(lazy.is_dirty());
+        assert_eq!(lazy.root(), eager.root());
+        assert!(!lazy.is_dirty());
+        assert_eq!(lazy.tree().nodes(), eager.nodes());
+
+        let proof = lazy.generate_proof(17);
+        assert_eq!(proof.root(), eager.root());
+        assert!(proof.validate(b"lime"));
+
+        // emptied leaves become padding again
+        lazy.replace_leaf(17, Default::default());
+        lazy.replace_leaf(63, Default::default());
+        eager.replace_leaf(17, Default::default());
+        eager.replace_leaf(63, Default::default());
+        lazy.flush().unwrap();
+        assert_eq!(lazy.into_inner().nodes(), eager.nodes());
+    }
+
+    #[test]
+    fn flush_across_bitset_words() {
+        // 255 nodes, the dirty ones are spread over several words of the bitset
+        let mut eager = StaticTree::<2, 8, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut lazy = LazyTree::new(eager);
+
+        for i in [0, 63, 64, 65, 127, 128, 200, 255] {
+            let word = format!("w{i}");
+            eager.replace(i, word.as_bytes());
+            lazy.replace(i, word.as_bytes());
+        }
+        assert_eq!(lazy.root(), eager.root());
+        assert!(!lazy.is_dirty());
+        assert_eq!(lazy.tree().nodes(), eager.nodes());
+    }
+
+    #[test]
+    fn flush_aggregates_as_eager() {
+        type AmountTree = StaticTree<2, 5, StdHash, 10, Proof<2, 5, StdHash, 10, Amount>, Amount>;
+
+        let mut eager = AmountTree::try_from_aggregated::<&[u8], _>(&[
+            (b"alice", Amount(100)),
+            (b"bob", Amount(20)),
+        ])
+        .unwrap();
+        let mut lazy = LazyTree::new(eager);
+        for (i, (word, amount)) in [(&b"carol"[..], 3), (b"erin", 7), (b"dave", 50)]
+            .iter()
+            .enumerate()
+        {
+            eager
+                .try_replace_with(i * 7, word, Amount(*amount))
+                .unwrap();
+            lazy.replace_with(i * 7, word, Amount(*amount));
+        }
+        assert_eq!(lazy.root_aggregate(), Amount(20 + 3 + 7 + 50));
+        assert_eq!(lazy.tree().nodes(), eager.nodes());
+        assert_eq!(lazy.root(), eager.root());
+
+        // the overflow is detected on flush, the tree stays dirty
+        lazy.replace_with(15, b"frank", Amount(200));
+        assert!(matches!(lazy.flush(), Err(Error::Overflow)));
+        assert!(lazy.is_dirty());
+        lazy.replace_with(15, b"frank", Amount(2));
+        eager.try_replace_with(15, b"frank", Amount(2)).unwrap();
+        lazy.flush().unwrap();
+        assert_eq!(lazy.root(), eager.root());
+        assert_eq!(lazy.root_aggregate(), Amount(82));
+    }
+}
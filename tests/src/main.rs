@@ -1,7 +1,36 @@
 #![allow(incomplete_features)]
 #![feature(generic_const_exprs)]
+#![feature(const_trait_impl)]
+#![feature(effects)]
 
+mod aggregate;
+mod arity;
 mod basic;
+mod batched;
+mod chain;
+mod compressed;
+mod const_tree;
+mod diff;
+mod export;
+#[cfg(test)]
+mod fixture;
+mod iter;
+mod journal;
+mod layout;
+mod lazy;
+mod mixed;
 mod mmr;
+mod mss;
+mod parallel;
+mod position;
+mod range;
+mod resizable;
+mod salted;
+mod sorted;
+mod store;
+mod sum_tree;
+mod sync;
+mod unbalanced;
+mod view;
 
 fn main() {}
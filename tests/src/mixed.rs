@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod mixed_tests {
+    use merkle_heapless::mixed::MixedProof;
+    use merkle_heapless::proof::{Proof, ProofChain};
+    use merkle_heapless::sum_tree::Sum;
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait};
+    use merkle_heapless::{Error, StaticTree};
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    #[test]
+    fn generate_mixed_proof() {
+        let tree = StaticTree::<4, 3, StdHash, 10, MixedProof<8, 3, StdHash, 10>>::try_from(WORDS)
+            .unwrap();
+        let proof = tree.generate_proof(4);
+        assert_eq!(proof.height(), 3);
+        assert!(proof.path().iter().all(|item| item.arity() == 4));
+        assert_eq!(proof.claim_index(), 4);
+        assert!(proof.validate(b"kiwi"));
+        assert!(!tree.generate_proof(3).validate(b"kiwi"));
+    }
+
+    #[test]
+    fn chain_binary_into_octal() {
+        // a binary subtree's root is a leaf of an 8-ary tree, whose root is a leaf of a 4-ary summit tree
+        let binary = StaticTree::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut octal = StaticTree::<8, 2, StdHash, 10>::try_from(WORDS).unwrap();
+        octal.replace_leaf(9, binary.root());
+        let mut summit = StaticTree::<4, 2, StdHash, 10>::try_from(WORDS).unwrap();
+        summit.replace_leaf(7, octal.root());
+
+        let proof = ProofChain::<8, 8, StdHash, 10>::new(binary.generate_proof(2))
+            .and_then(|chain| chain.then(octal.generate_proof(9)))
+            .and_then(|chain| chain.then(summit.generate_proof(7)))
+            .unwrap()
+            .finish();
+        assert_eq!(proof.height(), 3 + 2 + 2);
+        assert_eq!(proof.root(), summit.root());
+        assert_eq!(
+            proof
+                .path()
+                .iter()
+                .map(|item| item.arity())
+                .collect::<Vec<_>>(),
+            [2, 2, 2, 8, 8, 4, 4]
+        );
+        assert_eq!(proof.claim_index(), 2 + 9 * 8 + 7 * 8 * 64);
+        assert!(proof.validate(b"banana"));
+
+        // the binary root is not at leaf 8 of the 8-ary tree
+        assert!(matches!(
+            ProofChain::<8, 8, StdHash, 10>::new(binary.generate_proof(2))
+                .and_then(|chain| chain.then(octal.generate_proof(8))),
+            Err(Error::Chain)
+        ));
+        // longer than the chain
+        assert!(matches!(
+            ProofChain::<8, 6, StdHash, 10>::new(binary.generate_proof(2))
+                .and_then(|chain| chain.then(octal.generate_proof(9)))
+                .and_then(|chain| chain.then(summit.generate_proof(7))),
+            Err(Error::Chain)
+        ));
+    }
+
+    #[test]
+    fn chain_aggregated_proofs() {
+        // a binary subtree of balances whose root and total are a leaf of a 4-ary tree of balances
+        type Aggregated<const ARITY: usize, const HEIGHT: usize> =
+            StaticTree<ARITY, HEIGHT, StdHash, 10, Proof<ARITY, HEIGHT, StdHash, 10, Sum>, Sum>;
+        let balances: &[(&[u8], u64)] = &[(b"alice", 10), (b"bob", 20), (b"carol", 12)];
+        let binary = Aggregated::<2, 3>::try_from_aggregated(balances).unwrap();
+        let mut summit = Aggregated::<4, 2>::try_from_aggregated(balances).unwrap();
+        summit
+            .try_replace_leaf_with(5, binary.root(), binary.root_aggregate())
+            .unwrap();
+        assert_eq!(summit.root_aggregate().value(), 84);
+
+        let chain = || {
+            ProofChain::<4, 8, StdHash, 10, Sum>::new(binary.generate_proof(1))
+                .and_then(|chain| chain.then(summit.generate_proof(5)))
+                .unwrap()
+        };
+        assert_eq!(chain().root_aggregate().value(), 84);
+        let proof = chain().finish();
+        assert_eq!(
+            proof
+                .path()
+                .iter()
+                .map(|item| item.arity())
+                .collect::<Vec<_>>(),
+            [2, 2, 2, 4, 4]
+        );
+        assert!(proof.validate_with(b"bob", 20));
+        assert!(!chain().finish().validate_with(b"bob", 21));
+
+        // the leaf's total differs from the subtree's total
+        let mut summit = Aggregated::<4, 2>::try_from_aggregated(balances).unwrap();
+        summit.try_replace_leaf_with(5, binary.root(), 41).unwrap();
+        assert!(matches!(
+            ProofChain::<4, 8, StdHash, 10, Sum>::new(binary.generate_proof(1))
+                .and_then(|chain| chain.then(summit.generate_proof(5))),
+            Err(Error::Chain)
+        ));
+    }
+}
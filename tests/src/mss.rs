@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod mss_tests {
+    use merkle_heapless::mss::{Signer, MAX_MESSAGE_LEN};
+
+    use crate::fixture::StdHash;
+
+    const SEED: &[u8] = b"device secret seed";
+
+    #[test]
+    fn sign_and_verify() {
+        let mut signer = Signer::<2, 3, StdHash, 8>::from_seed(SEED);
+        let public_key = signer.public_key();
+        assert_eq!(signer.remaining(), 8);
+
+        for i in 0..8 {
+            let message = format!("firmware v{i}");
+            let signature = signer.sign(message.as_bytes()).unwrap();
+            assert_eq!(signature.index(), i);
+            assert!(signature.verify(&public_key, message.as_bytes()));
+        }
+        assert_eq!(signer.next_index(), 8);
+        assert_eq!(signer.remaining(), 0);
+        assert!(signer.sign(b"firmware v8").is_err());
+    }
+
+    #[test]
+    fn reject_forgeries() {
+        let mut signer = Signer::<4, 2, StdHash, 8>::from_seed(SEED);
+        let public_key = signer.public_key();
+
+        let signature = signer.sign(b"firmware v1").unwrap();
+        assert!(!signature.verify(&public_key, b"firmware v2"));
+
+        let signature = signer.sign(b"firmware v1").unwrap();
+        let other = Signer::<4, 2, StdHash, 8>::from_seed(b"another seed");
+        assert_ne!(other.public_key(), public_key);
+        assert!(!signature.verify(&other.public_key(), b"firmware v1"));
+    }
+
+    #[test]
+    fn resume_from_persisted_index() {
+        let mut signer = Signer::<2, 3, StdHash, 8>::from_seed(SEED);
+        signer.sign(b"firmware v1").unwrap();
+
+        let mut resumed = Signer::<2, 3, StdHash, 8>::resume(SEED, signer.next_index());
+        assert_eq!(resumed.public_key(), signer.public_key());
+        assert_eq!(resumed.remaining(), 7);
+        let signature = resumed.sign(b"firmware v2").unwrap();
+        assert_eq!(signature.index(), 1);
+        assert!(signature.verify(&signer.public_key(), b"firmware v2"));
+    }
+
+    #[test]
+    fn sign_truncated_digests() {
+        // the leading half of the digest is signed, with 11 chains per one-time key
+        let mut signer = Signer::<2, 2, StdHash, 4>::from_seed(SEED);
+        let public_key = signer.public_key();
+
+        let signature = signer.sign(b"firmware v1").unwrap();
+        assert!(signature.verify(&public_key, b"firmware v1"));
+        let signature = signer.sign(b"firmware v1").unwrap();
+        assert!(!signature.verify(&public_key, b"firmware v2"));
+        // a checksum of three base-16 digits covers up to 136 digest bytes
+        assert_eq!(MAX_MESSAGE_LEN, 136);
+    }
+}
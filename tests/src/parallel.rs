@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod parallel_tests {
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::traits::{NodeAggregate, ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::{StaticBinaryTree, StaticTree};
+    use crate::fixture::StdHash;
+
+    // number of non-empty leaves in a subtree, overflowing a byte
+    #[derive(Clone, Copy, Default, PartialEq, Debug)]
+    pub struct Count(u8);
+    impl NodeAggregate for Count {
+        type Encoded = [u8; 1];
+        fn combine(children: &[Self]) -> Option<Self> {
+            children
+                .iter()
+                .try_fold(0u8, |acc, c| acc.checked_add(c.0))
+                .map(Self)
+        }
+        fn encode(&self) -> Self::Encoded {
+            [self.0]
+        }
+        fn from_input(input: &[u8]) -> Self {
+            Self(!input.is_empty() as u8)
+        }
+    }
+
+    fn words(n: usize) -> Vec<Vec<u8>> {
+        (0..n).map(|i| format!("word {i}").into_bytes()).collect()
+    }
+
+    #[test]
+    fn build_as_serial() {
+        for n in [0, 1, 3, 4, 5, 100, 255, 256] {
+            let input = words(n);
+            let serial = StaticTree::<4, 5, StdHash, 10>::try_from(&input).unwrap();
+            let parallel = StaticTree::<4, 5, StdHash, 10>::par_try_from(&input).unwrap();
+            assert_eq!(parallel.root(), serial.root());
+            assert_eq!(parallel.nodes(), serial.nodes());
+        }
+        // inputs occupying the whole input buffer are not prefixed
+        let input: &[&[u8]] = &[b"apple", b"kiwi", b"cherry"];
+        let serial = StaticTree::<2, 3, StdHash, 6>::try_from(input).unwrap();
+        let parallel = StaticTree::<2, 3, StdHash, 6>::par_try_from(input).unwrap();
+        assert_eq!(parallel.nodes(), serial.nodes());
+
+        let input = words(1 << 11);
+        let serial = StaticBinaryTree::<12, StdHash, 12>::try_from(&input).unwrap();
+        let parallel = StaticBinaryTree::<12, StdHash, 12>::par_try_from(&input).unwrap();
+        assert_eq!(parallel.root(), serial.root());
+        assert!(parallel.generate_proof(1500).validate(b"word 1500"));
+
+        assert!(StaticTree::<4, 2, StdHash, 10>::par_try_from(&words(17)).is_err());
+    }
+
+    #[test]
+    fn build_aggregated_as_serial() {
+        type CountTree = StaticTree<2, 8, StdHash, 10, Proof<2, 8, StdHash, 10, Count>, Count>;
+
+        let mut input = words(200);
+        input[7].clear();
+        let serial = CountTree::try_from(&input).unwrap();
+        let parallel = CountTree::par_try_from(&input).unwrap();
+        assert_eq!(parallel.root(), serial.root());
+        assert_eq!(parallel.nodes(), serial.nodes());
+        assert_eq!(parallel.root_aggregate(), Count(199));
+
+        assert!(CountTree::try_from(&words(256)).is_err());
+        assert!(CountTree::par_try_from(&words(256)).is_err());
+    }
+}
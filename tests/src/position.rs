@@ -0,0 +1,132 @@
+#[cfg(test)]
+mod position_tests {
+    use merkle_heapless::compactable::DefaultCompactable;
+    use merkle_heapless::indexed::IndexedTree;
+    use merkle_heapless::mmr_macro;
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::resizable::ResizableTree;
+    use merkle_heapless::traits::{CanRemove, ProofValidator, StaticTreeExt, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"apricot"];
+
+    #[test]
+    fn find_positions() {
+        let mut mt = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+
+        assert_eq!(mt.position(b"banana"), Some(2));
+        // the first of duplicates
+        assert_eq!(mt.position(b"apricot"), Some(1));
+        assert_eq!(mt.position(b"kiwi"), None);
+        assert_eq!(mt.position_of_hash(&mt.leaf_hash(b"cherry")), Some(3));
+
+        mt.replace(1, b"kiwi");
+        assert_eq!(mt.position(b"apricot"), Some(4));
+        let index = mt.position(b"kiwi").unwrap();
+        assert!(mt.generate_proof(index).validate(b"kiwi"));
+        // a word of the maximal length is hashed without the prefix
+        mt.replace(7, b"watermelon");
+        assert_eq!(mt.position(b"watermelon"), Some(7));
+    }
+
+    #[test]
+    fn find_positions_in_all_tree_kinds() {
+        let mut cmt = DefaultCompactable::<2, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        cmt.remove(0);
+        assert_eq!(cmt.position(b"apple"), None);
+        assert_eq!(cmt.position(b"cherry"), Some(3));
+
+        let rt = ResizableTree::<2, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        assert_eq!(rt.position(b"cherry"), Some(3));
+
+        mmr_macro::mmr!(
+            Type = FooMMR,
+            BranchFactor = 2,
+            Peaks = 3,
+            Hash = StdHash,
+            MaxInputWordLength = 10
+        );
+        let mut mmr = FooMMR::default();
+        for word in WORDS {
+            mmr.try_append(word).unwrap();
+        }
+        // peak leaf numbers: [4, 1, 0], the last word is found in the first peak
+        assert_eq!(mmr.position(b"apricot"), Some(1));
+        assert_eq!(mmr.peaks()[1].position(b"apricot"), Some(0));
+        mmr.try_append(b"kiwi").unwrap();
+        let index = mmr.position(b"kiwi").unwrap();
+        assert_eq!(index, 5);
+        assert!(mmr.generate_proof(index).validate(b"kiwi"));
+    }
+
+    type Compactable = DefaultCompactable<4, 3, StdHash, 10>;
+    type Indexed = IndexedTree<4, 64, StdHash, Proof<4, 3, StdHash, 10>, Compactable>;
+
+    #[test]
+    fn index_agrees_with_scan() {
+        let cmt = Compactable::try_from(WORDS).unwrap();
+        assert!(IndexedTree::<4, 63, _, _, _>::try_from_tree(cmt.clone()).is_err());
+
+        let mut indexed = Indexed::try_from_tree(cmt).unwrap();
+        let words = (0..20).map(|i| format!("w{}", i % 7)).collect::<Vec<_>>();
+
+        let check = |indexed: &Indexed| {
+            for word in words
+                .iter()
+                .map(|w| w.as_bytes())
+                .chain(WORDS.iter().copied())
+            {
+                assert_eq!(indexed.position(word), indexed.tree().position(word));
+            }
+            let empty = indexed.leaf_hash(b"");
+            assert_eq!(
+                indexed.position_of_hash(&empty),
+                indexed.tree().position_of_hash(&empty)
+            );
+        };
+        check(&indexed);
+
+        // fill every slot with duplicates, then churn
+        for index in 0..64 {
+            indexed.replace(index, words[index % words.len()].as_bytes());
+        }
+        check(&indexed);
+        for index in (0..64).step_by(3) {
+            indexed.remove(index);
+            check(&indexed);
+        }
+        for index in (0..64).rev().step_by(5) {
+            indexed.replace(index, b"apricot");
+            check(&indexed);
+        }
+        assert_eq!(indexed.position(b"apricot"), Some(3));
+        assert_eq!(indexed.root(), indexed.tree().root());
+        assert_eq!(indexed.num_of_leaves(), 64 - 22 + 5);
+    }
+
+    #[test]
+    fn index_survives_long_churn() {
+        let mut indexed = Indexed::try_from_tree(Compactable::try_from(WORDS).unwrap()).unwrap();
+
+        // the tombstones of a full index are reclaimed, the lookups of every round stay correct
+        for round in 0..40 {
+            for index in 0..64 {
+                indexed.replace(index, format!("{round}:{index}").as_bytes());
+            }
+            for index in (round % 4..64).step_by(4) {
+                indexed.remove(index);
+            }
+            for index in 0..64 {
+                let word = format!("{round}:{index}");
+                let expected = (index % 4 != round % 4).then_some(index);
+                assert_eq!(indexed.position(word.as_bytes()), expected);
+            }
+            assert_eq!(indexed.position(b"missing"), None);
+            assert_eq!(
+                indexed.position_of_hash(&indexed.leaf_hash(b"")),
+                Some(round % 4)
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod range_tests {
+    use merkle_heapless::augmentable::DefaultAugmentable;
+    use merkle_heapless::compactable::DefaultCompactable;
+    use merkle_heapless::mmr_macro;
+    use merkle_heapless::resizable::ResizableTree;
+    use merkle_heapless::traits::{StaticTreeTrait};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    #[test]
+    fn validate_all_spans() {
+        const HEIGHT: usize = 4;
+        let input = (0u8..21).map(|i| vec![i; i as usize + 1]).collect::<Vec<_>>();
+        let input = input.iter().map(|d| d.as_slice()).collect::<Vec<_>>();
+
+        let mt2 = StaticTree::<2, { HEIGHT + 1 }, StdHash, 100>::try_from(&input).unwrap();
+        let mt4 = StaticTree::<4, HEIGHT, StdHash, 100>::try_from(&input).unwrap();
+
+        for start in 0..input.len() {
+            for end in start + 1..=input.len() {
+                let proof = mt2.generate_range_proof(start..end);
+                assert_eq!(proof.root(), mt2.root());
+                assert_eq!(proof.range(), start..end);
+                assert!(proof.validate(&input[start..end]));
+
+                let proof = mt4.generate_range_proof(start..end);
+                assert!(proof.validate(&input[start..end]));
+            }
+        }
+    }
+
+    #[test]
+    fn fail_on_tampered_span() {
+        const ARITY: usize = 4;
+        const HEIGHT: usize = 3;
+        let words: &[&[u8]] = &[
+            b"apple", b"apricot", b"banana", b"cherry", b"kiwi", b"lemon", b"mango",
+        ];
+        let mt = StaticTree::<ARITY, HEIGHT, StdHash, 100>::try_from(words).unwrap();
+
+        assert!(mt.generate_range_proof(2..6).validate(&words[2..6]));
+        // a wrong word
+        assert!(!mt
+            .generate_range_proof(2..6)
+            .validate::<&[u8]>(&[b"banana", b"cherry", b"kiwi", b"melon"]));
+        // a missing word
+        assert!(!mt.generate_range_proof(2..6).validate(&words[2..5]));
+        // an extra word
+        assert!(!mt.generate_range_proof(2..6).validate(&words[2..7]));
+        // swapped words
+        assert!(!mt
+            .generate_range_proof(2..6)
+            .validate::<&[u8]>(&[b"banana", b"kiwi", b"cherry", b"lemon"]));
+        // shifted span
+        assert!(!mt.generate_range_proof(3..7).validate(&words[2..6]));
+        // span past the present leaves is padding
+        assert!(!mt.generate_range_proof(5..9).validate::<&[u8]>(&[b"lemon", b"mango", b"", b""]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fail_on_empty_range() {
+        let mt = StaticTree::<2, 3, StdHash, 100>::try_from::<&[u8]>(&[b"apple", b"kiwi"]).unwrap();
+        mt.generate_range_proof(1..1);
+    }
+
+    #[test]
+    fn range_proofs_of_all_tree_kinds() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 3;
+        let words: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+        let amt = DefaultAugmentable::<ARITY, HEIGHT, StdHash, 100>::try_from(words).unwrap();
+        assert!(amt.generate_range_proof(1..5).validate(&words[1..5]));
+        let amt = amt.augment();
+        assert!(amt.generate_range_proof(0..4).validate(&words[0..4]));
+
+        let cmt = DefaultCompactable::<ARITY, HEIGHT, StdHash, 100>::try_from(words).unwrap();
+        assert!(cmt.generate_range_proof(2..4).validate(&words[2..4]));
+
+        let mut rt = ResizableTree::<ARITY, 4, StdHash, 100>::try_from(words).unwrap();
+        let proof = rt.generate_range_proof(0..5);
+        assert_eq!(proof.height(), 3);
+        assert!(proof.validate(words));
+        rt.try_grow().unwrap();
+        assert!(rt.generate_range_proof(0..5).validate(words));
+
+        mmr_macro::mmr!(
+            Type = FooMMR,
+            BranchFactor = 2,
+            Peaks = 3,
+            Hash = StdHash,
+            MaxInputWordLength = 100
+        );
+        let mut mmr = FooMMR::default();
+        for w in words {
+            mmr.try_append(w).unwrap();
+        }
+        // the first peak holds the first four words
+        let peak = &mmr.peaks()[0];
+        let proof = peak.generate_range_proof(1..4);
+        assert_eq!(proof.root(), peak.root());
+        assert!(proof.validate(&words[1..4]));
+    }
+}
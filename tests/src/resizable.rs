@@ -0,0 +1,113 @@
+#[cfg(test)]
+mod resizable_tests {
+    use merkle_heapless::resizable::ResizableTree;
+    use merkle_heapless::traits::{AppendOnly, CanRemove, ProofValidator, StaticTreeTrait};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    #[test]
+    fn smallest_height_on_creation() {
+        const ARITY: usize = 4;
+        const MAX_HEIGHT: usize = 4;
+        let words: &[&str] = &["apple", "apricot", "banana", "cherry", "kiwi"];
+
+        let rt = ResizableTree::<ARITY, MAX_HEIGHT, StdHash, 100>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(rt.height(), 2);
+        assert_eq!(rt.capacity(), 16);
+
+        let mt = StaticTree::<ARITY, 2, StdHash, 100>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(rt.root(), mt.root());
+
+        for (i, w) in words.iter().enumerate() {
+            let proof = rt.generate_proof(i);
+            assert_eq!(proof.height(), 2);
+            assert!(proof.validate(w.as_bytes()));
+        }
+
+        let input = (0u8..65).map(|i| vec![i]).collect::<Vec<_>>();
+        assert!(ResizableTree::<ARITY, 3, StdHash, 100>::try_from::<&[u8]>(
+            &input.iter().map(|d| d.as_ref()).collect::<Vec<_>>(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn grow_in_place() {
+        const ARITY: usize = 2;
+        const MAX_HEIGHT: usize = 4;
+        let words: &[&str] = &["apple", "apricot", "banana", "cherry", "kiwi", "kotleta"];
+
+        let mut rt = ResizableTree::<ARITY, MAX_HEIGHT, StdHash, 100>::default();
+        assert_eq!(rt.height(), 1);
+
+        for w in words {
+            if rt.try_append(w.as_bytes()).is_err() {
+                rt.try_grow().unwrap();
+                rt.try_append(w.as_bytes()).unwrap();
+            }
+        }
+        assert_eq!(rt.height(), 3);
+        assert_eq!(rt.num_of_leaves(), words.len());
+
+        let mt = StaticTree::<ARITY, 3, StdHash, 100>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(rt.root(), mt.root());
+
+        rt.try_grow().unwrap();
+        assert!(rt.try_grow().is_err());
+
+        for (i, w) in words.iter().enumerate() {
+            let proof = rt.generate_proof(i);
+            assert_eq!(proof.height(), MAX_HEIGHT);
+            assert_eq!(proof.path().len(), MAX_HEIGHT);
+            assert!(proof.validate(w.as_bytes()));
+        }
+    }
+
+    #[test]
+    fn remove_and_shrink() {
+        const ARITY: usize = 2;
+        const MAX_HEIGHT: usize = 4;
+        let words: &[&str] = &["apple", "apricot", "banana", "cherry", "kiwi"];
+        let test_words: &[&str] = &["apricot", "cherry", "kiwi"];
+
+        let mut rt = ResizableTree::<ARITY, MAX_HEIGHT, StdHash, 100>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(rt.height(), 3);
+        assert!(rt.try_shrink().is_err());
+
+        rt.remove(0);
+        rt.remove(2);
+        assert_eq!(rt.num_of_leaves(), 3);
+        assert!(!rt.generate_proof(0).validate(b"apple"));
+
+        rt.try_shrink().unwrap();
+        assert_eq!(rt.height(), 2);
+        assert!(rt.try_shrink().is_err());
+
+        let mt = StaticTree::<ARITY, 2, StdHash, 100>::try_from::<&[u8]>(
+            &test_words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(rt.root(), mt.root());
+
+        for (i, w) in test_words.iter().enumerate() {
+            let proof = rt.generate_proof(i);
+            assert_eq!(proof.height(), 2);
+            assert!(proof.validate(w.as_bytes()));
+        }
+
+        rt.try_append(b"lemon").unwrap();
+        assert!(rt.generate_proof(3).validate(b"lemon"));
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod salted_tests {
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::salted::Disclosure;
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    const SALT_LEN: usize = 8;
+    const FIELDS: &[&[u8]] = &[b"name: Alice", b"born: 1990", b"country: NZ"];
+
+    fn salt(i: u8) -> [u8; SALT_LEN] {
+        [i.wrapping_mul(37).wrapping_add(11); SALT_LEN]
+    }
+
+    type Tree = StaticTree<4, 2, StdHash, 32>;
+
+    fn salted_tree() -> Tree {
+        let input = FIELDS
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (salt(i as u8), *field))
+            .collect::<Vec<_>>();
+        Tree::try_from_salted(&input).unwrap()
+    }
+
+    #[test]
+    fn disclose_single_leaf() {
+        let tree = salted_tree();
+        let root = tree.root();
+
+        let disclosure = tree.disclose(2, salt(2), FIELDS[2]).unwrap();
+        assert_eq!(disclosure.index(), 2);
+        assert_eq!(disclosure.value(), FIELDS[2]);
+        assert_eq!(disclosure.salt(), &salt(2));
+        assert!(disclosure.verify(&root));
+
+        // the value alone, unsalted, is not a leaf
+        assert!(!tree.generate_proof(2).validate(FIELDS[2]));
+        assert!(tree.generate_proof(2).validate_salted(&salt(2), FIELDS[2]));
+        assert_eq!(
+            Proof::<4, 2, StdHash, 32>::hash_as_salted_leaf(&salt(1), FIELDS[1]),
+            tree.leaf_hashes().nth(1).unwrap().1
+        );
+        // the prefix is kept in front of the salt
+        let mut concat = salt(1).to_vec();
+        concat.extend_from_slice(FIELDS[1]);
+        assert_eq!(
+            tree.leaf_hash(&concat),
+            tree.salted_leaf_hash(&salt(1), FIELDS[1])
+        );
+    }
+
+    #[test]
+    fn reject_forged_disclosures() {
+        let tree = salted_tree();
+        let root = tree.root();
+
+        // the holder refuses to disclose a mismatching salt or value
+        assert!(tree.disclose(1, salt(2), FIELDS[1]).is_err());
+        assert!(tree.disclose(1, salt(1), b"born: 2000").is_err());
+        assert!(tree.disclose(1, salt(1), &[0u8; 32 - SALT_LEN]).is_err());
+
+        let forged = Disclosure::new(tree.generate_proof(1), salt(1), b"born: 2000").unwrap();
+        assert!(!forged.verify(&root));
+        let forged = Disclosure::new(tree.generate_proof(1), salt(0), FIELDS[1]).unwrap();
+        assert!(!forged.verify(&root));
+        // a valid disclosure against another root
+        let disclosure = tree.disclose(1, salt(1), FIELDS[1]).unwrap();
+        assert!(!disclosure.verify(&salted_tree_replaced().root()));
+    }
+
+    fn salted_tree_replaced() -> Tree {
+        let mut tree = salted_tree();
+        tree.replace_salted(5, &salt(5), b"email: a@b.c");
+        tree
+    }
+
+    #[test]
+    fn replace_salted_leaves() {
+        let tree = salted_tree_replaced();
+        assert!(tree
+            .disclose(5, salt(5), b"email: a@b.c")
+            .unwrap()
+            .verify(&tree.root()));
+        // the other disclosures follow the new root
+        assert!(tree
+            .disclose(0, salt(0), FIELDS[0])
+            .unwrap()
+            .verify(&tree.root()));
+
+        // too long for the salt and the prefix
+        assert!(Tree::try_from_salted(&[(salt(0), &[0u8; 32 - SALT_LEN][..])]).is_err());
+        assert!(Tree::try_from_salted(&[(salt(0), &[0u8; 32 - SALT_LEN - 1][..])]).is_ok());
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod sorted_tests {
+    use merkle_heapless::sorted::SortedTree;
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait};
+    use merkle_heapless::{Error, StaticTree};
+    use crate::fixture::StdHash;
+
+    #[test]
+    fn insert_and_remove_keep_order() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 4;
+        let words: &[&str] = &["kiwi", "apple", "cherry", "banana"];
+        let sorted: &[&str] = &["apple", "apricot", "banana", "cherry", "kiwi"];
+
+        let mut tree = SortedTree::<ARITY, HEIGHT, StdHash, 10>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        tree.insert(b"apricot").unwrap();
+        assert!(matches!(tree.insert(b"kiwi"), Err(Error::Duplicate)));
+
+        assert_eq!(
+            tree.keys().iter().map(|k| k.as_slice()).collect::<Vec<_>>(),
+            sorted.iter().map(|w| w.as_bytes()).collect::<Vec<_>>()
+        );
+        let mt = StaticTree::<ARITY, HEIGHT, StdHash, 10>::try_from::<&[u8]>(
+            &sorted.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(tree.root(), mt.root());
+        for (i, w) in sorted.iter().enumerate() {
+            assert!(tree.generate_proof(i).validate(w.as_bytes()));
+        }
+
+        assert!(tree.remove(b"apricot"));
+        assert!(!tree.remove(b"apricot"));
+        assert_eq!(tree.search(b"banana"), Ok(1));
+        assert_eq!(tree.search(b"blueberry"), Err(2));
+        let rebuilt = SortedTree::<ARITY, HEIGHT, StdHash, 10>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+        assert_eq!(tree, rebuilt);
+    }
+
+    #[test]
+    fn exclusion_proofs() {
+        const ARITY: usize = 4;
+        const HEIGHT: usize = 3;
+        let words: &[&str] = &["apple", "banana", "cherry", "kiwi", "lemon"];
+
+        let tree = SortedTree::<ARITY, HEIGHT, StdHash, 10>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        for missing in ["aardvark", "blueberry", "date", "mango"] {
+            let proof = tree.generate_exclusion_proof(missing.as_bytes()).unwrap();
+            assert_eq!(proof.root(), tree.root());
+            assert!(proof.validate_exclusion(missing.as_bytes()));
+        }
+        assert!(tree.generate_exclusion_proof(b"cherry").is_none());
+
+        let proof = tree.generate_exclusion_proof(b"date").unwrap();
+        assert_eq!(
+            proof.bracketing_keys(),
+            (Some(b"cherry".as_slice()), Some(b"kiwi".as_slice()))
+        );
+        // the bracketing keys do not bracket another key
+        assert!(!proof.validate_exclusion(b"banana"));
+        // a proof for the first key alone does not exclude a key after it
+        let proof = tree.generate_range_proof(b"a", b"apple");
+        assert!(!proof.validate::<&[u8]>(b"a", b"c", &[b"apple"]));
+
+        let empty = SortedTree::<ARITY, HEIGHT, StdHash, 10>::default();
+        assert!(empty.generate_exclusion_proof(b"apple").unwrap().validate_exclusion(b"apple"));
+        // a proof from another tree validates on its own, only its root tells it apart
+        let forged = empty.generate_exclusion_proof(b"banana").unwrap();
+        assert_ne!(forged.root(), tree.root());
+        assert!(forged.validate_exclusion(b"banana"));
+        assert!(!tree.generate_exclusion_proof(b"date").unwrap().validate_exclusion(b"mango"));
+    }
+
+    #[test]
+    fn range_proofs() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 5;
+        let words: &[&str] = &[
+            "apple", "apricot", "banana", "blueberry", "cherry", "date", "fig", "grape", "kiwi",
+            "lemon", "mango",
+        ];
+
+        let tree = SortedTree::<ARITY, HEIGHT, StdHash, 10>::try_from::<&[u8]>(
+            &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        let proof = tree.generate_range_proof(b"b", b"g");
+        assert!(proof.validate::<&[u8]>(b"b", b"g", &[b"banana", b"blueberry", b"cherry", b"date", b"fig"]));
+        // omitting a key in the range fails
+        let proof = tree.generate_range_proof(b"b", b"g");
+        assert!(!proof.validate::<&[u8]>(b"b", b"g", &[b"banana", b"blueberry", b"date", b"fig"]));
+        // a tampered key fails
+        let proof = tree.generate_range_proof(b"b", b"g");
+        assert!(!proof.validate::<&[u8]>(b"b", b"g", &[b"banana", b"blueberry", b"cherry", b"dates", b"fig"]));
+
+        // ranges at the boundaries of the tree
+        let proof = tree.generate_range_proof(b"", b"apricot");
+        assert!(proof.validate::<&[u8]>(b"", b"apricot", &[b"apple", b"apricot"]));
+        let proof = tree.generate_range_proof(b"l", b"z");
+        assert!(proof.validate::<&[u8]>(b"l", b"z", &[b"lemon", b"mango"]));
+        let proof = tree.generate_range_proof(b"", b"z");
+        assert!(proof.validate(b"", b"z", &words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>()));
+
+        // an empty range
+        let proof = tree.generate_range_proof(b"h", b"j");
+        assert!(proof.validate::<&[u8]>(b"h", b"j", &[]));
+    }
+}
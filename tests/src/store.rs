@@ -0,0 +1,144 @@
+#[cfg(test)]
+mod store_tests {
+    use merkle_heapless::prefixed::Prefixed;
+    use merkle_heapless::store::{FileStore, NodeStore, StoredTree};
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::{Error, StaticTree};
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    // a file in the temporary directory removed on drop
+    struct TempPath(std::path::PathBuf);
+    impl TempPath {
+        fn new(name: &str) -> Self {
+            Self(
+                std::env::temp_dir()
+                    .join(format!("merkle-heapless-{}-{name}.bin", std::process::id())),
+            )
+        }
+    }
+    impl Drop for TempPath {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn in_memory_store() {
+        // 1 + 4 + 16 nodes
+        let store = [Prefixed::<4, StdHash>::default(); 21];
+        let mut tree = StoredTree::<4, 3, StdHash, 10, _>::try_from(store, WORDS).unwrap();
+        let mut mt = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        assert_eq!(tree.root(), mt.root());
+        assert_eq!(tree.store()[..], mt.nodes()[..]);
+
+        tree.try_replace(33, b"lemon").unwrap();
+        tree.try_replace(1, &[]).unwrap();
+        mt.replace(33, b"lemon");
+        mt.replace(1, &[]);
+        assert_eq!(tree.root(), mt.root());
+        assert_eq!(tree.leaf(33).unwrap(), mt.leaf_hash(b"lemon"));
+        assert!(tree.generate_proof(33).unwrap().validate(b"lemon"));
+
+        let reopened = StoredTree::<4, 3, StdHash, 10, _>::open(tree.into_store()).unwrap();
+        assert_eq!(reopened.root(), mt.root());
+    }
+
+    // an in-memory store failing the writes once the shared number of the writes left runs out
+    struct Failing {
+        nodes: [Prefixed<2, StdHash>; 15],
+        writes_left: Rc<Cell<usize>>,
+    }
+    impl NodeStore<2, StdHash> for Failing {
+        fn read(&self, index: usize) -> Result<Prefixed<2, StdHash>, Error> {
+            self.nodes.read(index)
+        }
+        fn write(&mut self, index: usize, node: &Prefixed<2, StdHash>) -> Result<(), Error> {
+            let left = self.writes_left.get().checked_sub(1).ok_or(Error::Store)?;
+            self.writes_left.set(left);
+            self.nodes.write(index, node)
+        }
+    }
+
+    #[test]
+    fn rebuild_partially_written_path() {
+        let writes_left = Rc::new(Cell::new(usize::MAX));
+        let store = Failing {
+            nodes: [Prefixed::default(); 15],
+            writes_left: writes_left.clone(),
+        };
+        let mut tree = StoredTree::<2, 4, StdHash, 10, _>::try_from(store, WORDS).unwrap();
+        let mut mt = StaticTree::<2, 4, StdHash, 10>::try_from(WORDS).unwrap();
+        let root = mt.root();
+
+        // the leaf and its parent are written, the rest of the path is not
+        writes_left.set(2);
+        assert!(tree.try_replace(6, b"lemon").is_err());
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.leaf(6).unwrap(), mt.leaf_hash(b"lemon"));
+
+        writes_left.set(usize::MAX);
+        tree.try_rebuild().unwrap();
+        mt.replace(6, b"lemon");
+        assert_eq!(tree.root(), mt.root());
+        assert!(tree.generate_proof(6).unwrap().validate(b"lemon"));
+    }
+
+    type Store = FileStore<2, StdHash, 256>;
+
+    #[test]
+    fn file_store() {
+        let path = TempPath::new("file_store");
+        // 24 bytes long nodes, 10 nodes in a page
+        assert_eq!(Store::nodes_per_page(), 10);
+        let store = Store::create(&path.0, (1 << 5) - 1).unwrap();
+        assert_eq!(std::fs::metadata(&path.0).unwrap().len(), 4 * 256);
+
+        let mut tree = StoredTree::<2, 5, StdHash, 10, _>::try_from(store, WORDS).unwrap();
+        let mut mt = StaticTree::<2, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        assert_eq!(tree.root(), mt.root());
+
+        for (i, word) in [b"lemon", b"mango", b"peach"].iter().enumerate() {
+            tree.try_replace(i * 13, &word[..]).unwrap();
+            mt.replace(i * 13, &word[..]);
+        }
+        assert_eq!(tree.root(), mt.root());
+        drop(tree);
+
+        let store = Store::open(&path.0, (1 << 5) - 1).unwrap();
+        for (i, node) in mt.nodes().iter().enumerate() {
+            assert_eq!(&store.read(i).unwrap(), node);
+        }
+        let tree = StoredTree::<2, 5, StdHash, 10, _>::open(store).unwrap();
+        assert_eq!(tree.root(), mt.root());
+        assert!(tree.generate_proof(26).unwrap().validate(b"peach"));
+        assert!(tree.generate_proof(2).unwrap().validate(b"banana"));
+    }
+
+    #[test]
+    fn fail_on_store_errors() {
+        let path = TempPath::new("fail_on_store_errors");
+        // a node does not fit into a page
+        assert!(FileStore::<2, StdHash, 16>::create(&path.0, 7).is_err());
+        assert!(Store::open(&path.0, 7).is_err());
+
+        // the store is too small for the tree
+        let store = Store::create(&path.0, 7).unwrap();
+        assert!(StoredTree::<2, 5, StdHash, 10, _>::try_from(store, WORDS).is_err());
+
+        let store = Store::create(&path.0, 7).unwrap();
+        assert!(StoredTree::<2, 2, StdHash, 10, _>::try_from(store, WORDS).is_err());
+
+        // the file is of another number of nodes
+        assert!(Store::open(&path.0, 7).is_ok());
+        assert!(matches!(Store::open(&path.0, 31), Err(Error::Store)));
+        // a node of another arity
+        let mut store = FileStore::<4, StdHash, 256>::open(&path.0, 3).unwrap();
+        assert!(matches!(store.read(0), Err(Error::Store)));
+        store.write(0, &Prefixed::default()).unwrap();
+        assert_eq!(store.read(0).unwrap(), Prefixed::default());
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod sum_tree_tests {
+    use merkle_heapless::sum_tree::SumTree;
+    use merkle_heapless::Error;
+    use crate::fixture::StdHash;
+
+    #[test]
+    fn validate_balances_and_total() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 4;
+        let accounts: &[(&[u8], u64)] = &[
+            (b"alice", 100),
+            (b"bob", 20),
+            (b"carol", 3),
+            (b"dave", 0),
+            (b"eve", 7000),
+        ];
+
+        let tree = SumTree::<ARITY, HEIGHT, StdHash, 100>::try_from(accounts).unwrap();
+        assert_eq!(tree.total(), 7123);
+
+        for (i, (name, balance)) in accounts.iter().enumerate() {
+            assert_eq!(tree.balance(i), *balance);
+            let proof = tree.generate_proof(i);
+            assert_eq!(proof.total(), 7123);
+            assert!(proof.validate(name, *balance));
+            let proof = tree.generate_proof(i);
+            assert!(!proof.validate(name, balance + 1));
+        }
+        assert!(!tree.generate_proof(1).validate(b"alice", 100));
+    }
+
+    #[test]
+    fn replace_and_remove_balances() {
+        const ARITY: usize = 4;
+        const HEIGHT: usize = 3;
+        let accounts: &[(&[u8], u64)] = &[(b"alice", 100), (b"bob", 20), (b"carol", 3)];
+
+        let mut tree = SumTree::<ARITY, HEIGHT, StdHash, 100>::try_from(accounts).unwrap();
+        let root = tree.root();
+
+        tree.replace(9, b"dave", 50).unwrap();
+        assert_eq!(tree.total(), 173);
+        assert!(tree.generate_proof(9).validate(b"dave", 50));
+        assert!(tree.generate_proof(0).validate(b"alice", 100));
+
+        tree.replace(1, b"bob", 25).unwrap();
+        assert_eq!(tree.total(), 178);
+        assert!(tree.generate_proof(1).validate(b"bob", 25));
+
+        tree.replace(1, b"bob", 20).unwrap();
+        tree.remove(9);
+        assert_eq!(tree.total(), 123);
+        assert_eq!(tree.root(), root);
+
+        let rebuilt = SumTree::<ARITY, HEIGHT, StdHash, 100>::try_from(accounts).unwrap();
+        assert_eq!(tree, rebuilt);
+    }
+
+    #[test]
+    fn fail_on_overflow() {
+        const ARITY: usize = 2;
+        const HEIGHT: usize = 3;
+        let accounts: &[(&[u8], u64)] = &[(b"alice", u64::MAX), (b"bob", 1)];
+        assert!(matches!(
+            SumTree::<ARITY, HEIGHT, StdHash, 100>::try_from(accounts),
+            Err(Error::Overflow)
+        ));
+
+        let accounts: &[(&[u8], u64)] = &[(b"alice", u64::MAX - 1), (b"bob", 1)];
+        let mut tree = SumTree::<ARITY, HEIGHT, StdHash, 100>::try_from(accounts).unwrap();
+        let root = tree.root();
+        assert!(tree.replace(5, b"carol", 1).is_err());
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.total(), u64::MAX);
+    }
+
+    #[test]
+    fn fail_creating_sum_tree_too_few_layers_for_input() {
+        let accounts: &[(&[u8], u64)] = &[(b"alice", 1), (b"bob", 2), (b"carol", 3)];
+        assert!(SumTree::<2, 1, StdHash, 100>::try_from(accounts).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+#[cfg(test)]
+mod sync_tests {
+    use std::collections::VecDeque;
+
+    use merkle_heapless::compactable::DefaultCompactable;
+    use merkle_heapless::sync::{Initiator, Message, Responder, SyncError, SyncReplica};
+    use merkle_heapless::traits::{CanRemove, StaticTreeTrait};
+    use merkle_heapless::StaticTree;
+    use crate::fixture::StdHash;
+
+    const ARITY: usize = 4;
+    const HEIGHT: usize = 4;
+    // CAN FD frame
+    const FRAME_LEN: usize = 64;
+    type Frame = [u8; FRAME_LEN];
+
+    // runs both sides over an in-memory channel until the initiator is done,
+    // returns the number of frames sent both ways
+    fn run<L, R, const REMOTE_HEIGHT: usize>(
+        local: &mut L,
+        remote: &R,
+    ) -> Result<(Initiator<ARITY, HEIGHT, StdHash>, usize), SyncError>
+    where
+        L: SyncReplica<ARITY, HEIGHT, StdHash>,
+        R: SyncReplica<ARITY, REMOTE_HEIGHT, StdHash>,
+    {
+        let mut initiator = Initiator::<ARITY, HEIGHT, StdHash>::new();
+        let mut responder = Responder::<ARITY, REMOTE_HEIGHT, StdHash>::new();
+        let mut to_remote = VecDeque::<Frame>::new();
+        let mut to_local = VecDeque::<Frame>::new();
+        let mut frames = 0;
+
+        let mut send = |queue: &mut VecDeque<Frame>, message: Message<ARITY, StdHash>| {
+            let mut frame = [0u8; FRAME_LEN];
+            message.encode(&mut frame).unwrap();
+            queue.push_back(frame);
+            frames += 1;
+        };
+
+        send(&mut to_remote, initiator.start());
+        while !initiator.is_done() {
+            while let Some(frame) = to_remote.pop_front() {
+                responder.handle(remote, Message::decode(&frame)?)?;
+                while let Some(message) = responder.poll(remote) {
+                    send(&mut to_local, message);
+                }
+            }
+            let frame = to_local.pop_front().ok_or(SyncError::Unexpected)?;
+            if let Some(message) = initiator.handle(local, Message::decode(&frame)?)? {
+                send(&mut to_remote, message);
+            }
+        }
+        // the last message is the initiator's done
+        let frame = to_remote.pop_front().unwrap();
+        responder.handle(remote, Message::decode(&frame)?)?;
+        assert!(responder.is_done());
+
+        Ok((initiator, frames))
+    }
+
+    #[test]
+    fn sync_static_trees() {
+        type Tree = StaticTree<ARITY, HEIGHT, StdHash, 100>;
+        let words: Vec<String> = (0..40).map(|i| format!("word {i}")).collect();
+        let mut local =
+            Tree::try_from(&words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>()).unwrap();
+        let mut remote = local;
+
+        remote.replace(3, b"apple");
+        remote.replace(4, b"banana");
+        remote.replace(37, b"cherry");
+        remote.replace(50, b"kiwi");
+        local.replace(63, b"plum");
+        assert_ne!(local.root(), remote.root());
+
+        let (initiator, frames) = run(&mut local, &remote).unwrap();
+        assert_eq!(local.root(), remote.root());
+        assert_eq!(initiator.adopted(), 5);
+        // far fewer than a request and a frame per child hash of every node
+        assert!(frames < (1 + ARITY) * (1 + 4 + 16));
+
+        // nothing to do on the second run
+        let (initiator, frames) = run(&mut local, &remote).unwrap();
+        assert_eq!(initiator.adopted(), 0);
+        // the root request, its child hashes and done
+        assert_eq!(frames, 1 + ARITY + 1);
+    }
+
+    #[test]
+    fn sync_compactable_trees() {
+        type Tree = DefaultCompactable<ARITY, HEIGHT, StdHash, 100>;
+        let mut local = Tree::try_from::<&[u8]>(&[
+            b"apple", b"apricot", b"banana", b"cherry", b"kiwi", b"lemon",
+        ])
+        .unwrap();
+        let mut remote = local.clone();
+
+        remote.remove(1);
+        remote.remove(4);
+        remote.replace(9, b"mango");
+        local.replace(12, b"plum");
+
+        run(&mut local, &remote).unwrap();
+        assert_eq!(local.root(), remote.root());
+        assert_eq!(local.num_of_leaves(), remote.num_of_leaves());
+        assert_eq!(local.num_of_leaves(), 5);
+
+        local.compact();
+        remote.compact();
+        assert_eq!(local.root(), remote.root());
+    }
+
+    #[test]
+    fn reject_mismatching_shape() {
+        let mut local =
+            StaticTree::<ARITY, HEIGHT, StdHash, 100>::try_from::<&[u8]>(&[b"apple"]).unwrap();
+        let remote =
+            StaticTree::<ARITY, { HEIGHT + 1 }, StdHash, 100>::try_from::<&[u8]>(&[b"banana"])
+                .unwrap();
+
+        assert_eq!(run(&mut local, &remote).err(), Some(SyncError::Rejected));
+    }
+
+    #[test]
+    fn respond_to_range_requests() {
+        let tree =
+            StaticTree::<ARITY, HEIGHT, StdHash, 100>::try_from::<&[u8]>(&[b"apple"]).unwrap();
+        let mut responder = Responder::<ARITY, HEIGHT, StdHash>::new();
+
+        responder
+            .handle(
+                &tree,
+                Message::Request {
+                    height: HEIGHT,
+                    layer: 1,
+                    index: 1,
+                    count: 3,
+                },
+            )
+            .unwrap();
+        // no other request until the responses are taken
+        assert_eq!(
+            responder.handle(&tree, Message::Done),
+            Err(SyncError::Unexpected)
+        );
+        for index in 1..4 {
+            for offset in 0..ARITY {
+                assert_eq!(
+                    responder.poll(&tree),
+                    Some(Message::Nodes {
+                        layer: 1,
+                        index,
+                        offset,
+                        hash: tree.node(1, index).unwrap().hashes()[offset]
+                    })
+                );
+            }
+        }
+        assert_eq!(responder.poll(&tree), None);
+
+        // out of the layer bound
+        responder
+            .handle(
+                &tree,
+                Message::Request {
+                    height: HEIGHT,
+                    layer: 1,
+                    index: 14,
+                    count: 3,
+                },
+            )
+            .unwrap();
+        assert_eq!(responder.poll(&tree), Some(Message::Reject));
+        assert_eq!(responder.poll(&tree), None);
+    }
+
+    #[test]
+    fn encode_fixed_size_frames() {
+        type M = Message<ARITY, StdHash>;
+        // independent of the arity
+        assert_eq!(M::ENCODED_LEN, 7 + 8);
+
+        let messages = [
+            M::Request {
+                height: HEIGHT,
+                layer: 2,
+                index: 70000,
+                count: 2,
+            },
+            M::Nodes {
+                layer: 0,
+                index: 3,
+                offset: 2,
+                hash: 3.into(),
+            },
+            M::Done,
+            M::Reject,
+        ];
+        for message in messages {
+            let mut frame = [0xffu8; M::ENCODED_LEN];
+            message.encode(&mut frame).unwrap();
+            assert_eq!(M::decode(&frame), Ok(message));
+        }
+
+        let mut short = [0u8; 8];
+        assert_eq!(M::Done.encode(&mut short), Err(SyncError::Encode));
+        assert_eq!(M::decode(&short), Err(SyncError::Decode));
+        assert_eq!(M::decode(&[7u8; M::ENCODED_LEN]), Err(SyncError::Decode));
+
+        let mut frame = [0u8; M::ENCODED_LEN];
+        let too_wide = M::Request {
+            height: HEIGHT,
+            layer: 0,
+            index: 0,
+            count: 1 << 16,
+        };
+        assert_eq!(too_wide.encode(&mut frame), Err(SyncError::Encode));
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod unbalanced_tests {
+    use merkle_heapless::proof::Proof;
+    use merkle_heapless::traits::{AppendOnly, HashT, ProofValidator};
+    use merkle_heapless::unbalanced::UnbalancedTree;
+    use merkle_heapless::Error;
+    use crate::fixture::{StdHash, Wrapped8};
+
+    fn words(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("word{i}")).collect()
+    }
+
+    fn leaf(word: &str) -> Wrapped8 {
+        Proof::<2, 5, StdHash, 10>::hash_as_leaf(word.as_bytes())
+    }
+
+    // RFC 6962 tree hash with the nodes hashed as the prefix followed by the hashes
+    fn mth(leaves: &[Wrapped8]) -> Wrapped8 {
+        if leaves.len() == 1 {
+            return leaves[0];
+        }
+        let k = 1 << (leaves.len() - 1).ilog2();
+        let mut bytes = vec![1u8, 0, 0, 0, 0, 0, 0, 0];
+        bytes.extend_from_slice(&mth(&leaves[..k]).0);
+        bytes.extend_from_slice(&mth(&leaves[k..]).0);
+        StdHash::hash(&bytes)
+    }
+
+    // length of the RFC 6962 audit path of a leaf
+    fn path_len(index: usize, n: usize) -> usize {
+        if n == 1 {
+            return 0;
+        }
+        let k = 1 << (n - 1).ilog2();
+        1 + if index < k {
+            path_len(index, k)
+        } else {
+            path_len(index - k, n - k)
+        }
+    }
+
+    #[test]
+    fn rfc6962_shape() {
+        for n in 1..=32 {
+            let words = words(n);
+            let input = words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>();
+            let tree = UnbalancedTree::<2, 5, StdHash, 10>::try_from(&input).unwrap();
+            let leaves = words.iter().map(|w| leaf(w)).collect::<Vec<_>>();
+            assert_eq!(tree.root(), mth(&leaves));
+
+            for (i, word) in input.iter().enumerate() {
+                let proof = tree.generate_proof(i);
+                assert_eq!(proof.height(), path_len(i, n));
+                assert_eq!((proof.num_of_leaves(), proof.claim_index()), (n, i));
+                assert!(proof.height() <= n.next_power_of_two().trailing_zeros() as usize);
+                assert!(proof.validate(word));
+            }
+        }
+        let empty = UnbalancedTree::<2, 5, StdHash, 10>::try_from::<&[u8]>(&[]).unwrap();
+        assert_eq!(empty.root(), Wrapped8::default());
+    }
+
+    #[test]
+    fn append_as_created() {
+        let words = words(50);
+        let input = words.iter().map(|w| w.as_bytes()).collect::<Vec<_>>();
+        let mut tree = UnbalancedTree::<4, 3, StdHash, 10>::try_from::<&[u8]>(&[]).unwrap();
+        for (n, word) in input.iter().enumerate() {
+            tree.try_append(word).unwrap();
+            let created = UnbalancedTree::<4, 3, StdHash, 10>::try_from(&input[..=n]).unwrap();
+            assert_eq!(tree.root(), created.root());
+            assert_eq!(tree.num_of_leaves(), n + 1);
+        }
+        // 50 leaves need 3 layers of a 4-ary tree,
+        // the node of the leaves past 48 is the only child of its parent
+        assert_eq!(tree.generate_proof(0).height(), 3);
+        assert_eq!(tree.generate_proof(49).height(), 2);
+        assert_eq!(tree.generate_proof(49).claim_index(), 49);
+        assert!(tree.generate_proof(49).validate(b"word49"));
+
+        tree.replace(17, b"lime");
+        assert!(tree.generate_proof(17).validate(b"lime"));
+        assert!(tree.generate_proof(16).validate(b"word16"));
+        assert_eq!(
+            tree.leaf(17),
+            Proof::<4, 3, StdHash, 10>::hash_as_leaf(b"lime")
+        );
+
+        for _ in 50..64 {
+            tree.try_append(b"fig").unwrap();
+        }
+        assert!(matches!(tree.try_append(b"fig"), Err(Error::Append)));
+    }
+}
@@ -0,0 +1,80 @@
+#[cfg(test)]
+mod view_tests {
+    use merkle_heapless::traits::{ProofValidator, StaticTreeTrait, TreeNodes};
+    use merkle_heapless::view::StaticTreeRef;
+    use merkle_heapless::{Error, StaticTree};
+    use crate::fixture::StdHash;
+
+    const WORDS: &[&[u8]] = &[b"apple", b"apricot", b"banana", b"cherry", b"kiwi"];
+
+    #[test]
+    fn view_serialized_tree() {
+        let tree = StaticTree::<4, 3, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut bytes = vec![0u8; tree.serialized_len()];
+        // header, root and 1 + 4 + 16 nodes of a prefix and 4 hashes
+        assert_eq!(bytes.len(), 12 + 8 + 21 * 5 * 8);
+        assert!(matches!(
+            tree.serialize(&mut bytes[..100]),
+            Err(Error::Create)
+        ));
+        tree.serialize(&mut bytes).unwrap();
+
+        let view = StaticTreeRef::<4, 3, StdHash, 10>::try_from_bytes(&bytes).unwrap();
+        assert_eq!(view.root(), tree.root());
+        assert_eq!(view.leaves(), tree.leaves());
+        assert_eq!(view.nodes(), tree.nodes());
+        assert_eq!(view.height(), 3);
+        assert_eq!(view.leaf_hash(b"kiwi"), tree.leaf_hash(b"kiwi"));
+        for (i, word) in WORDS.iter().enumerate() {
+            let proof = view.generate_proof(i);
+            assert_eq!(proof.root(), tree.root());
+            assert!(proof.validate(word));
+        }
+        assert!(!view.generate_proof(1).validate(b"apple"));
+    }
+
+    #[test]
+    fn fail_on_invalid_bytes() {
+        let tree = StaticTree::<2, 5, StdHash, 10>::try_from(WORDS).unwrap();
+        let mut bytes = vec![0u8; tree.serialized_len()];
+        tree.serialize(&mut bytes).unwrap();
+        assert!(StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&bytes).is_ok());
+
+        // length
+        assert!(
+            StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&bytes[..bytes.len() - 1]).is_err()
+        );
+        // arity and height of another tree of the same length: 1 + 2 + 4 + 8 + 16 nodes of 3 hashes
+        // against 1 + 4 + 16 nodes of 5 hashes (a few bytes short)
+        assert!(StaticTreeRef::<4, 3, StdHash, 10>::try_from_bytes(&bytes).is_err());
+        let mut header = bytes.clone();
+        header[0..4].copy_from_slice(&4u32.to_le_bytes());
+        assert!(StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&header).is_err());
+        let mut header = bytes.clone();
+        header[4..8].copy_from_slice(&4u32.to_le_bytes());
+        assert!(StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&header).is_err());
+        // hash length
+        let mut header = bytes.clone();
+        header[8..12].copy_from_slice(&32u32.to_le_bytes());
+        assert!(StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&header).is_err());
+        // root
+        let mut root = bytes.clone();
+        root[12] ^= 1;
+        assert!(matches!(
+            StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&root),
+            Err(Error::Create)
+        ));
+        // top node
+        let mut top = bytes.clone();
+        let len = top.len();
+        top[len - 1] ^= 1;
+        assert!(StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&top).is_err());
+        // prefix of a leaf node, not covered by the root
+        let mut prefix = bytes.clone();
+        prefix[12 + 8] ^= 1;
+        assert!(matches!(
+            StaticTreeRef::<2, 5, StdHash, 10>::try_from_bytes(&prefix),
+            Err(Error::Create)
+        ));
+    }
+}